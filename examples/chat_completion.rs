@@ -50,23 +50,16 @@ async fn main() -> Result<()> {
         ModelIdentifier::Llama3Period18b,
         ModelIdentifier::Llama3Period370b,
     ];
+    let request = ChatCompletionRequest::builder(ModelIdentifier::Llama3Period18b)
+        .user_message("Write a haiku about programming")
+        .temperature(0.9)
+        .max_tokens(50)
+        .build();
 
-    for model in models {
+    // Both models run concurrently
+    for (model, result) in client.compare(request, models).await {
         println!("\nUsing model: {:?}", model);
-        let request = ChatCompletionRequest::builder(model)
-            .user_message("Write a haiku about programming")
-            .temperature(0.9)
-            .max_tokens(50)
-            .build();
-
-        let response = client.chat_completion(request).await?;
-        if let Some(choices) = &response.choices {
-            if let Some(first_choice) = choices.first() {
-                if let Some(message) = &first_choice.message {
-                    println!("{}", message.content);
-                }
-            }
-        }
+        println!("{}", result?.first_text()?);
     }
 
     // Example 4: JSON response format
@@ -138,8 +131,14 @@ async fn main() -> Result<()> {
             if let Some(message) = &first_choice.message {
                 println!("Response (stops at 5): {}", message.content);
             }
+            if let Some(stop) = first_choice.matched_stop() {
+                println!("Matched stop sequence: {:?}", stop);
+            }
         }
     }
+    if let Some(content) = response.strip_stop(&["5"]) {
+        println!("Response without the stop sequence: {}", content);
+    }
 
     // Print usage information
     if let Some(usage) = &response.usage {
@@ -1,6 +1,7 @@
 //! Example of function calling with the Cerebras SDK
 
-use cerebras_rs::models::{FunctionDefinition, FunctionName, Tool, ToolChoiceOption, tool};
+use cerebras_rs::ToolDispatcher;
+use cerebras_rs::models::{FunctionDefinition, Tool, ToolChoiceOption, tool};
 use cerebras_rs::prelude::*;
 use serde_json::json;
 
@@ -21,59 +22,43 @@ async fn main() -> Result<()> {
     let request = ChatCompletionRequest::builder(ModelIdentifier::Llama3Period18b)
         .user_message("What's the weather like in New York?")
         .tool(weather_function)
-        .tool_choice(ToolChoiceOption::String("auto".to_string()))
+        .tool_choice(ToolChoiceOption::auto())
         .temperature(0.3)
         .build();
 
     let response = client.chat_completion(request).await?;
 
     // Check if the model wants to call a function
-    if let Some(choices) = &response.choices {
-        if let Some(first_choice) = choices.first() {
-            if let Some(message) = &first_choice.message {
-                if let Some(tool_calls) = &message.tool_calls {
-                    for tool_call in tool_calls {
-                        println!(
-                            "Function call: {}",
-                            tool_call.name.as_ref().unwrap_or(&"unknown".to_string())
-                        );
-                        println!(
-                            "Arguments: {}",
-                            tool_call.arguments.as_ref().unwrap_or(&"{}".to_string())
-                        );
-
-                        // Simulate function execution
-                        let weather_result =
-                            simulate_weather_api(tool_call.arguments.as_deref().unwrap_or("{}"));
-
-                        // Send the function result back to the model
-                        let follow_up =
-                            ChatCompletionRequest::builder(ModelIdentifier::Llama3Period18b)
-                                .message(message.clone())
-                                .message(ChatMessage::tool(
-                                    weather_result,
-                                    format!(
-                                        "call_{}",
-                                        tool_call.name.as_ref().unwrap_or(&"unknown".to_string())
-                                    ),
-                                ))
-                                .temperature(0.3)
-                                .build();
-
-                        let final_response = client.chat_completion(follow_up).await?;
-                        if let Some(final_choices) = &final_response.choices {
-                            if let Some(final_choice) = final_choices.first() {
-                                if let Some(final_message) = &final_choice.message {
-                                    println!("Final response: {}", final_message.content);
-                                }
-                            }
-                        }
-                    }
-                } else {
-                    println!("Response: {}", message.content);
-                }
-            }
+    if response.has_tool_calls() {
+        for tool_call in response.tool_calls() {
+            println!(
+                "Function call: {}",
+                tool_call.name.as_deref().unwrap_or("unknown")
+            );
+            println!(
+                "Arguments: {}",
+                tool_call.arguments.as_deref().unwrap_or("{}")
+            );
+
+            // Simulate function execution
+            let weather_result =
+                simulate_weather_api(tool_call.arguments.as_deref().unwrap_or("{}"));
+
+            // Send the function result back to the model
+            let follow_up = ChatCompletionRequest::builder(ModelIdentifier::Llama3Period18b)
+                .messages(response.to_assistant_message())
+                .message(ChatMessage::tool(
+                    weather_result,
+                    tool_call.id.clone().unwrap_or_default(),
+                ))
+                .temperature(0.3)
+                .build();
+
+            let final_response = client.chat_completion(follow_up).await?;
+            println!("Final response: {}", final_response.first_text()?);
         }
+    } else {
+        println!("Response: {}", response.first_text()?);
     }
 
     // Example 2: Multiple functions
@@ -97,59 +82,37 @@ async fn main() -> Result<()> {
 
     let response = client.chat_completion(request).await?;
 
-    if let Some(choices) = &response.choices {
-        if let Some(first_choice) = choices.first() {
-            if let Some(message) = &first_choice.message {
-                if let Some(tool_calls) = &message.tool_calls {
-                    let mut messages = vec![message.clone()];
-
-                    for tool_call in tool_calls {
-                        println!(
-                            "\nFunction: {}",
-                            tool_call.name.as_ref().unwrap_or(&"unknown".to_string())
-                        );
-                        println!(
-                            "Arguments: {}",
-                            tool_call.arguments.as_ref().unwrap_or(&"{}".to_string())
-                        );
-
-                        let result = match tool_call.name.as_deref().unwrap_or("") {
-                            "calculate" => {
-                                simulate_calculator(tool_call.arguments.as_deref().unwrap_or("{}"))
-                            }
-                            "search_web" => {
-                                simulate_web_search(tool_call.arguments.as_deref().unwrap_or("{}"))
-                            }
-                            _ => "Unknown function".to_string(),
-                        };
-
-                        messages.push(ChatMessage::tool(
-                            result,
-                            format!(
-                                "call_{}",
-                                tool_call.name.as_ref().unwrap_or(&"unknown".to_string())
-                            ),
-                        ));
-                    }
-
-                    // Get final response with all function results
-                    let follow_up =
-                        ChatCompletionRequest::builder(ModelIdentifier::Llama3Period18b)
-                            .messages(messages)
-                            .temperature(0.3)
-                            .build();
-
-                    let final_response = client.chat_completion(follow_up).await?;
-                    if let Some(final_choices) = &final_response.choices {
-                        if let Some(final_choice) = final_choices.first() {
-                            if let Some(final_message) = &final_choice.message {
-                                println!("\nFinal response: {}", final_message.content);
-                            }
-                        }
-                    }
-                }
-            }
+    if let Some(message) = response
+        .to_assistant_message()
+        .filter(|_| response.has_tool_calls())
+    {
+        for tool_call in response.tool_calls() {
+            println!(
+                "\nFunction: {}",
+                tool_call.name.as_deref().unwrap_or("unknown")
+            );
+            println!(
+                "Arguments: {}",
+                tool_call.arguments.as_deref().unwrap_or("{}")
+            );
         }
+
+        // Run every call and collect the tool messages answering them
+        let dispatcher = ToolDispatcher::new()
+            .register("calculate", simulate_calculator)
+            .register("search_web", simulate_web_search);
+        let mut messages = vec![message.clone()];
+        messages.extend(dispatcher.dispatch(&message));
+
+        // Get final response with all function results
+        let follow_up = ChatCompletionRequest::builder(ModelIdentifier::Llama3Period18b)
+            .messages(messages)
+            .temperature(0.3)
+            .build();
+        follow_up.validate()?;
+
+        let final_response = client.chat_completion(follow_up).await?;
+        println!("\nFinal response: {}", final_response.first_text()?);
     }
 
     // Example 3: Forcing specific function use
@@ -163,31 +126,21 @@ async fn main() -> Result<()> {
     let request = ChatCompletionRequest::builder(ModelIdentifier::Llama3Period18b)
         .user_message("Tell me about Paris")
         .tool(weather_function.clone())
-        .tool_choice(ToolChoiceOption::FunctionName(FunctionName {
-            name: Some("get_weather".to_string()),
-        }))
+        .tool_choice(ToolChoiceOption::function("get_weather"))
         .temperature(0.3)
         .build();
 
     let response = client.chat_completion(request).await?;
 
-    if let Some(choices) = &response.choices {
-        if let Some(first_choice) = choices.first() {
-            if let Some(message) = &first_choice.message {
-                if let Some(tool_calls) = &message.tool_calls {
-                    if let Some(first_call) = tool_calls.first() {
-                        println!(
-                            "Forced function call: {}",
-                            first_call.name.as_ref().unwrap_or(&"unknown".to_string())
-                        );
-                        println!(
-                            "Arguments: {}",
-                            first_call.arguments.as_ref().unwrap_or(&"{}".to_string())
-                        );
-                    }
-                }
-            }
-        }
+    if let Some(first_call) = response.tool_calls().first() {
+        println!(
+            "Forced function call: {}",
+            first_call.name.as_deref().unwrap_or("unknown")
+        );
+        println!(
+            "Arguments: {}",
+            first_call.arguments.as_deref().unwrap_or("{}")
+        );
     }
 
     // Example 4: Streaming with function calls
@@ -214,20 +167,14 @@ async fn main() -> Result<()> {
         let stream = client.chat_completion_stream(request).await?;
         let complete_response = stream.collect().await?;
 
-        if let Some(choices) = &complete_response.choices {
-            if let Some(first_choice) = choices.first() {
-                if let Some(message) = &first_choice.message {
-                    if let Some(tool_calls) = &message.tool_calls {
-                        println!("Functions to call:");
-                        for tool_call in tool_calls {
-                            println!(
-                                "  - {}: {}",
-                                tool_call.name.as_ref().unwrap_or(&"unknown".to_string()),
-                                tool_call.arguments.as_ref().unwrap_or(&"{}".to_string())
-                            );
-                        }
-                    }
-                }
+        if complete_response.has_tool_calls() {
+            println!("Functions to call:");
+            for tool_call in complete_response.tool_calls() {
+                println!(
+                    "  - {}: {}",
+                    tool_call.name.as_deref().unwrap_or("unknown"),
+                    tool_call.arguments.as_deref().unwrap_or("{}")
+                );
             }
         }
     }
@@ -252,8 +199,7 @@ fn simulate_weather_api(args: &str) -> String {
     .to_string()
 }
 
-fn simulate_calculator(args: &str) -> String {
-    let parsed: serde_json::Value = serde_json::from_str(args).unwrap_or(json!({}));
+fn simulate_calculator(parsed: serde_json::Value) -> String {
     let operation = parsed["operation"].as_str().unwrap_or("add");
     let a = parsed["a"].as_f64().unwrap_or(0.0);
     let b = parsed["b"].as_f64().unwrap_or(0.0);
@@ -281,8 +227,7 @@ fn simulate_calculator(args: &str) -> String {
     .to_string()
 }
 
-fn simulate_web_search(args: &str) -> String {
-    let parsed: serde_json::Value = serde_json::from_str(args).unwrap_or(json!({}));
+fn simulate_web_search(parsed: serde_json::Value) -> String {
     let query = parsed["query"].as_str().unwrap_or("Unknown");
     let num_results = parsed["num_results"].as_u64().unwrap_or(5);
 
@@ -26,15 +26,9 @@ async fn main() -> Result<()> {
     while let Some(chunk) = stream.next().await {
         match chunk {
             Ok(chunk) => {
-                if let Some(choices) = &chunk.choices {
-                    for choice in choices {
-                        if let Some(delta) = &choice.delta {
-                            if let Some(content) = &delta.content {
-                                print!("{}", content);
-                                let _ = io::stdout().flush();
-                            }
-                        }
-                    }
+                if let Some(content) = chunk.delta_text() {
+                    print!("{}", content);
+                    let _ = io::stdout().flush();
                 }
             }
             Err(e) => eprintln!("\nError: {}", e),
@@ -60,21 +54,15 @@ async fn main() -> Result<()> {
     while let Some(chunk) = stream.next().await {
         match chunk {
             Ok(chunk) => {
-                if let Some(choices) = &chunk.choices {
-                    for choice in choices {
-                        if let Some(delta) = &choice.delta {
-                            if let Some(content) = &delta.content {
-                                print!("{}", content);
-                                let _ = io::stdout().flush();
-                                token_count += 1;
-                            }
-                        }
+                if let Some(content) = chunk.delta_text() {
+                    print!("{}", content);
+                    let _ = io::stdout().flush();
+                    token_count += 1;
+                }
 
-                        if let Some(reason) = &choice.finish_reason {
-                            println!("\n\nFinished: {:?}", reason);
-                            println!("Approximate tokens generated: {}", token_count);
-                        }
-                    }
+                if let Some(reason) = chunk.finish_reason() {
+                    println!("\n\nFinished: {:?}", reason);
+                    println!("Approximate tokens generated: {}", token_count);
                 }
             }
             Err(e) => eprintln!("\nError: {}", e),
@@ -148,15 +136,9 @@ async fn main() -> Result<()> {
     while let Some(chunk) = stream.next().await {
         match chunk {
             Ok(chunk) => {
-                if let Some(choices) = &chunk.choices {
-                    for choice in choices {
-                        if let Some(delta) = &choice.delta {
-                            if let Some(content) = &delta.content {
-                                print!("{}", content);
-                                let _ = io::stdout().flush();
-                            }
-                        }
-                    }
+                if let Some(content) = chunk.delta_text() {
+                    print!("{}", content);
+                    let _ = io::stdout().flush();
                 }
             }
             Err(e) => {
@@ -164,7 +146,7 @@ async fn main() -> Result<()> {
                 eprintln!("\nStreaming error #{}: {}", error_count, e);
 
                 // Decide whether to continue or break based on error type
-                match &e {
+                match e.root() {
                     Error::RateLimit(retry_after) => {
                         eprintln!("Rate limit hit, retry after {} seconds", retry_after);
                         break;
@@ -0,0 +1,397 @@
+//! Adaptive chat completions that recover from context and length limits
+
+use crate::{
+    Client, Error, Result,
+    chat_message::Role,
+    models::{
+        ChatCompletionRequest, ChatMessage, CompletionTokensDetails, CreateChatCompletionResponse,
+        Usage, chat_choice,
+    },
+};
+
+/// How to shorten a conversation that no longer fits in the context window
+///
+/// System messages are always kept, as is the final message of the conversation.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum TrimStrategy {
+    /// Drop the oldest non-system message, along with any tool results answering it
+    #[default]
+    OldestMessage,
+    /// Drop the oldest exchange: the first non-system message and everything up to the next user message
+    OldestTurn,
+}
+
+impl TrimStrategy {
+    /// Remove messages from `messages` according to the strategy
+    ///
+    /// Returns `false` if nothing could be removed.
+    pub fn trim(self, messages: &mut Vec<ChatMessage>) -> bool {
+        let Some(first) = messages.iter().position(|m| m.role != Role::System) else {
+            return false;
+        };
+        let last = messages.len() - 1;
+        if first >= last {
+            return false;
+        }
+
+        let mut end = first + 1;
+        match self {
+            TrimStrategy::OldestMessage => {
+                while end < last && messages[end].role == Role::Tool {
+                    end += 1;
+                }
+            }
+            TrimStrategy::OldestTurn => {
+                while end < last && messages[end].role != Role::User {
+                    end += 1;
+                }
+            }
+        }
+        messages.drain(first..end);
+        true
+    }
+}
+
+/// Options for [`Client::chat_completion_adaptive_with`]
+#[derive(Debug, Clone)]
+pub struct AdaptiveOptions {
+    /// Maximum number of trims and continuations before giving up
+    pub max_adaptations: usize,
+    /// How to shorten the conversation when the context window is exceeded
+    pub trim_strategy: TrimStrategy,
+    /// Ask the model to continue when a response is cut off by `max_tokens`
+    pub continue_on_length: bool,
+    /// User message sent to request a continuation
+    pub continuation_prompt: String,
+}
+
+impl Default for AdaptiveOptions {
+    fn default() -> Self {
+        Self {
+            max_adaptations: 3,
+            trim_strategy: TrimStrategy::default(),
+            continue_on_length: false,
+            continuation_prompt: "Continue exactly where you left off.".to_string(),
+        }
+    }
+}
+
+impl Client {
+    /// Create a chat completion, adapting the request when it hits context or length limits
+    ///
+    /// Uses [`AdaptiveOptions::default`]; see [`Client::chat_completion_adaptive_with`].
+    pub async fn chat_completion_adaptive(
+        &self,
+        request: ChatCompletionRequest,
+    ) -> Result<CreateChatCompletionResponse> {
+        self.chat_completion_adaptive_with(request, AdaptiveOptions::default())
+            .await
+    }
+
+    /// Create a chat completion, adapting the request when it hits context or length limits
+    ///
+    /// On [`Error::ContextLengthExceeded`] the oldest messages are trimmed with
+    /// the configured [`TrimStrategy`] and the request is retried. If
+    /// `continue_on_length` is set and a response stops because of
+    /// `max_tokens`, the partial answer is appended to the history and the
+    /// model is asked to continue; the pieces are stitched into one response.
+    ///
+    /// At most `max_adaptations` trims and continuations are performed. If a
+    /// continuation fails, the content gathered so far is returned.
+    ///
+    /// # Example
+    /// ```rust,no_run
+    /// use cerebras_rs::{Client, ChatCompletionRequest, ModelIdentifier};
+    /// use cerebras_rs::adaptive::{AdaptiveOptions, TrimStrategy};
+    ///
+    /// # async fn example() -> Result<(), Box<dyn std::error::Error>> {
+    /// let client = Client::from_env()?;
+    /// let request = ChatCompletionRequest::builder(ModelIdentifier::Llama3Period18b)
+    ///     .system_message("You are a helpful assistant")
+    ///     .user_message("Write a long essay about the ocean")
+    ///     .max_tokens(256)
+    ///     .build();
+    ///
+    /// let options = AdaptiveOptions {
+    ///     trim_strategy: TrimStrategy::OldestTurn,
+    ///     continue_on_length: true,
+    ///     ..Default::default()
+    /// };
+    /// let response = client.chat_completion_adaptive_with(request, options).await?;
+    /// # Ok(())
+    /// # }
+    /// ```
+    pub async fn chat_completion_adaptive_with(
+        &self,
+        request: ChatCompletionRequest,
+        options: AdaptiveOptions,
+    ) -> Result<CreateChatCompletionResponse> {
+        let mut response = self.adapt(request, options).await?;
+        if self.configuration().trim_output {
+            response.trim_whitespace();
+        }
+        Ok(response)
+    }
+
+    /// The adaptive loop, on untrimmed responses so continuations join exactly
+    async fn adapt(
+        &self,
+        mut request: ChatCompletionRequest,
+        options: AdaptiveOptions,
+    ) -> Result<CreateChatCompletionResponse> {
+        let mut adaptations = 0;
+        let mut combined: Option<CreateChatCompletionResponse> = None;
+
+        loop {
+            let response = match self.chat_completion_untrimmed(request.clone()).await {
+                Ok(response) => response,
+                Err(e)
+                    if combined.is_none()
+                        && matches!(e.root(), Error::ContextLengthExceeded(_)) =>
+                {
+                    if adaptations >= options.max_adaptations
+                        || !options.trim_strategy.trim(&mut request.messages)
+                    {
+                        return Err(e);
+                    }
+                    adaptations += 1;
+                    continue;
+                }
+                Err(e) => return combined.ok_or(e),
+            };
+
+            let truncated = response.was_truncated();
+            let partial = response
+                .choices
+                .as_ref()
+                .and_then(|c| c.first())
+                .and_then(|c| c.message.as_ref())
+                .map(|m| m.content.clone())
+                .unwrap_or_default();
+
+            let merged = match combined.take() {
+                Some(previous) => merge_continuation(previous, response),
+                None => response,
+            };
+
+            if !(options.continue_on_length && truncated && adaptations < options.max_adaptations) {
+                return Ok(merged);
+            }
+
+            adaptations += 1;
+            combined = Some(merged);
+            request.messages.push(ChatMessage::assistant(partial));
+            request
+                .messages
+                .push(ChatMessage::user(options.continuation_prompt.clone()));
+        }
+    }
+}
+
+/// Append a continuation's content and usage to the response it continues
+fn merge_continuation(
+    mut previous: CreateChatCompletionResponse,
+    next: CreateChatCompletionResponse,
+) -> CreateChatCompletionResponse {
+    let next_choice = next.choices.and_then(|c| c.into_iter().next());
+    if let Some(choice) = previous.choices.as_mut().and_then(|c| c.first_mut())
+        && let Some(next_choice) = next_choice
+    {
+        if let (Some(message), Some(next_message)) = (choice.message.as_mut(), next_choice.message)
+        {
+            message.content.push_str(&next_message.content);
+        }
+        choice.finish_reason = next_choice.finish_reason;
+    }
+
+    previous.usage = match (previous.usage, next.usage) {
+        (Some(a), Some(b)) => Some(Usage {
+            prompt_tokens: sum(a.prompt_tokens, b.prompt_tokens),
+            completion_tokens: sum(a.completion_tokens, b.completion_tokens),
+            total_tokens: sum(a.total_tokens, b.total_tokens),
+            completion_tokens_details: match (a.reasoning_tokens(), b.reasoning_tokens()) {
+                (None, None) => None,
+                (x, y) => Some(CompletionTokensDetails {
+                    reasoning_tokens: sum(x, y),
+                }),
+            },
+        }),
+        (a, b) => a.or(b),
+    };
+    previous.time_info = next.time_info.or(previous.time_info);
+    previous
+}
+
+fn sum(a: Option<i32>, b: Option<i32>) -> Option<i32> {
+    match (a, b) {
+        (Some(a), Some(b)) => Some(a.saturating_add(b)),
+        (a, b) => a.or(b),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::models::ModelIdentifier;
+    use wiremock::matchers::{method, path};
+    use wiremock::{Mock, MockServer, ResponseTemplate};
+
+    fn roles(messages: &[ChatMessage]) -> Vec<Role> {
+        messages.iter().map(|m| m.role).collect()
+    }
+
+    fn history() -> Vec<ChatMessage> {
+        vec![
+            ChatMessage::system("sys"),
+            ChatMessage::user("q1"),
+            ChatMessage::assistant("a1"),
+            ChatMessage::tool("t1", "call_1"),
+            ChatMessage::user("q2"),
+        ]
+    }
+
+    #[test]
+    fn test_trim_oldest_message() {
+        let mut messages = history();
+        assert!(TrimStrategy::OldestMessage.trim(&mut messages));
+        assert_eq!(
+            roles(&messages),
+            vec![Role::System, Role::Assistant, Role::Tool, Role::User]
+        );
+        assert!(TrimStrategy::OldestMessage.trim(&mut messages));
+        assert_eq!(roles(&messages), vec![Role::System, Role::User]);
+        assert!(!TrimStrategy::OldestMessage.trim(&mut messages));
+    }
+
+    #[test]
+    fn test_trim_oldest_turn() {
+        let mut messages = history();
+        assert!(TrimStrategy::OldestTurn.trim(&mut messages));
+        assert_eq!(roles(&messages), vec![Role::System, Role::User]);
+        assert_eq!(messages[1].content, "q2");
+        assert!(!TrimStrategy::OldestTurn.trim(&mut messages));
+    }
+
+    fn completion(content: &str, finish_reason: &str) -> serde_json::Value {
+        serde_json::json!({
+            "choices": [{
+                "index": 0,
+                "message": { "role": "assistant", "content": content },
+                "finish_reason": finish_reason
+            }],
+            "usage": { "prompt_tokens": 10, "completion_tokens": 5, "total_tokens": 15 }
+        })
+    }
+
+    #[tokio::test]
+    async fn test_adaptive_trims_on_context_length() {
+        let server = MockServer::start().await;
+        Mock::given(method("POST"))
+            .and(path("/chat/completions"))
+            .respond_with(ResponseTemplate::new(400).set_body_json(serde_json::json!({
+                "code": "context_length_exceeded",
+                "message": "Too many tokens"
+            })))
+            .up_to_n_times(1)
+            .with_priority(1)
+            .mount(&server)
+            .await;
+        Mock::given(method("POST"))
+            .and(path("/chat/completions"))
+            .respond_with(ResponseTemplate::new(200).set_body_json(completion("ok", "stop")))
+            .mount(&server)
+            .await;
+
+        let client = Client::new("test-key").with_base_url(server.uri());
+        let request = ChatCompletionRequest::new(ModelIdentifier::Llama3Period18b, history());
+        let response = client.chat_completion_adaptive(request).await.unwrap();
+        assert_eq!(
+            response.choices.unwrap()[0]
+                .message
+                .as_ref()
+                .unwrap()
+                .content,
+            "ok"
+        );
+
+        let requests = server.received_requests().await.unwrap();
+        assert_eq!(requests.len(), 2);
+        let retried: ChatCompletionRequest = serde_json::from_slice(&requests[1].body).unwrap();
+        assert_eq!(retried.messages.len(), 4);
+    }
+
+    #[tokio::test]
+    async fn test_adaptive_continues_on_length() {
+        let server = MockServer::start().await;
+        Mock::given(method("POST"))
+            .and(path("/chat/completions"))
+            .respond_with(ResponseTemplate::new(200).set_body_json(completion("Hello, ", "length")))
+            .up_to_n_times(1)
+            .with_priority(1)
+            .mount(&server)
+            .await;
+        Mock::given(method("POST"))
+            .and(path("/chat/completions"))
+            .respond_with(ResponseTemplate::new(200).set_body_json(completion("world", "stop")))
+            .mount(&server)
+            .await;
+
+        let client = Client::new("test-key").with_base_url(server.uri());
+        let request = ChatCompletionRequest::builder(ModelIdentifier::Llama3Period18b)
+            .user_message("Greet the world")
+            .build();
+        let options = AdaptiveOptions {
+            continue_on_length: true,
+            ..Default::default()
+        };
+        let response = client
+            .chat_completion_adaptive_with(request, options)
+            .await
+            .unwrap();
+
+        let choice = &response.choices.unwrap()[0];
+        assert_eq!(choice.message.as_ref().unwrap().content, "Hello, world");
+        assert_eq!(choice.finish_reason, Some(chat_choice::FinishReason::Stop));
+        assert_eq!(response.usage.unwrap().total_tokens, Some(30));
+    }
+
+    #[tokio::test]
+    async fn test_adaptive_continuation_with_trim_output() {
+        let server = MockServer::start().await;
+        Mock::given(method("POST"))
+            .and(path("/chat/completions"))
+            .respond_with(
+                ResponseTemplate::new(200).set_body_json(completion("\nOnce upon ", "length")),
+            )
+            .up_to_n_times(1)
+            .with_priority(1)
+            .mount(&server)
+            .await;
+        Mock::given(method("POST"))
+            .and(path("/chat/completions"))
+            .respond_with(ResponseTemplate::new(200).set_body_json(completion("a time\n", "stop")))
+            .mount(&server)
+            .await;
+
+        let client = Client::new("test-key")
+            .with_base_url(server.uri())
+            .with_trim_output(true);
+        let request = ChatCompletionRequest::builder(ModelIdentifier::Llama3Period18b)
+            .user_message("Tell me a story")
+            .build();
+        let options = AdaptiveOptions {
+            continue_on_length: true,
+            ..Default::default()
+        };
+        let response = client
+            .chat_completion_adaptive_with(request, options)
+            .await
+            .unwrap();
+
+        // Only the joined result is trimmed, so the space at the seam survives
+        assert_eq!(response.first_text().unwrap(), "Once upon a time");
+        let requests = server.received_requests().await.unwrap();
+        let continuation: serde_json::Value = requests[1].body_json().unwrap();
+        assert_eq!(continuation["messages"][1]["content"], "\nOnce upon ");
+    }
+}
@@ -17,10 +17,85 @@ pub struct Configuration {
     pub oauth_access_token: Option<String>,
     pub bearer_access_token: Option<String>,
     pub api_key: Option<ApiKey>,
+    /// Header to send `bearer_access_token` in as-is, instead of `Authorization: Bearer`
+    ///
+    /// For gateways that expect the key in a custom header such as `api-key`.
+    pub auth_header_name: Option<String>,
+    /// Base path override for `/chat/completions`, falling back to `base_path`
+    pub chat_base_path: Option<String>,
+    /// Base path override for `/completions`, falling back to `base_path`
+    pub completions_base_path: Option<String>,
+    /// Base path override for `/models`, falling back to `base_path`
+    pub models_base_path: Option<String>,
+    /// Use HTTP/2 without negotiation; only works against servers that speak HTTP/2
+    pub http2_prior_knowledge: bool,
+    /// Time allowed to establish a connection, separate from how long the response takes
+    pub connect_timeout: Option<std::time::Duration>,
+    /// Generate a fresh UUID request id for every request made through `Client`
+    pub generate_request_ids: bool,
+    /// Request id sent as `X-Client-Request-Id`, overriding generation
+    pub request_id: Option<String>,
+    /// Treat a stream that closes without the `[DONE]` sentinel as an error
+    ///
+    /// Off by default, since some compatible gateways never send the sentinel
+    /// and simply close the connection.
+    pub require_stream_done: bool,
+    /// Times a dropped stream may be reopened with `Last-Event-ID` to resume it
+    ///
+    /// Zero, the default, disables resumption. Only streams whose server
+    /// assigns SSE event ids can be resumed.
+    pub stream_resume_attempts: u32,
+    /// Extra root certificates trusted by `client`, e.g. a corporate proxy's CA
+    #[cfg(any(feature = "rustls-tls", feature = "native-tls"))]
+    pub root_certificates: Vec<reqwest::Certificate>,
+    /// Ask for gzip, brotli or deflate compressed responses and decode them transparently
+    ///
+    /// On by default. Streamed responses are decoded incrementally, so
+    /// compression doesn't delay server-sent events.
+    #[cfg(feature = "compression")]
+    pub compression: bool,
+    /// Maximum size of a buffered (non-streaming) response body, in bytes
+    ///
+    /// Larger responses fail with an error instead of being read into memory.
+    /// Streaming responses are consumed incrementally and aren't affected.
+    pub max_response_bytes: Option<usize>,
+    /// Context window size, in tokens, of each model; used for budgeting
+    pub context_windows: std::collections::HashMap<crate::models::ModelIdentifier, u32>,
+    /// `max_tokens` sent with requests that leave it unset, unless overridden per model
+    pub default_max_tokens: Option<u32>,
+    /// `max_tokens` sent with requests for each model that leave it unset
+    pub model_max_tokens: std::collections::HashMap<crate::models::ModelIdentifier, u32>,
+    /// Logical model names, e.g. `"fast"`, and the models they resolve to
+    pub model_aliases: std::collections::HashMap<String, crate::models::ModelIdentifier>,
+    /// Trim leading and trailing whitespace from returned message content and text
+    ///
+    /// Off by default, so output is returned exactly as generated.
+    pub trim_output: bool,
+    /// Cancelled by [`Client::shutdown`](crate::Client::shutdown) to abort every request and stream
+    ///
+    /// Clones of a configuration share the same token.
+    pub shutdown: tokio_util::sync::CancellationToken,
+}
+
+/// Endpoint categories that can be routed to their own base path
+#[derive(Clone, Copy, Debug, Eq, PartialEq, Hash)]
+pub enum EndpointCategory {
+    /// `/chat/completions`
+    Chat,
+    /// `/completions`
+    Completions,
+    /// `/models` and `/models/{model}`
+    Models,
 }
 
 pub type BasicAuth = (String, Option<String>);
 
+/// Shortest bearer token accepted by [`Configuration::validate_key`]
+const MIN_API_KEY_LEN: usize = 16;
+
+/// Header carrying the client-side request id
+pub const REQUEST_ID_HEADER: &str = "X-Client-Request-Id";
+
 #[derive(Debug, Clone)]
 pub struct ApiKey {
     pub prefix: Option<String>,
@@ -31,6 +106,167 @@ impl Configuration {
     pub fn new() -> Configuration {
         Configuration::default()
     }
+
+    /// Rebuild `client` from the transport options on this configuration
+    ///
+    /// Any custom `reqwest::Client` previously assigned to `client` is replaced.
+    pub fn rebuild_client(&mut self) -> Result<(), reqwest::Error> {
+        let mut builder = reqwest::Client::builder();
+        if self.http2_prior_knowledge {
+            builder = builder.http2_prior_knowledge();
+        }
+        if let Some(timeout) = self.connect_timeout {
+            builder = builder.connect_timeout(timeout);
+        }
+        #[cfg(feature = "compression")]
+        {
+            builder = builder
+                .gzip(self.compression)
+                .brotli(self.compression)
+                .deflate(self.compression);
+        }
+        #[cfg(any(feature = "rustls-tls", feature = "native-tls"))]
+        for certificate in &self.root_certificates {
+            builder = builder.add_root_certificate(certificate.clone());
+        }
+        self.client = builder.build()?;
+        Ok(())
+    }
+
+    /// Trust an additional root certificate, given in PEM or DER encoding
+    ///
+    /// The client is rebuilt (see [`Configuration::rebuild_client`]), so the
+    /// certificate applies to regular and streaming requests alike. If the
+    /// certificate can't be parsed, the configuration is left unchanged.
+    #[cfg(any(feature = "rustls-tls", feature = "native-tls"))]
+    pub fn add_root_certificate(&mut self, der_or_pem: &[u8]) -> crate::Result<()> {
+        let certificate = if der_or_pem.trim_ascii_start().starts_with(b"-----BEGIN") {
+            reqwest::Certificate::from_pem(der_or_pem)
+        } else {
+            reqwest::Certificate::from_der(der_or_pem)
+        }
+        .map_err(|e| crate::Error::Configuration(format!("Invalid root certificate: {}", e)))?;
+
+        self.root_certificates.push(certificate);
+        if let Err(e) = self.rebuild_client() {
+            self.root_certificates.pop();
+            return Err(crate::Error::Configuration(format!(
+                "Invalid root certificate: {}",
+                e
+            )));
+        }
+        Ok(())
+    }
+
+    /// Check that the bearer token looks like an API key, without contacting the API
+    ///
+    /// Catches a missing or blank key, stray whitespace or quotes from copying,
+    /// and values far too short to be a key. The key's prefix is not checked,
+    /// so new key formats are still accepted.
+    pub fn validate_key(&self) -> crate::Result<()> {
+        let invalid = |reason: &str| {
+            Err(crate::Error::Configuration(format!(
+                "Invalid API key: {}",
+                reason
+            )))
+        };
+        let Some(key) = self.bearer_access_token.as_deref() else {
+            return invalid("no key configured");
+        };
+
+        if key.trim().is_empty() {
+            invalid("the key is empty")
+        } else if key.chars().any(|c| c.is_whitespace() || c.is_control()) {
+            invalid("the key contains whitespace")
+        } else if key.starts_with(['"', '\'']) || key.ends_with(['"', '\'']) {
+            invalid("the key is wrapped in quotes")
+        } else if key.len() < MIN_API_KEY_LEN {
+            invalid("the key is too short")
+        } else {
+            Ok(())
+        }
+    }
+
+    /// Read a response body, enforcing `max_response_bytes`
+    pub(crate) async fn read_body<T>(
+        &self,
+        mut response: reqwest::Response,
+    ) -> Result<bytes::Bytes, super::Error<T>> {
+        let Some(max) = self.max_response_bytes else {
+            return Ok(response.bytes().await?);
+        };
+        let too_large = || {
+            super::Error::Io(std::io::Error::new(
+                std::io::ErrorKind::InvalidData,
+                format!(
+                    "Response body exceeds the configured limit of {} bytes",
+                    max
+                ),
+            ))
+        };
+
+        if response
+            .content_length()
+            .is_some_and(|len| len > max as u64)
+        {
+            return Err(too_large());
+        }
+        let mut body = Vec::new();
+        while let Some(chunk) = response.chunk().await? {
+            if body.len() + chunk.len() > max {
+                return Err(too_large());
+            }
+            body.extend_from_slice(&chunk);
+        }
+        Ok(body.into())
+    }
+
+    /// Read a response body as text, enforcing `max_response_bytes`
+    pub(crate) async fn read_text<T>(
+        &self,
+        response: reqwest::Response,
+    ) -> Result<String, super::Error<T>> {
+        let body = self.read_body(response).await?;
+        Ok(String::from_utf8_lossy(&body).into_owned())
+    }
+
+    /// Add the API key to a request, as a bearer token or under `auth_header_name`
+    pub(crate) fn apply_auth(&self, builder: reqwest::RequestBuilder) -> reqwest::RequestBuilder {
+        match (&self.bearer_access_token, &self.auth_header_name) {
+            (Some(token), Some(name)) => builder.header(name.as_str(), token.as_str()),
+            (Some(token), None) => builder.bearer_auth(token),
+            (None, _) => builder,
+        }
+    }
+
+    /// Add the configured request id header to a request, if any
+    pub(crate) fn apply_request_id(
+        &self,
+        builder: reqwest::RequestBuilder,
+    ) -> reqwest::RequestBuilder {
+        match self.request_id {
+            Some(ref id) => builder.header(REQUEST_ID_HEADER, id),
+            None => builder,
+        }
+    }
+
+    /// The `max_tokens` to fill in for requests to `model` that don't set it
+    pub fn max_tokens_for(&self, model: crate::models::ModelIdentifier) -> Option<u32> {
+        self.model_max_tokens
+            .get(&model)
+            .copied()
+            .or(self.default_max_tokens)
+    }
+
+    /// Base path to use for the given endpoint category
+    pub fn base_path_for(&self, category: EndpointCategory) -> &str {
+        let custom = match category {
+            EndpointCategory::Chat => &self.chat_base_path,
+            EndpointCategory::Completions => &self.completions_base_path,
+            EndpointCategory::Models => &self.models_base_path,
+        };
+        custom.as_deref().unwrap_or(&self.base_path)
+    }
 }
 
 impl Default for Configuration {
@@ -43,6 +279,27 @@ impl Default for Configuration {
             oauth_access_token: None,
             bearer_access_token: None,
             api_key: None,
+            auth_header_name: None,
+            chat_base_path: None,
+            completions_base_path: None,
+            models_base_path: None,
+            http2_prior_knowledge: false,
+            connect_timeout: None,
+            generate_request_ids: false,
+            request_id: None,
+            require_stream_done: false,
+            stream_resume_attempts: 0,
+            #[cfg(any(feature = "rustls-tls", feature = "native-tls"))]
+            root_certificates: Vec::new(),
+            #[cfg(feature = "compression")]
+            compression: true,
+            max_response_bytes: None,
+            context_windows: Default::default(),
+            default_max_tokens: None,
+            model_max_tokens: Default::default(),
+            model_aliases: Default::default(),
+            trim_output: false,
+            shutdown: Default::default(),
         }
     }
 }
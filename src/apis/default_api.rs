@@ -8,7 +8,10 @@
  * Generated by: https://openapi-generator.tech
  */
 
-use super::{ContentType, Error, configuration};
+use super::{
+    ContentType, Error,
+    configuration::{self, EndpointCategory},
+};
 use crate::{apis::ResponseContent, models};
 use reqwest;
 use serde::{Deserialize, Serialize, de::Error as _};
@@ -16,6 +19,7 @@ use serde::{Deserialize, Serialize, de::Error as _};
 /// struct for typed successes of method [`create_chat_completion`]
 #[derive(Debug, Clone, Serialize, Deserialize)]
 #[serde(untagged)]
+#[allow(clippy::large_enum_variant)]
 pub enum CreateChatCompletionSuccess {
     Status200(models::CreateChatCompletion200Response),
     UnknownValue(serde_json::Value),
@@ -24,6 +28,7 @@ pub enum CreateChatCompletionSuccess {
 /// struct for typed successes of method [`create_completion`]
 #[derive(Debug, Clone, Serialize, Deserialize)]
 #[serde(untagged)]
+#[allow(clippy::large_enum_variant)]
 pub enum CreateCompletionSuccess {
     Status200(models::CreateCompletion200Response),
     UnknownValue(serde_json::Value),
@@ -98,7 +103,10 @@ pub async fn create_chat_completion(
     // add a prefix to parameters to efficiently prevent name collisions
     let p_chat_completion_request = chat_completion_request;
 
-    let uri_str = format!("{}/chat/completions", configuration.base_path);
+    let uri_str = format!(
+        "{}/chat/completions",
+        configuration.base_path_for(EndpointCategory::Chat)
+    );
     let mut req_builder = configuration
         .client
         .request(reqwest::Method::POST, &uri_str);
@@ -106,29 +114,31 @@ pub async fn create_chat_completion(
     if let Some(ref user_agent) = configuration.user_agent {
         req_builder = req_builder.header(reqwest::header::USER_AGENT, user_agent.clone());
     }
-    if let Some(ref token) = configuration.bearer_access_token {
-        req_builder = req_builder.bearer_auth(token.to_owned());
-    };
+    req_builder = configuration.apply_auth(req_builder);
+    req_builder = configuration.apply_request_id(req_builder);
     req_builder = req_builder.json(&p_chat_completion_request);
 
     let req = req_builder.build()?;
     let resp = configuration.client.execute(req).await?;
 
     let status = resp.status();
+    let headers = resp.headers().clone();
 
     if !status.is_client_error() && !status.is_server_error() {
-        let content = resp.text().await?;
+        let content = configuration.read_text(resp).await?;
         let entity: Option<CreateChatCompletionSuccess> = serde_json::from_str(&content).ok();
         Ok(ResponseContent {
             status,
+            headers,
             content,
             entity,
         })
     } else {
-        let content = resp.text().await?;
+        let content = configuration.read_text(resp).await?;
         let entity: Option<CreateChatCompletionError> = serde_json::from_str(&content).ok();
         Err(Error::ResponseError(ResponseContent {
             status,
+            headers,
             content,
             entity,
         }))
@@ -143,7 +153,10 @@ pub async fn create_completion(
     // add a prefix to parameters to efficiently prevent name collisions
     let p_completion_request = completion_request;
 
-    let uri_str = format!("{}/completions", configuration.base_path);
+    let uri_str = format!(
+        "{}/completions",
+        configuration.base_path_for(EndpointCategory::Completions)
+    );
     let mut req_builder = configuration
         .client
         .request(reqwest::Method::POST, &uri_str);
@@ -151,29 +164,31 @@ pub async fn create_completion(
     if let Some(ref user_agent) = configuration.user_agent {
         req_builder = req_builder.header(reqwest::header::USER_AGENT, user_agent.clone());
     }
-    if let Some(ref token) = configuration.bearer_access_token {
-        req_builder = req_builder.bearer_auth(token.to_owned());
-    };
+    req_builder = configuration.apply_auth(req_builder);
+    req_builder = configuration.apply_request_id(req_builder);
     req_builder = req_builder.json(&p_completion_request);
 
     let req = req_builder.build()?;
     let resp = configuration.client.execute(req).await?;
 
     let status = resp.status();
+    let headers = resp.headers().clone();
 
     if !status.is_client_error() && !status.is_server_error() {
-        let content = resp.text().await?;
+        let content = configuration.read_text(resp).await?;
         let entity: Option<CreateCompletionSuccess> = serde_json::from_str(&content).ok();
         Ok(ResponseContent {
             status,
+            headers,
             content,
             entity,
         })
     } else {
-        let content = resp.text().await?;
+        let content = configuration.read_text(resp).await?;
         let entity: Option<CreateCompletionError> = serde_json::from_str(&content).ok();
         Err(Error::ResponseError(ResponseContent {
             status,
+            headers,
             content,
             entity,
         }))
@@ -184,34 +199,39 @@ pub async fn create_completion(
 pub async fn list_models(
     configuration: &configuration::Configuration,
 ) -> Result<ResponseContent<ListModelsSuccess>, Error<ListModelsError>> {
-    let uri_str = format!("{}/models", configuration.base_path);
+    let uri_str = format!(
+        "{}/models",
+        configuration.base_path_for(EndpointCategory::Models)
+    );
     let mut req_builder = configuration.client.request(reqwest::Method::GET, &uri_str);
 
     if let Some(ref user_agent) = configuration.user_agent {
         req_builder = req_builder.header(reqwest::header::USER_AGENT, user_agent.clone());
     }
-    if let Some(ref token) = configuration.bearer_access_token {
-        req_builder = req_builder.bearer_auth(token.to_owned());
-    };
+    req_builder = configuration.apply_auth(req_builder);
+    req_builder = configuration.apply_request_id(req_builder);
 
     let req = req_builder.build()?;
     let resp = configuration.client.execute(req).await?;
 
     let status = resp.status();
+    let headers = resp.headers().clone();
 
     if !status.is_client_error() && !status.is_server_error() {
-        let content = resp.text().await?;
+        let content = configuration.read_text(resp).await?;
         let entity: Option<ListModelsSuccess> = serde_json::from_str(&content).ok();
         Ok(ResponseContent {
             status,
+            headers,
             content,
             entity,
         })
     } else {
-        let content = resp.text().await?;
+        let content = configuration.read_text(resp).await?;
         let entity: Option<ListModelsError> = serde_json::from_str(&content).ok();
         Err(Error::ResponseError(ResponseContent {
             status,
+            headers,
             content,
             entity,
         }))
@@ -228,7 +248,7 @@ pub async fn retrieve_model(
 
     let uri_str = format!(
         "{}/models/{model}",
-        configuration.base_path,
+        configuration.base_path_for(EndpointCategory::Models),
         model = p_model.to_string()
     );
     let mut req_builder = configuration.client.request(reqwest::Method::GET, &uri_str);
@@ -236,28 +256,30 @@ pub async fn retrieve_model(
     if let Some(ref user_agent) = configuration.user_agent {
         req_builder = req_builder.header(reqwest::header::USER_AGENT, user_agent.clone());
     }
-    if let Some(ref token) = configuration.bearer_access_token {
-        req_builder = req_builder.bearer_auth(token.to_owned());
-    };
+    req_builder = configuration.apply_auth(req_builder);
+    req_builder = configuration.apply_request_id(req_builder);
 
     let req = req_builder.build()?;
     let resp = configuration.client.execute(req).await?;
 
     let status = resp.status();
+    let headers = resp.headers().clone();
 
     if !status.is_client_error() && !status.is_server_error() {
-        let content = resp.text().await?;
+        let content = configuration.read_text(resp).await?;
         let entity: Option<RetrieveModelSuccess> = serde_json::from_str(&content).ok();
         Ok(ResponseContent {
             status,
+            headers,
             content,
             entity,
         })
     } else {
-        let content = resp.text().await?;
+        let content = configuration.read_text(resp).await?;
         let entity: Option<RetrieveModelError> = serde_json::from_str(&content).ok();
         Err(Error::ResponseError(ResponseContent {
             status,
+            headers,
             content,
             entity,
         }))
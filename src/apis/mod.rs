@@ -4,6 +4,8 @@ use std::fmt;
 #[derive(Debug, Clone)]
 pub struct ResponseContent<T> {
     pub status: reqwest::StatusCode,
+    /// Headers of the response, e.g. `Retry-After` on a rate limit
+    pub headers: reqwest::header::HeaderMap,
     pub content: String,
     pub entity: Option<T>,
 }
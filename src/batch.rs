@@ -0,0 +1,409 @@
+//! Sending many chat completions concurrently
+
+use std::time::{Duration, Instant};
+
+use futures_util::{StreamExt, stream};
+
+use crate::{
+    Client, Result,
+    models::{ChatCompletionRequest, CreateChatCompletionResponse, ModelIdentifier, Usage},
+};
+
+/// One request's outcome in a batch, with its position and latency
+#[derive(Debug)]
+pub struct BatchItem {
+    /// Position of the request in the input
+    pub index: usize,
+    /// The response, or the error the request failed with
+    pub result: Result<CreateChatCompletionResponse>,
+    /// Time from sending the request to receiving the full response
+    pub latency: Duration,
+}
+
+/// The outcomes of every request in a batch sent to one model
+#[derive(Debug)]
+pub struct ModelBatch {
+    /// The model the requests asked for
+    pub model: ModelIdentifier,
+    /// Outcomes in input order
+    pub items: Vec<BatchItem>,
+}
+
+impl ModelBatch {
+    /// Number of requests that failed
+    pub fn failures(&self) -> usize {
+        self.items
+            .iter()
+            .filter(|item| item.result.is_err())
+            .count()
+    }
+
+    /// Mean latency over all requests, successful or not
+    pub fn mean_latency(&self) -> Option<Duration> {
+        let count = u32::try_from(self.items.len()).ok().filter(|&n| n > 0)?;
+        Some(self.items.iter().map(|item| item.latency).sum::<Duration>() / count)
+    }
+
+    /// Token usage summed over the successful responses that reported it
+    pub fn usage(&self) -> Usage {
+        let mut total = Usage::default();
+        let reported = self
+            .items
+            .iter()
+            .filter_map(|item| item.result.as_ref().ok()?.usage.as_ref());
+        for usage in reported {
+            total.prompt_tokens = sum(total.prompt_tokens, usage.prompt_tokens);
+            total.completion_tokens = sum(total.completion_tokens, usage.completion_tokens);
+            total.total_tokens = sum(total.total_tokens, usage.total_tokens);
+        }
+        total
+    }
+}
+
+fn sum(a: Option<i32>, b: Option<i32>) -> Option<i32> {
+    match (a, b) {
+        (Some(a), Some(b)) => Some(a.saturating_add(b)),
+        (a, b) => a.or(b),
+    }
+}
+
+impl Client {
+    /// Send every request, at most `concurrency` at a time, returning results in input order
+    ///
+    /// Each request is sent to its own `model`, so one batch can mix models.
+    /// A failed request doesn't stop the others. A `concurrency` of zero is
+    /// treated as one.
+    ///
+    /// # Example
+    /// ```rust,no_run
+    /// use cerebras_rs::{ChatCompletionRequest, Client, ModelIdentifier};
+    ///
+    /// # async fn example() -> Result<(), Box<dyn std::error::Error>> {
+    /// let client = Client::from_env()?;
+    /// let requests = ["Paris", "Rome", "Tokyo"].map(|city| {
+    ///     ChatCompletionRequest::builder(ModelIdentifier::Llama3Period18b)
+    ///         .user_message(format!("Describe {} in one sentence", city))
+    ///         .build()
+    /// });
+    ///
+    /// for result in client.chat_completion_batch(requests, 2).await {
+    ///     println!("{:?}", result?.choices);
+    /// }
+    /// # Ok(())
+    /// # }
+    /// ```
+    pub async fn chat_completion_batch(
+        &self,
+        requests: impl IntoIterator<Item = ChatCompletionRequest>,
+        concurrency: usize,
+    ) -> Vec<Result<CreateChatCompletionResponse>> {
+        self.timed_batch(requests, concurrency)
+            .await
+            .into_iter()
+            .map(|item| item.result)
+            .collect()
+    }
+
+    /// Send every request like [`chat_completion_batch`](Self::chat_completion_batch), keeping each one's index and latency
+    ///
+    /// Items are in input order, whatever order the responses arrive in, and
+    /// each [`BatchItem::index`] is the request's position in `requests`.
+    /// Sort by [`BatchItem::latency`] to find slow outliers.
+    ///
+    /// # Example
+    /// ```rust,no_run
+    /// use cerebras_rs::{ChatCompletionRequest, Client, ModelIdentifier};
+    ///
+    /// # async fn example() -> Result<(), Box<dyn std::error::Error>> {
+    /// let client = Client::from_env()?;
+    /// let requests = ["Paris", "Rome", "Tokyo"].map(|city| {
+    ///     ChatCompletionRequest::builder(ModelIdentifier::Llama3Period18b)
+    ///         .user_message(format!("Describe {} in one sentence", city))
+    ///         .build()
+    /// });
+    ///
+    /// let mut items = client.chat_completion_batch_timed(requests, 3).await;
+    /// items.sort_by_key(|item| std::cmp::Reverse(item.latency));
+    /// println!("Slowest: request {} took {:?}", items[0].index, items[0].latency);
+    /// # Ok(())
+    /// # }
+    /// ```
+    pub async fn chat_completion_batch_timed(
+        &self,
+        requests: impl IntoIterator<Item = ChatCompletionRequest>,
+        concurrency: usize,
+    ) -> Vec<BatchItem> {
+        self.timed_batch(requests, concurrency).await
+    }
+
+    /// Send every request like [`chat_completion_batch`](Self::chat_completion_batch), grouping the outcomes by model
+    ///
+    /// Groups are ordered by the first request for each model. Use the
+    /// [`ModelBatch`] helpers to compare latency and usage across models.
+    ///
+    /// # Example
+    /// ```rust,no_run
+    /// use cerebras_rs::{ChatCompletionRequest, Client, ModelIdentifier};
+    ///
+    /// # async fn example() -> Result<(), Box<dyn std::error::Error>> {
+    /// let client = Client::from_env()?;
+    /// let requests = [ModelIdentifier::Llama3Period18b, ModelIdentifier::Qwen332b].map(|model| {
+    ///     ChatCompletionRequest::builder(model)
+    ///         .user_message("Name three prime numbers")
+    ///         .build()
+    /// });
+    ///
+    /// for batch in client.chat_completion_batch_by_model(requests, 4).await {
+    ///     println!(
+    ///         "{}: {:?} mean, {:?} tokens, {} failed",
+    ///         batch.model,
+    ///         batch.mean_latency(),
+    ///         batch.usage().total_tokens,
+    ///         batch.failures()
+    ///     );
+    /// }
+    /// # Ok(())
+    /// # }
+    /// ```
+    pub async fn chat_completion_batch_by_model(
+        &self,
+        requests: impl IntoIterator<Item = ChatCompletionRequest>,
+        concurrency: usize,
+    ) -> Vec<ModelBatch> {
+        let requests: Vec<_> = requests.into_iter().collect();
+        let models: Vec<_> = requests.iter().map(|request| request.model).collect();
+
+        let mut batches: Vec<ModelBatch> = Vec::new();
+        for (item, model) in self
+            .timed_batch(requests, concurrency)
+            .await
+            .into_iter()
+            .zip(models)
+        {
+            match batches.iter_mut().find(|batch| batch.model == model) {
+                Some(batch) => batch.items.push(item),
+                None => batches.push(ModelBatch {
+                    model,
+                    items: vec![item],
+                }),
+            }
+        }
+        batches
+    }
+
+    /// Send the same request to each of `models` concurrently, pairing every model with its result
+    ///
+    /// The request is cloned per model with `model` overridden, so all
+    /// samples see identical messages and parameters. Results are in the
+    /// order of `models`.
+    ///
+    /// # Example
+    /// ```rust,no_run
+    /// use cerebras_rs::{ChatCompletionRequest, Client, ModelIdentifier};
+    ///
+    /// # async fn example() -> Result<(), Box<dyn std::error::Error>> {
+    /// let client = Client::from_env()?;
+    /// let request = ChatCompletionRequest::builder(ModelIdentifier::Llama3Period18b)
+    ///     .user_message("Write a haiku about programming")
+    ///     .build();
+    /// let models = vec![ModelIdentifier::Llama3Period18b, ModelIdentifier::Qwen332b];
+    ///
+    /// for (model, result) in client.compare(request, models).await {
+    ///     println!("{}: {}", model, result?.first_text()?);
+    /// }
+    /// # Ok(())
+    /// # }
+    /// ```
+    pub async fn compare(
+        &self,
+        request: ChatCompletionRequest,
+        models: Vec<ModelIdentifier>,
+    ) -> Vec<(ModelIdentifier, Result<CreateChatCompletionResponse>)> {
+        let requests = models.iter().map(|&model| ChatCompletionRequest {
+            model,
+            ..request.clone()
+        });
+        let results = self.chat_completion_batch(requests, models.len()).await;
+        models.into_iter().zip(results).collect()
+    }
+
+    /// Send every request concurrently, timing each one
+    async fn timed_batch(
+        &self,
+        requests: impl IntoIterator<Item = ChatCompletionRequest>,
+        concurrency: usize,
+    ) -> Vec<BatchItem> {
+        stream::iter(requests.into_iter().enumerate())
+            .map(|(index, request)| async move {
+                let started = Instant::now();
+                let result = self.chat_completion(request).await;
+                BatchItem {
+                    index,
+                    result,
+                    latency: started.elapsed(),
+                }
+            })
+            .buffered(concurrency.max(1))
+            .collect()
+            .await
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use wiremock::matchers::{body_partial_json, method, path};
+    use wiremock::{Mock, MockServer, ResponseTemplate};
+
+    async fn mock_model(server: &MockServer, model: &str) {
+        Mock::given(method("POST"))
+            .and(path("/chat/completions"))
+            .and(body_partial_json(serde_json::json!({ "model": model })))
+            .respond_with(ResponseTemplate::new(200).set_body_json(serde_json::json!({
+                "model": model,
+                "choices": [{
+                    "index": 0,
+                    "message": { "role": "assistant", "content": model },
+                    "finish_reason": "stop"
+                }],
+                "usage": { "prompt_tokens": 5, "completion_tokens": 2, "total_tokens": 7 }
+            })))
+            .mount(server)
+            .await;
+    }
+
+    fn request(model: ModelIdentifier) -> ChatCompletionRequest {
+        ChatCompletionRequest::builder(model)
+            .user_message("Hi")
+            .build()
+    }
+
+    #[tokio::test]
+    async fn test_batch_by_model() {
+        let server = MockServer::start().await;
+        mock_model(&server, "llama3.1-8b").await;
+        mock_model(&server, "qwen-3-32b").await;
+        let client = Client::new("test-key").with_base_url(server.uri());
+
+        let requests = [
+            request(ModelIdentifier::Qwen332b),
+            request(ModelIdentifier::Llama3Period18b),
+            request(ModelIdentifier::Qwen332b),
+            request(ModelIdentifier::Llama3Period370b),
+        ];
+
+        let results = client.chat_completion_batch(requests.clone(), 2).await;
+        let models: Vec<_> = results
+            .iter()
+            .map(|result| result.as_ref().ok().and_then(|r| r.model.as_deref()))
+            .collect();
+        assert_eq!(
+            models,
+            vec![
+                Some("qwen-3-32b"),
+                Some("llama3.1-8b"),
+                Some("qwen-3-32b"),
+                None
+            ]
+        );
+
+        let batches = client.chat_completion_batch_by_model(requests, 0).await;
+        let grouped: Vec<_> = batches
+            .iter()
+            .map(|batch| {
+                let indices: Vec<_> = batch.items.iter().map(|item| item.index).collect();
+                (batch.model, indices)
+            })
+            .collect();
+        assert_eq!(
+            grouped,
+            vec![
+                (ModelIdentifier::Qwen332b, vec![0, 2]),
+                (ModelIdentifier::Llama3Period18b, vec![1]),
+                (ModelIdentifier::Llama3Period370b, vec![3]),
+            ]
+        );
+        assert_eq!(batches[0].usage().total_tokens, Some(14));
+        assert_eq!(batches[0].failures(), 0);
+        assert!(batches[0].mean_latency().is_some());
+        // No mock answers this model
+        assert_eq!(batches[2].failures(), 1);
+        assert_eq!(batches[2].usage().total_tokens, None);
+    }
+
+    #[tokio::test]
+    async fn test_compare() {
+        let server = MockServer::start().await;
+        mock_model(&server, "llama3.1-8b").await;
+        mock_model(&server, "qwen-3-32b").await;
+        let client = Client::new("test-key").with_base_url(server.uri());
+
+        let mut request = request(ModelIdentifier::Llama3Period370b);
+        request.temperature = Some(0.2);
+        let results = client
+            .compare(
+                request,
+                vec![ModelIdentifier::Qwen332b, ModelIdentifier::Llama3Period18b],
+            )
+            .await;
+        let pairs: Vec<_> = results
+            .iter()
+            .map(|(model, result)| (*model, result.as_ref().unwrap().first_text().unwrap()))
+            .collect();
+        assert_eq!(
+            pairs,
+            vec![
+                (ModelIdentifier::Qwen332b, "qwen-3-32b"),
+                (ModelIdentifier::Llama3Period18b, "llama3.1-8b"),
+            ]
+        );
+
+        let received = server.received_requests().await.unwrap();
+        assert_eq!(received.len(), 2);
+        for request in received {
+            let body: serde_json::Value = request.body_json().unwrap();
+            assert_eq!(body["temperature"], 0.2);
+        }
+        assert!(
+            client
+                .compare(ChatCompletionRequest::default(), Vec::new())
+                .await
+                .is_empty()
+        );
+    }
+
+    #[tokio::test]
+    async fn test_batch_timed_keeps_input_order() {
+        let server = MockServer::start().await;
+        // The first request finishes last
+        Mock::given(method("POST"))
+            .and(path("/chat/completions"))
+            .and(body_partial_json(
+                serde_json::json!({ "model": "qwen-3-32b" }),
+            ))
+            .respond_with(
+                ResponseTemplate::new(200)
+                    .set_body_json(serde_json::json!({ "model": "qwen-3-32b", "choices": [] }))
+                    .set_delay(Duration::from_millis(300)),
+            )
+            .mount(&server)
+            .await;
+        mock_model(&server, "llama3.1-8b").await;
+        let client = Client::new("test-key").with_base_url(server.uri());
+
+        let requests = [
+            request(ModelIdentifier::Qwen332b),
+            request(ModelIdentifier::Llama3Period18b),
+            request(ModelIdentifier::Llama3Period18b),
+        ];
+        let items = client.chat_completion_batch_timed(requests, 3).await;
+        let indices: Vec<_> = items.iter().map(|item| item.index).collect();
+        assert_eq!(indices, vec![0, 1, 2]);
+        assert_eq!(
+            items[0].result.as_ref().unwrap().model.as_deref(),
+            Some("qwen-3-32b")
+        );
+        assert!(items[0].latency >= Duration::from_millis(300));
+        assert!(items[1].latency < items[0].latency);
+    }
+}
@@ -1,10 +1,143 @@
 //! Builder pattern for ChatCompletionRequest
 
+use std::collections::HashMap;
+use std::convert::Infallible;
+
+use serde::{Deserialize, Serialize};
+
 use crate::chat_message::Role;
 use crate::models::{
-    ChatCompletionRequest, ChatMessage, ModelIdentifier, ResponseFormat, StopCondition, Tool,
-    ToolChoiceOption,
+    ChatCompletionRequest, ChatMessage, FunctionCall, Modality, ModelIdentifier, PromptTruncation,
+    ResponseFormat, StopCondition, StreamOptions, Tool, ToolChoiceOption,
 };
+use crate::{Error, Result, StreamingRequest};
+
+/// Sampling parameters applied together with [`ChatCompletionBuilder::sampling`]
+///
+/// Deserializable, so the parameters can be loaded from a config file; missing
+/// fields are left unset.
+///
+/// # Example
+/// ```rust
+/// use cerebras_rs::builders::{ChatCompletionBuilder, Sampling};
+/// use cerebras_rs::ModelIdentifier;
+///
+/// let sampling: Sampling =
+///     serde_json::from_str(r#"{"temperature": 0.2, "presence_penalty": 0.5}"#).unwrap();
+/// let request = ChatCompletionBuilder::new(ModelIdentifier::Llama3Period18b)
+///     .user_message("Summarize this article")
+///     .sampling(sampling)
+///     .build();
+/// assert_eq!(request.temperature, Some(0.2));
+/// ```
+#[derive(Debug, Clone, Copy, Default, PartialEq, Serialize, Deserialize)]
+#[serde(default)]
+pub struct Sampling {
+    /// Sampling temperature (0.0 to 2.0)
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub temperature: Option<f64>,
+    /// Nucleus sampling parameter (0.0 to 1.0)
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub top_p: Option<f64>,
+    /// Only sample from the `top_k` most likely tokens
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub top_k: Option<u32>,
+    /// Frequency penalty (-2.0 to 2.0)
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub frequency_penalty: Option<f64>,
+    /// Presence penalty (-2.0 to 2.0)
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub presence_penalty: Option<f64>,
+}
+
+/// A prompt with `{{name}}` placeholders, filled in from a map of variables
+///
+/// Whitespace inside the braces is ignored, so `{{ name }}` works too. A
+/// `{{` without a closing `}}` is kept as written. There is no escaping,
+/// conditionals or nesting; substituted values are inserted verbatim and
+/// never expanded themselves.
+///
+/// # Example
+/// ```rust
+/// use cerebras_rs::builders::PromptTemplate;
+/// use std::collections::HashMap;
+///
+/// let template = PromptTemplate::new("You are a {{role}}. Answer in {{ language }}.");
+/// let vars = HashMap::from([
+///     ("role".to_string(), "travel agent".to_string()),
+///     ("language".to_string(), "French".to_string()),
+/// ]);
+/// assert_eq!(template.render(&vars), "You are a travel agent. Answer in French.");
+///
+/// // Missing variables are left in place by `render` and rejected by `try_render`
+/// let vars = HashMap::from([("role".to_string(), "chef".to_string())]);
+/// assert_eq!(template.render(&vars), "You are a chef. Answer in {{ language }}.");
+/// assert!(template.try_render(&vars).is_err());
+/// ```
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct PromptTemplate {
+    template: String,
+}
+
+impl PromptTemplate {
+    /// Create a template from its source text
+    pub fn new(template: impl Into<String>) -> Self {
+        Self {
+            template: template.into(),
+        }
+    }
+
+    /// The source text, placeholders included
+    pub fn as_str(&self) -> &str {
+        &self.template
+    }
+
+    /// Fill in the placeholders, leaving any without a variable as written
+    pub fn render(&self, vars: &HashMap<String, String>) -> String {
+        let Ok(rendered) = self.substitute(vars, |_| Ok::<_, Infallible>(()));
+        rendered
+    }
+
+    /// Fill in the placeholders, failing with [`Error::InvalidRequest`] on the first without a variable
+    pub fn try_render(&self, vars: &HashMap<String, String>) -> Result<String> {
+        self.substitute(vars, |name| {
+            Err(Error::InvalidRequest(format!(
+                "Prompt template variable '{}' is not set",
+                name
+            )))
+        })
+    }
+
+    /// Fill in the placeholders, calling `missing` for each without a variable
+    ///
+    /// The placeholder is kept as written if `missing` returns `Ok`.
+    fn substitute<E>(
+        &self,
+        vars: &HashMap<String, String>,
+        missing: impl Fn(&str) -> std::result::Result<(), E>,
+    ) -> std::result::Result<String, E> {
+        let mut rendered = String::with_capacity(self.template.len());
+        let mut rest = self.template.as_str();
+        while let Some(start) = rest.find("{{") {
+            let Some(len) = rest[start + 2..].find("}}") else {
+                break;
+            };
+            let end = start + 2 + len + 2;
+            let name = rest[start + 2..end - 2].trim();
+            rendered.push_str(&rest[..start]);
+            match vars.get(name) {
+                Some(value) => rendered.push_str(value),
+                None => {
+                    missing(name)?;
+                    rendered.push_str(&rest[start..end]);
+                }
+            }
+            rest = &rest[end..];
+        }
+        rendered.push_str(rest);
+        Ok(rendered)
+    }
+}
 
 /// Builder for creating ChatCompletionRequest instances
 ///
@@ -27,13 +160,25 @@ pub struct ChatCompletionBuilder {
     max_tokens: Option<u32>,
     temperature: Option<f64>,
     top_p: Option<f64>,
+    top_k: Option<u32>,
+    frequency_penalty: Option<f64>,
+    presence_penalty: Option<f64>,
+    seed: Option<i64>,
     stream: Option<bool>,
+    stream_options: Option<StreamOptions>,
     stop: Option<Vec<String>>,
+    stop_as_array: bool,
     response_format: Option<ResponseFormat>,
     tools: Option<Vec<Tool>>,
     tool_choice: Option<ToolChoiceOption>,
+    modalities: Option<Vec<Modality>>,
+    prompt_truncation: Option<PromptTruncation>,
+    date_format: Option<String>,
 }
 
+/// Date format used by [`ChatCompletionBuilder::with_current_date`]
+pub const DEFAULT_DATE_FORMAT: &str = "%Y-%m-%d";
+
 impl ChatCompletionBuilder {
     /// Create a new builder with the specified model
     pub fn new(model: ModelIdentifier) -> Self {
@@ -43,11 +188,20 @@ impl ChatCompletionBuilder {
             max_tokens: None,
             temperature: None,
             top_p: None,
+            top_k: None,
+            frequency_penalty: None,
+            presence_penalty: None,
+            seed: None,
             stream: None,
+            stream_options: None,
             stop: None,
+            stop_as_array: false,
             response_format: None,
             tools: None,
             tool_choice: None,
+            modalities: None,
+            prompt_truncation: None,
+            date_format: None,
         }
     }
 
@@ -69,6 +223,36 @@ impl ChatCompletionBuilder {
         self
     }
 
+    /// Add a system message rendered from `template`
+    ///
+    /// Fails with [`Error::InvalidRequest`] if a placeholder has no variable;
+    /// to keep unfilled placeholders instead, pass
+    /// [`template.render(vars)`](PromptTemplate::render) to
+    /// [`system_message`](Self::system_message).
+    ///
+    /// # Example
+    /// ```rust
+    /// use cerebras_rs::builders::{ChatCompletionBuilder, PromptTemplate};
+    /// use cerebras_rs::ModelIdentifier;
+    /// use std::collections::HashMap;
+    ///
+    /// let template = PromptTemplate::new("You are a {{role}}");
+    /// let vars = HashMap::from([("role".to_string(), "poet".to_string())]);
+    /// let request = ChatCompletionBuilder::new(ModelIdentifier::Llama3Period18b)
+    ///     .system_template(&template, &vars)?
+    ///     .user_message("Write about the sea")
+    ///     .build();
+    /// assert_eq!(request.messages[0].content, "You are a poet");
+    /// # Ok::<(), cerebras_rs::Error>(())
+    /// ```
+    pub fn system_template(
+        self,
+        template: &PromptTemplate,
+        vars: &HashMap<String, String>,
+    ) -> Result<Self> {
+        Ok(self.system_message(template.try_render(vars)?))
+    }
+
     /// Add a user message
     pub fn user_message(mut self, content: impl Into<String>) -> Self {
         self.messages.push(ChatMessage::user(content));
@@ -81,6 +265,94 @@ impl ChatCompletionBuilder {
         self
     }
 
+    /// Add a few-shot example as a user message followed by an assistant reply
+    pub fn example(self, user: impl Into<String>, assistant: impl Into<String>) -> Self {
+        self.user_message(user).assistant_message(assistant)
+    }
+
+    /// Add several few-shot examples, each as a user/assistant message pair
+    pub fn examples<U, A>(self, examples: impl IntoIterator<Item = (U, A)>) -> Self
+    where
+        U: Into<String>,
+        A: Into<String>,
+    {
+        examples
+            .into_iter()
+            .fold(self, |builder, (user, assistant)| {
+                builder.example(user, assistant)
+            })
+    }
+
+    /// Add a tool message for each `(tool_call_id, content)` pair, in order
+    ///
+    /// Use after the assistant message whose tool calls the results answer.
+    pub fn tool_results<I, C>(mut self, results: impl IntoIterator<Item = (I, C)>) -> Self
+    where
+        I: Into<String>,
+        C: Into<String>,
+    {
+        self.messages.extend(
+            results
+                .into_iter()
+                .map(|(id, content)| ChatMessage::tool(content.into(), id.into())),
+        );
+        self
+    }
+
+    /// Add a few-shot tool-use example: an assistant message with tool calls, then their results
+    ///
+    /// Each call is paired with the content of the tool message answering it.
+    /// Calls without an id are given one (`example_call_1`, ...), and every
+    /// tool message carries the id of its call, so the two always match.
+    /// Follow with [`assistant_message`](Self::assistant_message) to show how
+    /// the model should use the results.
+    ///
+    /// # Example
+    /// ```rust
+    /// use cerebras_rs::{ChatCompletionRequest, FunctionCall, ModelIdentifier};
+    ///
+    /// let weather = FunctionCall {
+    ///     id: None,
+    ///     name: Some("get_weather".into()),
+    ///     arguments: Some(r#"{"location": "Paris"}"#.into()),
+    /// };
+    /// let request = ChatCompletionRequest::builder(ModelIdentifier::Llama3Period18b)
+    ///     .user_message("What's the weather in Paris?")
+    ///     .tool_example([(weather, r#"{"temperature": 18}"#)])
+    ///     .assistant_message("It's 18°C in Paris.")
+    ///     .user_message("And in Rome?")
+    ///     .build();
+    ///
+    /// let call_id = request.messages[1].tool_calls.as_ref().unwrap()[0].id.clone();
+    /// assert_eq!(request.messages[2].tool_call_id, call_id);
+    /// ```
+    pub fn tool_example<R: Into<String>>(
+        mut self,
+        calls: impl IntoIterator<Item = (FunctionCall, R)>,
+    ) -> Self {
+        let mut generated = self
+            .messages
+            .iter()
+            .filter(|message| message.role == Role::Tool)
+            .count();
+        let (calls, results): (Vec<_>, Vec<_>) = calls
+            .into_iter()
+            .map(|(mut call, result)| {
+                let id = call.id.get_or_insert_with(|| {
+                    generated += 1;
+                    format!("example_call_{}", generated)
+                });
+                let result = ChatMessage::tool(result.into(), id.clone());
+                (call, result)
+            })
+            .unzip();
+
+        self.messages
+            .push(ChatMessage::assistant_with_tool_calls("", calls));
+        self.messages.extend(results);
+        self
+    }
+
     /// Set the maximum number of tokens to generate
     pub fn max_tokens(mut self, max_tokens: u32) -> Self {
         self.max_tokens = Some(max_tokens);
@@ -99,30 +371,119 @@ impl ChatCompletionBuilder {
         self
     }
 
+    /// Only sample from the `top_k` most likely tokens
+    pub fn top_k(mut self, top_k: u32) -> Self {
+        self.top_k = Some(top_k);
+        self
+    }
+
+    /// Set the frequency penalty (-2.0 to 2.0)
+    pub fn frequency_penalty(mut self, penalty: f64) -> Self {
+        self.frequency_penalty = Some(penalty);
+        self
+    }
+
+    /// Set the presence penalty (-2.0 to 2.0)
+    pub fn presence_penalty(mut self, penalty: f64) -> Self {
+        self.presence_penalty = Some(penalty);
+        self
+    }
+
+    /// Apply a set of sampling parameters at once
+    ///
+    /// Only the parameters that are `Some` are set; the others keep their
+    /// current values, so this can be combined with the individual setters.
+    pub fn sampling(mut self, sampling: Sampling) -> Self {
+        self.temperature = sampling.temperature.or(self.temperature);
+        self.top_p = sampling.top_p.or(self.top_p);
+        self.top_k = sampling.top_k.or(self.top_k);
+        self.frequency_penalty = sampling.frequency_penalty.or(self.frequency_penalty);
+        self.presence_penalty = sampling.presence_penalty.or(self.presence_penalty);
+        self
+    }
+
+    /// Set the sampling seed, for reproducible outputs
+    ///
+    /// Determinism is best-effort: compare
+    /// [`system_fingerprint`](crate::models::CreateChatCompletionResponse::system_fingerprint)
+    /// across responses to detect backend changes, and
+    /// [`seed()`](crate::models::CreateChatCompletionResponse::seed) to confirm
+    /// the seed that was applied.
+    pub fn seed(mut self, seed: i64) -> Self {
+        self.seed = Some(seed);
+        self
+    }
+
     /// Enable or disable streaming
     pub fn stream(mut self, stream: bool) -> Self {
         self.stream = Some(stream);
         self
     }
 
+    /// Ask for token usage to be reported while streaming
+    ///
+    /// Sets `stream_options.include_usage`. The final chunk then carries the
+    /// usage for the whole response, and some servers also report running
+    /// totals on intermediate chunks; see
+    /// [`ChatCompletionStream::with_usage`](crate::streaming::ChatCompletionStream::with_usage).
+    /// Has no effect on non-streaming requests.
+    pub fn include_usage(mut self, include_usage: bool) -> Self {
+        self.stream_options = Some(StreamOptions {
+            include_usage: Some(include_usage),
+        });
+        self
+    }
+
     /// Set stop sequences
+    ///
+    /// A single sequence is sent as a plain string rather than a one-element
+    /// array; use [`stop_array`](Self::stop_array) to control the wire shape.
     pub fn stop(mut self, stop: Vec<String>) -> Self {
         self.stop = Some(stop);
+        self.stop_as_array = false;
         self
     }
 
     /// Add a single stop sequence
+    ///
+    /// Collapses to a plain string when it is the only sequence, unless the
+    /// sequences were set with [`stop_array`](Self::stop_array).
     pub fn stop_sequence(mut self, sequence: impl Into<String>) -> Self {
         self.stop.get_or_insert_with(Vec::new).push(sequence.into());
         self
     }
 
+    /// Set a single stop sequence, sent as a plain string
+    pub fn stop_string(mut self, stop: impl Into<String>) -> Self {
+        self.stop = Some(vec![stop.into()]);
+        self.stop_as_array = false;
+        self
+    }
+
+    /// Set stop sequences, always sent as an array even with one element
+    pub fn stop_array(mut self, stop: Vec<String>) -> Self {
+        self.stop = Some(stop);
+        self.stop_as_array = true;
+        self
+    }
+
     /// Set the response format
     pub fn response_format(mut self, format: ResponseFormat) -> Self {
         self.response_format = Some(format);
         self
     }
 
+    /// Request plain text output
+    ///
+    /// Overrides any JSON response format set earlier, e.g. on a reused template.
+    pub fn text_response(mut self) -> Self {
+        self.response_format = Some(ResponseFormat {
+            r#type: Some(crate::response_format::Type::Text),
+            json_schema: None,
+        });
+        self
+    }
+
     /// Set JSON response format with a schema
     pub fn json_response_with_schema(mut self, schema: crate::models::JsonSchema) -> Self {
         self.response_format = Some(ResponseFormat {
@@ -133,6 +494,9 @@ impl ChatCompletionBuilder {
     }
 
     /// Set JSON response format with schema details
+    ///
+    /// [`ChatCompletionRequest::validate`] checks the schema for the most
+    /// common mistakes, such as a strict schema with open objects.
     pub fn json_schema(
         mut self,
         name: impl Into<String>,
@@ -168,17 +532,134 @@ impl ChatCompletionBuilder {
         self
     }
 
+    /// Require the model to call one of the named tools
+    ///
+    /// Cerebras has no allowed-tools form of `tool_choice`, so this narrows
+    /// `tools` to the named functions and sets `tool_choice` to `"required"`;
+    /// the model can then only pick from the subset. Call it after adding the
+    /// tools. Fails with [`Error::InvalidRequest`] if a name doesn't match any
+    /// tool, or if no names are given.
+    ///
+    /// # Example
+    /// ```rust
+    /// use cerebras_rs::{ChatCompletionRequest, ModelIdentifier};
+    /// use cerebras_rs::models::{FunctionDefinition, Tool, tool};
+    ///
+    /// let function = |name: &str| Tool {
+    ///     r#type: Some(tool::Type::Function),
+    ///     function: Some(FunctionDefinition::new(name.to_string())),
+    /// };
+    /// let request = ChatCompletionRequest::builder(ModelIdentifier::Llama3Period18b)
+    ///     .user_message("Book me a table for two")
+    ///     .tools(vec![function("search"), function("book"), function("cancel")])
+    ///     .require_tool_from(["search", "book"])?
+    ///     .build();
+    /// assert_eq!(request.tools.unwrap().len(), 2);
+    /// # Ok::<(), cerebras_rs::Error>(())
+    /// ```
+    pub fn require_tool_from<I, S>(mut self, names: I) -> Result<Self>
+    where
+        I: IntoIterator<Item = S>,
+        S: Into<String>,
+    {
+        let names: Vec<String> = names.into_iter().map(Into::into).collect();
+        if names.is_empty() {
+            return Err(Error::InvalidRequest(
+                "require_tool_from needs at least one tool name".into(),
+            ));
+        }
+        let tools = self.tools.take().unwrap_or_default();
+        let tool_name = |tool: &Tool| tool.function.as_ref().map(|f| f.name.clone());
+        if let Some(missing) = names.iter().find(|name| {
+            !tools
+                .iter()
+                .any(|tool| tool_name(tool).as_ref() == Some(name))
+        }) {
+            return Err(Error::InvalidRequest(format!(
+                "require_tool_from names unknown tool '{}'",
+                missing
+            )));
+        }
+
+        self.tools = Some(
+            tools
+                .into_iter()
+                .filter(|tool| tool_name(tool).is_some_and(|name| names.contains(&name)))
+                .collect(),
+        );
+        self.tool_choice = Some(ToolChoiceOption::required());
+        Ok(self)
+    }
+
+    /// Set the output modalities, e.g. `[Modality::Text, Modality::Audio]`
+    ///
+    /// Omitted by default, which means text only. The models currently served
+    /// by Cerebras only produce text; audio output, when supported by a model,
+    /// arrives in the message's `audio` field.
+    pub fn modalities(mut self, modalities: impl IntoIterator<Item = Modality>) -> Self {
+        self.modalities = Some(modalities.into_iter().collect());
+        self
+    }
+
+    /// Set how the server handles a prompt that exceeds the context window
+    ///
+    /// Omitted by default. With [`PromptTruncation::Auto`] the server may drop
+    /// the oldest messages instead of rejecting the request. Cerebras does not
+    /// document which models honor this, so deployments that ignore it still
+    /// fail with [`Error::ContextLengthExceeded`](crate::Error::ContextLengthExceeded);
+    /// handle that with [`Client::chat_completion_adaptive`](crate::Client::chat_completion_adaptive),
+    /// which trims on the client side.
+    pub fn prompt_truncation(mut self, truncation: PromptTruncation) -> Self {
+        self.prompt_truncation = Some(truncation);
+        self
+    }
+
+    /// Tell the model today's date in a system message
+    ///
+    /// Uses [`DEFAULT_DATE_FORMAT`]; see
+    /// [`with_current_date_format`](Self::with_current_date_format).
+    pub fn with_current_date(self) -> Self {
+        self.with_current_date_format(DEFAULT_DATE_FORMAT)
+    }
+
+    /// Tell the model today's date, formatted with a `chrono` format string
+    ///
+    /// When the request is built, a system message such as
+    /// `Current date: 2025-01-31` is inserted after any leading system
+    /// messages, using the local time zone. Only one date message is added,
+    /// however often this is called; the last format wins. An invalid format
+    /// falls back to [`DEFAULT_DATE_FORMAT`].
+    pub fn with_current_date_format(mut self, format: impl Into<String>) -> Self {
+        self.date_format = Some(format.into());
+        self
+    }
+
     /// Build the ChatCompletionRequest
-    pub fn build(self) -> ChatCompletionRequest {
+    pub fn build(mut self) -> ChatCompletionRequest {
+        if let Some(format) = self.date_format.take() {
+            let position = self
+                .messages
+                .iter()
+                .take_while(|message| message.role == Role::System)
+                .count();
+            self.messages
+                .insert(position, ChatMessage::system(current_date(&format)));
+        }
+
         ChatCompletionRequest {
             model: self.model,
             messages: self.messages,
             max_tokens: self.max_tokens,
             temperature: self.temperature,
             top_p: self.top_p,
+            top_k: self.top_k,
+            frequency_penalty: self.frequency_penalty,
+            presence_penalty: self.presence_penalty,
+            seed: self.seed,
             stream: self.stream,
+            stream_options: self.stream_options,
             stop: self.stop.map(|s| {
-                if s.len() == 1 {
+                if s.len() == 1 && !self.stop_as_array {
                     StopCondition::String(s.into_iter().next().unwrap())
                 } else {
                     StopCondition::Array(s)
@@ -187,10 +668,35 @@ impl ChatCompletionBuilder {
             response_format: self.response_format,
             tools: self.tools,
             tool_choice: self.tool_choice,
+            modalities: self.modalities,
+            prompt_truncation: self.prompt_truncation,
         }
     }
 }
 
+/// The system message content announcing today's date in `format`
+fn current_date(format: &str) -> String {
+    use std::fmt::Write;
+
+    let today = chrono::Local::now();
+    let mut content = String::from("Current date: ");
+    if write!(content, "{}", today.format(format)).is_err() {
+        content.truncate("Current date: ".len());
+        let _ = write!(content, "{}", today.format(DEFAULT_DATE_FORMAT));
+    }
+    content
+}
+
+impl ChatCompletionBuilder {
+    /// Build a request for [`Client::chat_completion_stream`](crate::Client::chat_completion_stream)
+    ///
+    /// The result can't be passed to the non-streaming
+    /// [`Client::chat_completion`](crate::Client::chat_completion).
+    pub fn build_streaming(self) -> StreamingRequest {
+        self.build().into()
+    }
+}
+
 impl ChatCompletionRequest {
     /// Create a new builder for this request type
     pub fn builder(model: ModelIdentifier) -> ChatCompletionBuilder {
@@ -202,6 +708,40 @@ impl ChatCompletionRequest {
 mod tests {
     use super::*;
 
+    #[test]
+    fn test_prompt_template() {
+        let vars = HashMap::from([
+            ("name".to_string(), "Ada".to_string()),
+            ("topic".to_string(), "{{name}}".to_string()),
+        ]);
+        let template = PromptTemplate::new("Hi {{name}}, {{ topic }} and {{name}}");
+        assert_eq!(template.render(&vars), "Hi Ada, {{name}} and Ada");
+
+        // Unknown and unclosed placeholders
+        let template = PromptTemplate::new("{{missing}} {{name}} {{ unclosed");
+        assert_eq!(template.render(&vars), "{{missing}} Ada {{ unclosed");
+        assert!(matches!(
+            template.try_render(&vars),
+            Err(Error::InvalidRequest(message)) if message.contains("'missing'")
+        ));
+        assert_eq!(
+            PromptTemplate::new("no {placeholders}").render(&HashMap::new()),
+            "no {placeholders}"
+        );
+
+        let request = ChatCompletionBuilder::new(ModelIdentifier::Llama3Period18b)
+            .system_template(&PromptTemplate::new("You help {{name}}"), &vars)
+            .unwrap()
+            .build();
+        assert_eq!(request.messages[0].content, "You help Ada");
+        assert_eq!(request.messages[0].role, Role::System);
+        assert!(
+            ChatCompletionBuilder::new(ModelIdentifier::Llama3Period18b)
+                .system_template(&PromptTemplate::new("{{missing}}"), &vars)
+                .is_err()
+        );
+    }
+
     #[test]
     fn test_builder_basic() {
         let request = ChatCompletionBuilder::new(ModelIdentifier::Llama3Period18b)
@@ -229,4 +769,321 @@ mod tests {
         assert_eq!(request.messages[2].role, Role::Assistant);
         assert_eq!(request.messages[3].role, Role::User);
     }
+
+    #[test]
+    fn test_builder_few_shot_examples() {
+        let request = ChatCompletionBuilder::new(ModelIdentifier::Llama3Period18b)
+            .system_message("Translate English to French")
+            .example("cheese", "fromage")
+            .examples(vec![("dog", "chien"), ("cat", "chat")])
+            .user_message("bread")
+            .build();
+
+        let roles: Vec<_> = request.messages.iter().map(|m| m.role).collect();
+        assert_eq!(
+            roles,
+            vec![
+                Role::System,
+                Role::User,
+                Role::Assistant,
+                Role::User,
+                Role::Assistant,
+                Role::User,
+                Role::Assistant,
+                Role::User,
+            ]
+        );
+        assert_eq!(request.messages[3].content, "dog");
+        assert_eq!(request.messages[4].content, "chien");
+    }
+
+    #[test]
+    fn test_builder_text_response_overrides_schema() {
+        let request = ChatCompletionBuilder::new(ModelIdentifier::Llama3Period18b)
+            .json_schema("answer", serde_json::json!({"type": "object"}), true)
+            .text_response()
+            .user_message("Hi")
+            .build();
+
+        let format = request.response_format.unwrap();
+        assert_eq!(format.r#type, Some(crate::response_format::Type::Text));
+        assert!(format.json_schema.is_none());
+    }
+
+    #[test]
+    fn test_builder_stop_shape() {
+        let build = |builder: ChatCompletionBuilder| builder.user_message("Hi").build().stop;
+        let builder = || ChatCompletionBuilder::new(ModelIdentifier::Llama3Period18b);
+
+        assert_eq!(
+            build(builder().stop_sequence("END")),
+            Some(StopCondition::String("END".into()))
+        );
+        assert_eq!(
+            build(builder().stop_string("END")),
+            Some(StopCondition::String("END".into()))
+        );
+        assert_eq!(
+            build(builder().stop_array(vec!["END".into()])),
+            Some(StopCondition::Array(vec!["END".into()]))
+        );
+        assert_eq!(
+            build(
+                builder()
+                    .stop_array(vec!["END".into()])
+                    .stop(vec!["X".into()])
+            ),
+            Some(StopCondition::String("X".into()))
+        );
+    }
+
+    #[test]
+    fn test_builder_sampling() {
+        let request = ChatCompletionBuilder::new(ModelIdentifier::Llama3Period18b)
+            .user_message("Hello")
+            .temperature(0.9)
+            .max_tokens(10)
+            .sampling(Sampling {
+                top_p: Some(0.8),
+                top_k: Some(40),
+                frequency_penalty: Some(0.5),
+                ..Default::default()
+            })
+            .presence_penalty(-0.5)
+            .build();
+
+        assert_eq!(request.temperature, Some(0.9));
+        assert_eq!(request.top_p, Some(0.8));
+        assert_eq!(request.top_k, Some(40));
+        assert_eq!(request.frequency_penalty, Some(0.5));
+        assert_eq!(request.presence_penalty, Some(-0.5));
+        assert_eq!(request.max_tokens, Some(10));
+
+        let json = serde_json::to_value(&request).unwrap();
+        assert_eq!(json["top_k"], 40);
+        assert_eq!(json["frequency_penalty"], 0.5);
+    }
+
+    #[test]
+    fn test_builder_modalities() {
+        let request = ChatCompletionBuilder::new(ModelIdentifier::Llama3Period18b)
+            .user_message("Say hello")
+            .build();
+        assert!(
+            serde_json::to_value(&request)
+                .unwrap()
+                .get("modalities")
+                .is_none()
+        );
+
+        let request = ChatCompletionBuilder::new(ModelIdentifier::Llama3Period18b)
+            .user_message("Say hello")
+            .modalities([Modality::Text, Modality::Audio])
+            .build();
+        assert_eq!(
+            serde_json::to_value(&request).unwrap()["modalities"],
+            serde_json::json!(["text", "audio"])
+        );
+
+        let message: ChatMessage = serde_json::from_value(serde_json::json!({
+            "role": "assistant",
+            "content": "",
+            "audio": { "id": "audio_1", "data": "UklGRg==", "transcript": "Hello" }
+        }))
+        .unwrap();
+        assert_eq!(message.audio.unwrap().transcript.as_deref(), Some("Hello"));
+    }
+
+    #[test]
+    fn test_builder_tool_results() {
+        let request = ChatCompletionBuilder::new(ModelIdentifier::Llama3Period18b)
+            .user_message("Add 1 and 2, then look up the weather")
+            .assistant_message("")
+            .tool_results(vec![
+                ("call_1".to_string(), "3".to_string()),
+                ("call_2".to_string(), "Sunny".to_string()),
+            ])
+            .build();
+
+        assert_eq!(request.messages.len(), 4);
+        assert_eq!(request.messages[2].role, Role::Tool);
+        assert_eq!(request.messages[2].tool_call_id.as_deref(), Some("call_1"));
+        assert_eq!(request.messages[2].content, "3");
+        assert_eq!(request.messages[3].tool_call_id.as_deref(), Some("call_2"));
+        assert_eq!(request.messages[3].content, "Sunny");
+    }
+
+    #[test]
+    fn test_builder_prompt_truncation() {
+        let request = ChatCompletionBuilder::new(ModelIdentifier::Llama3Period18b)
+            .user_message("Summarize this")
+            .build();
+        assert_eq!(request.prompt_truncation, None);
+
+        let request = ChatCompletionBuilder::new(ModelIdentifier::Llama3Period18b)
+            .user_message("Summarize this")
+            .prompt_truncation(PromptTruncation::Auto)
+            .build();
+        assert_eq!(
+            serde_json::to_value(&request).unwrap()["prompt_truncation"],
+            "AUTO"
+        );
+    }
+
+    #[test]
+    fn test_builder_include_usage() {
+        let request = ChatCompletionBuilder::new(ModelIdentifier::Llama3Period18b)
+            .user_message("Hi")
+            .include_usage(true)
+            .build_streaming();
+        assert_eq!(
+            serde_json::to_value(&*request).unwrap()["stream_options"],
+            serde_json::json!({ "include_usage": true })
+        );
+
+        // Stream options are only valid on streamed requests
+        let mut request = request.into_inner();
+        request.stream = None;
+        let request: crate::NonStreamingRequest = request.into();
+        assert_eq!(request.stream_options, None);
+    }
+
+    #[test]
+    fn test_builder_current_date() {
+        let today = chrono::Local::now().format(DEFAULT_DATE_FORMAT).to_string();
+        let request = ChatCompletionBuilder::new(ModelIdentifier::Llama3Period18b)
+            .system_message("You are a helpful assistant")
+            .user_message("What day is it?")
+            .with_current_date()
+            .with_current_date()
+            .build();
+        assert_eq!(request.messages.len(), 3);
+        assert_eq!(request.messages[0].content, "You are a helpful assistant");
+        assert_eq!(request.messages[1].role, Role::System);
+        assert_eq!(
+            request.messages[1].content,
+            format!("Current date: {}", today)
+        );
+
+        let year = chrono::Local::now().format("%Y").to_string();
+        let request = ChatCompletionBuilder::new(ModelIdentifier::Llama3Period18b)
+            .user_message("What year is it?")
+            .with_current_date_format("%Y")
+            .build();
+        assert_eq!(
+            request.messages[0].content,
+            format!("Current date: {}", year)
+        );
+
+        let request = ChatCompletionBuilder::new(ModelIdentifier::Llama3Period18b)
+            .with_current_date_format("%Q")
+            .build();
+        assert_eq!(
+            request.messages[0].content,
+            format!("Current date: {}", today)
+        );
+    }
+
+    #[test]
+    fn test_builder_seed() {
+        let request = ChatCompletionBuilder::new(ModelIdentifier::Llama3Period18b)
+            .user_message("Pick a number")
+            .seed(42)
+            .build();
+        assert_eq!(serde_json::to_value(&request).unwrap()["seed"], 42);
+
+        let merged = ChatCompletionRequest::merge(
+            request,
+            ChatCompletionRequest::new(ModelIdentifier::Llama3Period18b, Vec::new()),
+            crate::MessageMerge::Append,
+        );
+        assert_eq!(merged.seed, Some(42));
+    }
+
+    #[test]
+    fn test_builder_require_tool_from() {
+        use crate::models::{FunctionDefinition, tool};
+
+        let function = |name: &str| Tool {
+            r#type: Some(tool::Type::Function),
+            function: Some(FunctionDefinition::new(name.to_string())),
+        };
+        let builder = ChatCompletionBuilder::new(ModelIdentifier::Llama3Period18b)
+            .user_message("Hi")
+            .tools(vec![function("a"), function("b"), function("c")]);
+
+        let request = builder
+            .clone()
+            .require_tool_from(["c", "a"])
+            .unwrap()
+            .build();
+        let names: Vec<_> = request
+            .tools
+            .iter()
+            .flatten()
+            .map(|tool| tool.function.as_ref().unwrap().name.as_str())
+            .collect();
+        assert_eq!(names, vec!["a", "c"]);
+        assert_eq!(
+            serde_json::to_value(&request).unwrap()["tool_choice"],
+            "required"
+        );
+
+        match builder.clone().require_tool_from(["a", "d"]) {
+            Err(Error::InvalidRequest(message)) => assert!(message.contains("'d'")),
+            other => panic!("Expected InvalidRequest, got {:?}", other),
+        }
+        assert!(builder.require_tool_from(Vec::<String>::new()).is_err());
+    }
+
+    #[test]
+    fn test_builder_tool_example() {
+        let call = |id: Option<&str>, name: &str| FunctionCall {
+            id: id.map(str::to_string),
+            name: Some(name.to_string()),
+            arguments: Some("{}".to_string()),
+        };
+        let request = ChatCompletionBuilder::new(ModelIdentifier::Llama3Period18b)
+            .user_message("Time and weather?")
+            .tool_example([
+                (call(None, "time"), "12:00"),
+                (call(Some("w"), "weather"), "Sunny"),
+            ])
+            .assistant_message("It's noon and sunny.")
+            .tool_example([(call(None, "time"), "13:00")])
+            .build();
+
+        let roles: Vec<_> = request.messages.iter().map(|m| m.role).collect();
+        assert_eq!(
+            roles,
+            vec![
+                Role::User,
+                Role::Assistant,
+                Role::Tool,
+                Role::Tool,
+                Role::Assistant,
+                Role::Assistant,
+                Role::Tool
+            ]
+        );
+        let ids: Vec<_> = request.messages[1]
+            .tool_calls
+            .iter()
+            .flatten()
+            .map(|call| call.id.as_deref())
+            .collect();
+        assert_eq!(ids, vec![Some("example_call_1"), Some("w")]);
+        assert_eq!(
+            request.messages[2].tool_call_id.as_deref(),
+            Some("example_call_1")
+        );
+        assert_eq!(request.messages[3].tool_call_id.as_deref(), Some("w"));
+        assert_eq!(request.messages[3].content, "Sunny");
+        // Generated ids stay unique across examples
+        assert_eq!(
+            request.messages[6].tool_call_id.as_deref(),
+            Some("example_call_3")
+        );
+        assert!(request.validate().is_ok());
+    }
 }
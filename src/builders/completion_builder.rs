@@ -1,6 +1,69 @@
 //! Builder pattern for CompletionRequest
 
-use crate::models::{CompletionRequest, ModelIdentifier, Prompt, StopCondition};
+use crate::{
+    Error, Result,
+    chat_message::Role,
+    models::{
+        ChatMessage, CompletionRequest, ModelIdentifier, Prompt, StopCondition, StreamOptions,
+    },
+};
+
+/// A role-tagged template for rendering chat messages into a completion prompt
+///
+/// Each message is rendered as `message_prefix`, its content, then
+/// `message_suffix`, with `{role}` in either replaced by the message's role
+/// (`system`, `user`, `assistant` or `tool`). `generation_prompt` is appended
+/// after the last message to cue the model's reply. The default renders:
+///
+/// ```text
+/// <|system|>
+/// You are terse
+/// <|user|>
+/// Hi
+/// <|assistant|>
+/// ```
+///
+/// Models are trained on their own template, so for best results set the
+/// fields to match the model's, e.g. its special tokens.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct ChatTemplate {
+    /// Text before each message's content
+    pub message_prefix: String,
+    /// Text after each message's content
+    pub message_suffix: String,
+    /// Text after the last message, where the model continues
+    pub generation_prompt: String,
+}
+
+impl Default for ChatTemplate {
+    fn default() -> Self {
+        Self {
+            message_prefix: "<|{role}|>\n".to_string(),
+            message_suffix: "\n".to_string(),
+            generation_prompt: "<|assistant|>\n".to_string(),
+        }
+    }
+}
+
+impl ChatTemplate {
+    /// Render `messages` into a single prompt string
+    pub fn render(&self, messages: &[ChatMessage]) -> String {
+        let mut prompt = String::new();
+        for message in messages {
+            let role = match message.role {
+                Role::System => "system",
+                Role::User => "user",
+                Role::Assistant => "assistant",
+                Role::Tool => "tool",
+            };
+            prompt.push_str(&self.message_prefix.replace("{role}", role));
+            prompt.push_str(&message.content);
+            prompt.push_str(&self.message_suffix.replace("{role}", role));
+        }
+        prompt.push_str(&self.generation_prompt);
+        prompt
+    }
+}
 
 /// Builder for creating CompletionRequest instances
 ///
@@ -22,9 +85,15 @@ pub struct CompletionBuilder {
     max_tokens: Option<u32>,
     temperature: Option<f64>,
     top_p: Option<f64>,
+    frequency_penalty: Option<f64>,
+    presence_penalty: Option<f64>,
     stream: Option<bool>,
+    stream_options: Option<StreamOptions>,
     stop: Option<Vec<String>>,
+    stop_as_array: bool,
     return_raw_tokens: Option<bool>,
+    suffix: Option<String>,
+    logprobs: Option<u8>,
 }
 
 impl CompletionBuilder {
@@ -36,9 +105,15 @@ impl CompletionBuilder {
             max_tokens: None,
             temperature: None,
             top_p: None,
+            frequency_penalty: None,
+            presence_penalty: None,
             stream: None,
+            stream_options: None,
             stop: None,
+            stop_as_array: false,
             return_raw_tokens: None,
+            suffix: None,
+            logprobs: None,
         }
     }
 
@@ -55,6 +130,46 @@ impl CompletionBuilder {
         self
     }
 
+    /// Set the prompt to `messages` rendered with the default [`ChatTemplate`]
+    ///
+    /// For sending chat-style input to the completions endpoint, e.g. for
+    /// models or gateways that only serve raw completions. Use
+    /// [`chat_template_with`](Self::chat_template_with) to match the model's
+    /// own template.
+    ///
+    /// # Example
+    /// ```rust
+    /// use cerebras_rs::builders::CompletionBuilder;
+    /// use cerebras_rs::models::Prompt;
+    /// use cerebras_rs::{ChatMessage, ModelIdentifier};
+    ///
+    /// let request = CompletionBuilder::new(ModelIdentifier::Llama3Period18b)
+    ///     .chat_template(&[ChatMessage::system("You are terse"), ChatMessage::user("Hi")])
+    ///     .build();
+    /// assert_eq!(
+    ///     request.prompt,
+    ///     Prompt::String("<|system|>\nYou are terse\n<|user|>\nHi\n<|assistant|>\n".into())
+    /// );
+    /// ```
+    pub fn chat_template(self, messages: &[ChatMessage]) -> Self {
+        self.chat_template_with(&ChatTemplate::default(), messages)
+    }
+
+    /// Set the prompt to `messages` rendered with `template`
+    pub fn chat_template_with(mut self, template: &ChatTemplate, messages: &[ChatMessage]) -> Self {
+        self.prompt = Some(Prompt::String(template.render(messages)));
+        self
+    }
+
+    /// Set the prompt as token ids, skipping tokenization on the server
+    ///
+    /// The ids must come from the model's own tokenizer, e.g. the raw tokens
+    /// returned with [`return_raw_tokens`](Self::return_raw_tokens).
+    pub fn prompt_tokens(mut self, tokens: Vec<u32>) -> Self {
+        self.prompt = Some(Prompt::Tokens(tokens));
+        self
+    }
+
     /// Set the maximum number of tokens to generate
     pub fn max_tokens(mut self, max_tokens: u32) -> Self {
         self.max_tokens = Some(max_tokens);
@@ -73,30 +188,123 @@ impl CompletionBuilder {
         self
     }
 
+    /// Set the frequency penalty (-2.0 to 2.0)
+    pub fn frequency_penalty(mut self, penalty: f64) -> Self {
+        self.frequency_penalty = Some(penalty);
+        self
+    }
+
+    /// Set the presence penalty (-2.0 to 2.0)
+    pub fn presence_penalty(mut self, penalty: f64) -> Self {
+        self.presence_penalty = Some(penalty);
+        self
+    }
+
     /// Enable or disable streaming
     pub fn stream(mut self, stream: bool) -> Self {
         self.stream = Some(stream);
         self
     }
 
+    /// Ask for token usage to be reported while streaming
+    ///
+    /// Sets `stream_options.include_usage`, so the collected
+    /// [`Completion`](crate::models::Completion) carries `usage`. Has no
+    /// effect on non-streaming requests.
+    pub fn include_usage(mut self, include_usage: bool) -> Self {
+        self.stream_options = Some(StreamOptions {
+            include_usage: Some(include_usage),
+        });
+        self
+    }
+
     /// Set stop sequences
+    ///
+    /// A single sequence is sent as a plain string rather than a one-element
+    /// array; use [`stop_array`](Self::stop_array) to control the wire shape.
     pub fn stop(mut self, stop: Vec<String>) -> Self {
         self.stop = Some(stop);
+        self.stop_as_array = false;
         self
     }
 
     /// Add a single stop sequence
+    ///
+    /// Collapses to a plain string when it is the only sequence, unless the
+    /// sequences were set with [`stop_array`](Self::stop_array).
     pub fn stop_sequence(mut self, sequence: impl Into<String>) -> Self {
         self.stop.get_or_insert_with(Vec::new).push(sequence.into());
         self
     }
 
+    /// Set a single stop sequence, sent as a plain string
+    pub fn stop_string(mut self, stop: impl Into<String>) -> Self {
+        self.stop = Some(vec![stop.into()]);
+        self.stop_as_array = false;
+        self
+    }
+
+    /// Set stop sequences, always sent as an array even with one element
+    pub fn stop_array(mut self, stop: Vec<String>) -> Self {
+        self.stop = Some(stop);
+        self.stop_as_array = true;
+        self
+    }
+
     /// Return raw tokens instead of text
     pub fn return_raw_tokens(mut self, return_raw: bool) -> Self {
         self.return_raw_tokens = Some(return_raw);
         self
     }
 
+    /// Set the text that follows the completion, for fill-in-the-middle
+    ///
+    /// Only models trained for infilling (typically code models) honor the
+    /// suffix; other models may reject the request or ignore it.
+    pub fn suffix(mut self, suffix: impl Into<String>) -> Self {
+        self.suffix = Some(suffix.into());
+        self
+    }
+
+    /// Return log probabilities for the `n` most likely tokens at each position
+    ///
+    /// The results are in each choice's `logprobs`. Pass `0` to get only the
+    /// log probabilities of the generated tokens.
+    pub fn logprobs(mut self, n: u8) -> Self {
+        self.logprobs = Some(n);
+        self
+    }
+
+    /// Build the CompletionRequest, checking that the penalties are within -2.0 to 2.0
+    ///
+    /// # Example
+    /// ```rust
+    /// use cerebras_rs::builders::CompletionBuilder;
+    /// use cerebras_rs::ModelIdentifier;
+    ///
+    /// let result = CompletionBuilder::new(ModelIdentifier::Llama3Period18b)
+    ///     .prompt("Once upon a time")
+    ///     .frequency_penalty(3.0)
+    ///     .try_build();
+    /// assert!(result.is_err());
+    /// ```
+    pub fn try_build(self) -> Result<CompletionRequest> {
+        for (name, penalty) in [
+            ("frequency_penalty", self.frequency_penalty),
+            ("presence_penalty", self.presence_penalty),
+        ] {
+            if let Some(penalty) = penalty
+                && !(-2.0..=2.0).contains(&penalty)
+            {
+                return Err(Error::InvalidRequest(format!(
+                    "{} must be between -2.0 and 2.0, got {}",
+                    name, penalty
+                )));
+            }
+        }
+        Ok(self.build())
+    }
+
     /// Build the CompletionRequest
     pub fn build(self) -> CompletionRequest {
         CompletionRequest {
@@ -105,15 +313,20 @@ impl CompletionBuilder {
             max_tokens: self.max_tokens,
             temperature: self.temperature,
             top_p: self.top_p,
+            frequency_penalty: self.frequency_penalty,
+            presence_penalty: self.presence_penalty,
             stream: self.stream,
+            stream_options: self.stream_options,
             stop: self.stop.map(|s| {
-                if s.len() == 1 {
+                if s.len() == 1 && !self.stop_as_array {
                     StopCondition::String(s.into_iter().next().unwrap())
                 } else {
                     StopCondition::Array(s)
                 }
             }),
             return_raw_tokens: self.return_raw_tokens,
+            suffix: self.suffix,
+            logprobs: self.logprobs,
         }
     }
 }
@@ -128,6 +341,7 @@ impl CompletionRequest {
 #[cfg(test)]
 mod tests {
     use super::*;
+    use crate::models::CompletionChoice;
 
     #[test]
     fn test_builder_basic() {
@@ -145,6 +359,22 @@ mod tests {
         assert_eq!(request.max_tokens, Some(50));
     }
 
+    #[test]
+    fn test_builder_prompt_tokens() {
+        let request = CompletionBuilder::new(ModelIdentifier::Llama3Period18b)
+            .prompt_tokens(vec![9906, 1917])
+            .build();
+        assert_eq!(
+            serde_json::to_value(&request).unwrap()["prompt"],
+            serde_json::json!([9906, 1917])
+        );
+
+        let arrays: Prompt = serde_json::from_str("[[1, 2], [3]]").unwrap();
+        assert_eq!(arrays, Prompt::TokenArrays(vec![vec![1, 2], vec![3]]));
+        let text: Prompt = serde_json::from_str(r#"["a", "b"]"#).unwrap();
+        assert!(matches!(text, Prompt::Array(_)));
+    }
+
     #[test]
     fn test_builder_multiple_prompts() {
         let request = CompletionBuilder::new(ModelIdentifier::Llama3Period18b)
@@ -158,4 +388,124 @@ mod tests {
             _ => panic!("Expected string prompt"),
         }
     }
+
+    #[test]
+    fn test_builder_chat_template() {
+        let messages = [
+            ChatMessage::system("Be brief"),
+            ChatMessage::user("Hi"),
+            ChatMessage::assistant("Hello"),
+            ChatMessage::user("Bye"),
+        ];
+        let request = CompletionBuilder::new(ModelIdentifier::Llama3Period18b)
+            .chat_template(&messages)
+            .build();
+        assert_eq!(
+            request.prompt,
+            Prompt::String(
+                "<|system|>\nBe brief\n<|user|>\nHi\n<|assistant|>\nHello\n<|user|>\nBye\n<|assistant|>\n"
+                    .into()
+            )
+        );
+
+        let llama = ChatTemplate {
+            message_prefix: "<|start_header_id|>{role}<|end_header_id|>\n\n".into(),
+            message_suffix: "<|eot_id|>".into(),
+            generation_prompt: "<|start_header_id|>assistant<|end_header_id|>\n\n".into(),
+        };
+        let request = CompletionBuilder::new(ModelIdentifier::Llama3Period18b)
+            .chat_template_with(&llama, &messages[1..2])
+            .build();
+        assert_eq!(
+            request.prompt,
+            Prompt::String(
+                "<|start_header_id|>user<|end_header_id|>\n\nHi<|eot_id|><|start_header_id|>assistant<|end_header_id|>\n\n"
+                    .into()
+            )
+        );
+        assert_eq!(ChatTemplate::default().render(&[]), "<|assistant|>\n");
+    }
+
+    #[test]
+    fn test_builder_suffix() {
+        let request = CompletionBuilder::new(ModelIdentifier::Llama3Period18b)
+            .prompt("fn add(a: i32, b: i32) -> i32 {")
+            .suffix("}")
+            .build();
+
+        assert_eq!(request.suffix.as_deref(), Some("}"));
+        let json = serde_json::to_value(&request).unwrap();
+        assert_eq!(json["suffix"], "}");
+    }
+
+    #[test]
+    fn test_builder_logprobs() {
+        let request = CompletionBuilder::new(ModelIdentifier::Llama3Period18b)
+            .prompt("The sky is")
+            .logprobs(3)
+            .build();
+        assert_eq!(request.logprobs, Some(3));
+        assert_eq!(serde_json::to_value(&request).unwrap()["logprobs"], 3);
+
+        let choice: CompletionChoice = serde_json::from_value(serde_json::json!({
+            "index": 0,
+            "text": " blue",
+            "logprobs": {
+                "tokens": [" blue"],
+                "token_logprobs": [-0.25],
+                "top_logprobs": [{ " blue": -0.25, " clear": -1.5 }],
+                "text_offset": [10]
+            }
+        }))
+        .unwrap();
+        let logprobs = choice.logprobs.unwrap();
+        assert_eq!(logprobs.token_logprobs, Some(vec![Some(-0.25)]));
+        assert_eq!(
+            logprobs.top_logprobs.unwrap()[0].as_ref().unwrap()[" clear"],
+            -1.5
+        );
+    }
+
+    #[test]
+    fn test_builder_penalties() {
+        let request = CompletionBuilder::new(ModelIdentifier::Llama3Period18b)
+            .prompt("Once upon a time")
+            .frequency_penalty(0.5)
+            .presence_penalty(-2.0)
+            .try_build()
+            .unwrap();
+        assert_eq!(request.frequency_penalty, Some(0.5));
+        let json = serde_json::to_value(&request).unwrap();
+        assert_eq!(json["frequency_penalty"], 0.5);
+        assert_eq!(json["presence_penalty"], -2.0);
+
+        let plain = CompletionBuilder::new(ModelIdentifier::Llama3Period18b).build();
+        assert!(
+            serde_json::to_value(&plain)
+                .unwrap()
+                .get("presence_penalty")
+                .is_none()
+        );
+
+        for builder in [
+            CompletionBuilder::new(ModelIdentifier::Llama3Period18b).frequency_penalty(2.5),
+            CompletionBuilder::new(ModelIdentifier::Llama3Period18b).presence_penalty(-2.1),
+            CompletionBuilder::new(ModelIdentifier::Llama3Period18b).presence_penalty(f64::NAN),
+        ] {
+            assert!(matches!(builder.try_build(), Err(Error::InvalidRequest(_))));
+        }
+    }
+
+    #[test]
+    fn test_builder_include_usage() {
+        let request = CompletionBuilder::new(ModelIdentifier::Llama3Period18b)
+            .prompt("Once upon a time")
+            .stream(true)
+            .include_usage(true)
+            .build();
+        assert_eq!(
+            serde_json::to_value(&request).unwrap()["stream_options"],
+            serde_json::json!({ "include_usage": true })
+        );
+    }
 }
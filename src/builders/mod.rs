@@ -5,5 +5,7 @@
 mod chat_completion_builder;
 mod completion_builder;
 
-pub use chat_completion_builder::ChatCompletionBuilder;
-pub use completion_builder::CompletionBuilder;
+pub use chat_completion_builder::{
+    ChatCompletionBuilder, DEFAULT_DATE_FORMAT, PromptTemplate, Sampling,
+};
+pub use completion_builder::{ChatTemplate, CompletionBuilder};
@@ -0,0 +1,273 @@
+//! In-memory caching of chat completion responses
+
+use std::collections::HashMap;
+use std::collections::hash_map::DefaultHasher;
+use std::hash::{Hash, Hasher};
+use std::sync::{Arc, Mutex};
+use std::time::{Duration, Instant};
+
+use crate::apis::configuration::{Configuration, EndpointCategory};
+use crate::models::{ChatCompletionRequest, CreateChatCompletionResponse};
+
+/// Which requests a [`ResponseCache`] stores responses for
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum CachePolicy {
+    /// Only requests with `temperature` set to `0.0`, whose output is repeatable
+    #[default]
+    Deterministic,
+    /// Every request, even when sampling would give a different answer
+    Always,
+}
+
+impl CachePolicy {
+    fn allows(self, request: &ChatCompletionRequest) -> bool {
+        match self {
+            CachePolicy::Deterministic => request.temperature == Some(0.0),
+            CachePolicy::Always => true,
+        }
+    }
+}
+
+/// A bounded, time-limited cache of chat completion responses
+///
+/// Responses are keyed by a hash of the endpoint, the credentials and the
+/// serialized request, so only identical requests sent with the same API key
+/// share an entry. Entries expire after the TTL;
+/// when the cache is full the oldest entry is evicted. Clones share the same
+/// storage, so a cache can be attached to several clients.
+///
+/// # Example
+/// ```rust
+/// use cerebras_rs::Client;
+/// use cerebras_rs::cache::{CachePolicy, ResponseCache};
+/// use std::time::Duration;
+///
+/// let cache = ResponseCache::new(100, Duration::from_secs(300)).policy(CachePolicy::Always);
+/// let client = Client::new("test-key").with_cache(cache.clone());
+/// assert!(cache.is_empty());
+/// ```
+#[derive(Debug, Clone)]
+pub struct ResponseCache {
+    capacity: usize,
+    ttl: Duration,
+    policy: CachePolicy,
+    entries: Arc<Mutex<Entries>>,
+}
+
+#[derive(Debug, Default)]
+struct Entries {
+    /// Insertion counter, used to find the oldest entry
+    inserted: u64,
+    map: HashMap<u64, Entry>,
+}
+
+#[derive(Debug)]
+struct Entry {
+    order: u64,
+    stored_at: Instant,
+    response: CreateChatCompletionResponse,
+}
+
+impl ResponseCache {
+    /// Create a cache holding at most `capacity` responses, each for up to `ttl`
+    pub fn new(capacity: usize, ttl: Duration) -> Self {
+        Self {
+            capacity,
+            ttl,
+            policy: CachePolicy::default(),
+            entries: Arc::default(),
+        }
+    }
+
+    /// Set which requests are cached
+    pub fn policy(mut self, policy: CachePolicy) -> Self {
+        self.policy = policy;
+        self
+    }
+
+    /// Number of responses currently stored, including expired ones not yet evicted
+    pub fn len(&self) -> usize {
+        self.entries.lock().map_or(0, |entries| entries.map.len())
+    }
+
+    /// Whether no responses are stored
+    pub fn is_empty(&self) -> bool {
+        self.len() == 0
+    }
+
+    /// Remove every stored response
+    pub fn clear(&self) {
+        if let Ok(mut entries) = self.entries.lock() {
+            entries.map.clear();
+        }
+    }
+
+    /// Cache key for `request` sent with `configuration`, or `None` if the policy excludes it
+    ///
+    /// The credentials are part of the key so that clients with different
+    /// API keys sharing one cache never see each other's responses.
+    pub(crate) fn key(
+        &self,
+        configuration: &Configuration,
+        request: &ChatCompletionRequest,
+    ) -> Option<u64> {
+        if self.capacity == 0 || !self.policy.allows(request) {
+            return None;
+        }
+        let serialized = serde_json::to_string(request).ok()?;
+        let mut hasher = DefaultHasher::new();
+        configuration
+            .base_path_for(EndpointCategory::Chat)
+            .hash(&mut hasher);
+        configuration.bearer_access_token.hash(&mut hasher);
+        configuration.auth_header_name.hash(&mut hasher);
+        configuration.oauth_access_token.hash(&mut hasher);
+        configuration.basic_auth.hash(&mut hasher);
+        configuration
+            .api_key
+            .as_ref()
+            .map(|api_key| (&api_key.prefix, &api_key.key))
+            .hash(&mut hasher);
+        serialized.hash(&mut hasher);
+        Some(hasher.finish())
+    }
+
+    /// The unexpired response stored under `key`
+    pub(crate) fn get(&self, key: u64) -> Option<CreateChatCompletionResponse> {
+        let mut entries = self.entries.lock().ok()?;
+        match entries.map.get(&key) {
+            Some(entry) if entry.stored_at.elapsed() < self.ttl => Some(entry.response.clone()),
+            Some(_) => {
+                entries.map.remove(&key);
+                None
+            }
+            None => None,
+        }
+    }
+
+    /// Store `response` under `key`, evicting expired and then the oldest entries to make room
+    pub(crate) fn insert(&self, key: u64, response: CreateChatCompletionResponse) {
+        let Ok(mut entries) = self.entries.lock() else {
+            return;
+        };
+        if entries.map.len() >= self.capacity {
+            entries
+                .map
+                .retain(|_, entry| entry.stored_at.elapsed() < self.ttl);
+        }
+        while entries.map.len() >= self.capacity && !entries.map.contains_key(&key) {
+            let Some(oldest) = entries
+                .map
+                .iter()
+                .min_by_key(|(_, entry)| entry.order)
+                .map(|(key, _)| *key)
+            else {
+                break;
+            };
+            entries.map.remove(&oldest);
+        }
+        entries.inserted += 1;
+        let entry = Entry {
+            order: entries.inserted,
+            stored_at: Instant::now(),
+            response,
+        };
+        entries.map.insert(key, entry);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::models::ModelIdentifier;
+
+    fn request(temperature: Option<f64>, content: &str) -> ChatCompletionRequest {
+        let mut request = ChatCompletionRequest::builder(ModelIdentifier::Llama3Period18b)
+            .user_message(content)
+            .build();
+        request.temperature = temperature;
+        request
+    }
+
+    fn response(id: &str) -> CreateChatCompletionResponse {
+        CreateChatCompletionResponse {
+            id: Some(id.to_string()),
+            ..Default::default()
+        }
+    }
+
+    fn configuration(base_path: &str) -> Configuration {
+        Configuration {
+            base_path: base_path.to_string(),
+            bearer_access_token: Some("key-a".to_string()),
+            ..Default::default()
+        }
+    }
+
+    #[test]
+    fn test_cache_policy_and_keys() {
+        let cache = ResponseCache::new(10, Duration::from_secs(60));
+        assert!(
+            cache
+                .key(&configuration("base"), &request(None, "a"))
+                .is_none()
+        );
+        assert!(
+            cache
+                .key(&configuration("base"), &request(Some(0.7), "a"))
+                .is_none()
+        );
+
+        let a = cache
+            .key(&configuration("base"), &request(Some(0.0), "a"))
+            .unwrap();
+        assert_eq!(
+            cache.key(&configuration("base"), &request(Some(0.0), "a")),
+            Some(a)
+        );
+        assert_ne!(
+            cache.key(&configuration("base"), &request(Some(0.0), "b")),
+            Some(a)
+        );
+        assert_ne!(
+            cache.key(&configuration("other"), &request(Some(0.0), "a")),
+            Some(a)
+        );
+
+        // Different credentials never share entries
+        let mut other_key = configuration("base");
+        other_key.bearer_access_token = Some("key-b".to_string());
+        assert_ne!(cache.key(&other_key, &request(Some(0.0), "a")), Some(a));
+        let mut other_header = configuration("base");
+        other_header.auth_header_name = Some("api-key".to_string());
+        assert_ne!(cache.key(&other_header, &request(Some(0.0), "a")), Some(a));
+
+        let cache = cache.policy(CachePolicy::Always);
+        assert!(
+            cache
+                .key(&configuration("base"), &request(Some(0.7), "a"))
+                .is_some()
+        );
+        assert!(
+            ResponseCache::new(0, Duration::from_secs(60))
+                .key(&configuration("base"), &request(Some(0.0), "a"))
+                .is_none()
+        );
+    }
+
+    #[test]
+    fn test_cache_eviction_and_expiry() {
+        let cache = ResponseCache::new(2, Duration::from_secs(60));
+        cache.insert(1, response("one"));
+        cache.insert(2, response("two"));
+        cache.insert(3, response("three"));
+        assert_eq!(cache.len(), 2);
+        assert!(cache.get(1).is_none());
+        assert_eq!(cache.get(3).unwrap().id.as_deref(), Some("three"));
+
+        let cache = ResponseCache::new(2, Duration::ZERO);
+        cache.insert(1, response("one"));
+        assert!(cache.get(1).is_none());
+        assert!(cache.is_empty());
+    }
+}
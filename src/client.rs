@@ -3,13 +3,27 @@
 //! This module provides an ergonomic client interface that wraps the generated API code
 //! with additional conveniences like builder patterns and streaming support.
 
+use std::borrow::Cow;
+use std::time::Duration;
+
+use futures_util::{Stream, StreamExt};
+
 use crate::{
-    Error, Result,
-    apis::{ResponseContent, configuration::Configuration, default_api},
+    Error, NonStreamingRequest, Result, StreamingRequest,
+    apis::{
+        ResponseContent,
+        configuration::{Configuration, EndpointCategory},
+        default_api,
+    },
+    cache::ResponseCache,
     chat_message::Role,
     models::*,
+    telemetry,
 };
 
+/// Content tokens buffered by [`Client::chat_completion_stream_channel`] before the task waits
+const STREAM_CHANNEL_CAPACITY: usize = 64;
+
 /// High-level client for interacting with the Cerebras Inference API
 ///
 /// # Example
@@ -24,6 +38,7 @@ use crate::{
 #[derive(Clone, Debug)]
 pub struct Client {
     configuration: Configuration,
+    cache: Option<ResponseCache>,
 }
 
 impl Client {
@@ -32,20 +47,31 @@ impl Client {
         let mut configuration = Configuration::new();
         configuration.bearer_access_token = Some(api_key.into());
 
-        Self { configuration }
+        Self {
+            configuration,
+            cache: None,
+        }
     }
 
     /// Create a new client from the CEREBRAS_API_KEY environment variable
+    ///
+    /// Fails with [`Error::Configuration`] if the variable is unset or the key
+    /// is obviously malformed (see [`Configuration::validate_key`]).
     pub fn from_env() -> Result<Self> {
         let api_key = std::env::var("CEREBRAS_API_KEY").map_err(|_| {
             Error::Configuration("CEREBRAS_API_KEY environment variable not set".into())
         })?;
-        Ok(Self::new(api_key))
+        let client = Self::new(api_key);
+        client.configuration.validate_key()?;
+        Ok(client)
     }
 
     /// Create a new client with a custom configuration
     pub fn with_configuration(configuration: Configuration) -> Self {
-        Self { configuration }
+        Self {
+            configuration,
+            cache: None,
+        }
     }
 
     /// Set a custom base URL (useful for testing or proxies)
@@ -54,11 +80,324 @@ impl Client {
         self
     }
 
+    /// Force HTTP/2 with prior knowledge, skipping protocol negotiation
+    ///
+    /// This avoids the HTTP/1.1 fallback and lets many concurrent requests share
+    /// one multiplexed connection. Over HTTPS, HTTP/2 is already negotiated
+    /// automatically when the server supports it, so this is mainly useful for
+    /// `h2c` gateways. Requests fail against servers that only speak HTTP/1.1.
+    ///
+    /// The underlying HTTP client is rebuilt, replacing any custom client set on
+    /// the configuration. If the client can't be rebuilt, the previous one is
+    /// kept and the option is left disabled.
+    pub fn with_http2(mut self) -> Self {
+        self.configuration.http2_prior_knowledge = true;
+        if self.configuration.rebuild_client().is_err() {
+            self.configuration.http2_prior_knowledge = false;
+        }
+        self
+    }
+
+    /// Fail requests that can't connect to the API within `timeout`
+    ///
+    /// This bounds DNS resolution and connection setup only, so an unreachable
+    /// endpoint fails fast without cutting off long generations or streams.
+    ///
+    /// The underlying HTTP client is rebuilt, replacing any custom client set on
+    /// the configuration. If the client can't be rebuilt, the previous one is
+    /// kept and the option is left unset.
+    pub fn with_connect_timeout(mut self, timeout: Duration) -> Self {
+        let previous = self.configuration.connect_timeout.replace(timeout);
+        if self.configuration.rebuild_client().is_err() {
+            self.configuration.connect_timeout = previous;
+        }
+        self
+    }
+
+    /// Enable or disable compressed responses
+    ///
+    /// Compression is on by default with the `compression` feature; the client
+    /// advertises gzip, brotli and deflate and decodes whichever the server
+    /// picks, which mostly pays off for long completions and model lists.
+    /// Streams are decoded as they arrive.
+    ///
+    /// The underlying HTTP client is rebuilt, replacing any custom client set on
+    /// the configuration. If the client can't be rebuilt, the previous one is
+    /// kept along with the previous setting.
+    #[cfg(feature = "compression")]
+    pub fn with_compression(mut self, enabled: bool) -> Self {
+        let previous = std::mem::replace(&mut self.configuration.compression, enabled);
+        if self.configuration.rebuild_client().is_err() {
+            self.configuration.compression = previous;
+        }
+        self
+    }
+
+    /// Reopen dropped streams up to `attempts` times, resuming from the last event received
+    ///
+    /// When a stream's connection fails or closes before `[DONE]`, the request
+    /// is sent again with `Last-Event-ID` so the server continues the stream
+    /// instead of starting the generation over. This only works against
+    /// servers that assign SSE event ids; other streams end as they would
+    /// without this option. Since a server closing a stream without `[DONE]`
+    /// looks like a drop, such servers may see up to `attempts` extra requests
+    /// per stream.
+    pub fn with_stream_resume(mut self, attempts: u32) -> Self {
+        self.configuration.stream_resume_attempts = attempts;
+        self
+    }
+
+    /// Send the API key in the `name` header instead of `Authorization: Bearer`
+    ///
+    /// For gateways, such as Azure-style deployments fronting Cerebras, that
+    /// expect the raw key in a header like `api-key`.
+    pub fn with_auth_header<S: Into<String>>(mut self, name: S) -> Self {
+        self.configuration.auth_header_name = Some(name.into());
+        self
+    }
+
+    /// Cap generations at `max_tokens` for requests that don't set a limit
+    ///
+    /// Guards against runaway output, and cost, when a request forgets
+    /// `max_tokens`. The value is only filled in when the request leaves the
+    /// field unset; an explicit value is always sent as-is. Applies to chat
+    /// and text completions, streaming or not.
+    pub fn with_default_max_tokens(mut self, max_tokens: u32) -> Self {
+        self.configuration.default_max_tokens = Some(max_tokens);
+        self
+    }
+
+    /// Like [`with_default_max_tokens`](Self::with_default_max_tokens), for requests to `model` only
+    ///
+    /// Takes precedence over the default for all models.
+    pub fn with_model_max_tokens(mut self, model: ModelIdentifier, max_tokens: u32) -> Self {
+        self.configuration
+            .model_max_tokens
+            .insert(model, max_tokens);
+        self
+    }
+
+    /// Record the context window of `model`, in tokens
+    ///
+    /// Limits vary by model and account tier, so none are built in; this
+    /// enables budgeting helpers such as
+    /// [`Conversation::remaining_budget`](crate::Conversation::remaining_budget).
+    pub fn with_context_window(mut self, model: ModelIdentifier, tokens: u32) -> Self {
+        self.configuration.context_windows.insert(model, tokens);
+        self
+    }
+
+    /// Map logical model names such as `"fast"` to concrete models
+    ///
+    /// [`resolve_model`](Self::resolve_model) looks names up here first, so
+    /// config files can reference stable aliases while the model behind each
+    /// one is changed in a single place. Aliases are added to any set before.
+    pub fn with_model_aliases(
+        mut self,
+        aliases: std::collections::HashMap<String, ModelIdentifier>,
+    ) -> Self {
+        self.configuration.model_aliases.extend(aliases);
+        self
+    }
+
+    /// The model an alias or wire name refers to
+    ///
+    /// Aliases set with [`with_model_aliases`](Self::with_model_aliases) take
+    /// precedence; any other name is parsed as a model identifier, failing
+    /// with [`Error::InvalidModel`] if it is neither.
+    ///
+    /// # Example
+    /// ```rust
+    /// use cerebras_rs::{Client, ModelIdentifier};
+    /// use std::collections::HashMap;
+    ///
+    /// let client = Client::new("test-key").with_model_aliases(HashMap::from([
+    ///     ("fast".to_string(), ModelIdentifier::Llama3Period18b),
+    /// ]));
+    /// assert_eq!(client.resolve_model("fast")?, ModelIdentifier::Llama3Period18b);
+    /// assert_eq!(client.resolve_model("qwen-3-32b")?, ModelIdentifier::Qwen332b);
+    /// assert!(client.resolve_model("slow").is_err());
+    /// # Ok::<(), cerebras_rs::Error>(())
+    /// ```
+    pub fn resolve_model(&self, name: &str) -> Result<ModelIdentifier> {
+        match self.configuration.model_aliases.get(name) {
+            Some(&model) => Ok(model),
+            None => name.parse(),
+        }
+    }
+
+    /// The context window recorded for `model` with [`with_context_window`](Self::with_context_window)
+    pub fn context_window(&self, model: ModelIdentifier) -> Option<u32> {
+        self.configuration.context_windows.get(&model).copied()
+    }
+
+    /// Cache chat completion responses for identical temperature-0 requests
+    ///
+    /// Shorthand for [`with_cache`](Self::with_cache) with a new
+    /// [`ResponseCache`] holding up to `capacity` responses for `ttl` each.
+    pub fn with_response_cache(self, capacity: usize, ttl: Duration) -> Self {
+        self.with_cache(ResponseCache::new(capacity, ttl))
+    }
+
+    /// Serve [`chat_completion`](Self::chat_completion) from `cache` when possible
+    ///
+    /// Only successful responses are stored. Clones of the client, and other
+    /// clients given a clone of the same cache, share its entries, as long as
+    /// they use the same credentials and base URL.
+    pub fn with_cache(mut self, cache: ResponseCache) -> Self {
+        self.cache = Some(cache);
+        self
+    }
+
+    /// Trim leading and trailing whitespace from returned message content and text
+    ///
+    /// Models often start their output with a space or newline. When enabled,
+    /// the `content` of each chat choice and the `text` of each completion
+    /// choice is trimmed in [`chat_completion`](Self::chat_completion),
+    /// [`completion`](Self::completion) and the `collect` of streams this
+    /// client creates. Streamed chunks are passed through unchanged, as are
+    /// cached responses, which are trimmed only when returned. Responses
+    /// joined from continuations, as by
+    /// [`chat_completion_adaptive_with`](Self::chat_completion_adaptive_with),
+    /// are trimmed once joined. Off by default to preserve the exact output.
+    pub fn with_trim_output(mut self, enabled: bool) -> Self {
+        self.configuration.trim_output = enabled;
+        self
+    }
+
+    /// Generate a UUID request id for every request, sent as `X-Client-Request-Id`
+    ///
+    /// The id is recorded on the request's tracing span (with the `tracing`
+    /// feature), so client logs can be matched against server logs. Errors
+    /// are then tagged with the id as [`Error::WithRequestId`]; match on
+    /// [`Error::root`] to handle them by their underlying variant.
+    pub fn with_request_id_generation(mut self, enabled: bool) -> Self {
+        self.configuration.generate_request_ids = enabled;
+        self
+    }
+
+    /// Return a client that sends the given request id with its requests
+    ///
+    /// Useful for tagging a single call with an id you already track:
+    /// `client.with_request_id(id).chat_completion(request)`. As with
+    /// [`with_request_id_generation`](Self::with_request_id_generation),
+    /// errors are tagged with the id.
+    pub fn with_request_id<S: Into<String>>(&self, request_id: S) -> Self {
+        let mut client = self.clone();
+        client.configuration.request_id = Some(request_id.into());
+        client
+    }
+
+    /// Configuration for a single request, with a fresh request id if generation is enabled
+    fn request_configuration(&self) -> Cow<'_, Configuration> {
+        if self.configuration.generate_request_ids && self.configuration.request_id.is_none() {
+            let mut configuration = self.configuration.clone();
+            configuration.request_id = Some(uuid::Uuid::new_v4().to_string());
+            Cow::Owned(configuration)
+        } else {
+            Cow::Borrowed(&self.configuration)
+        }
+    }
+
+    /// Fill in `max_tokens` from the configured defaults if the request leaves it unset
+    ///
+    /// See [`with_default_max_tokens`](Self::with_default_max_tokens).
+    fn apply_default_max_tokens(&self, max_tokens: &mut Option<u32>, model: ModelIdentifier) {
+        if max_tokens.is_none() {
+            *max_tokens = self.configuration.max_tokens_for(model);
+        }
+    }
+
     /// Get a reference to the underlying configuration
     pub fn configuration(&self) -> &Configuration {
         &self.configuration
     }
 
+    /// Cancel every in-flight request and stream, on this client and all its clones
+    ///
+    /// Pending requests fail with [`Error::Cancelled`] and open streams yield
+    /// it as their last item, dropping their connections. The client is
+    /// unusable afterwards: every later request fails with the same error
+    /// without being sent, so create a new client to start over.
+    ///
+    /// # Example
+    /// ```rust,no_run
+    /// use cerebras_rs::Client;
+    ///
+    /// # async fn example() -> Result<(), Box<dyn std::error::Error>> {
+    /// let client = Client::from_env()?;
+    /// let worker = client.clone();
+    /// let task = tokio::spawn(async move { worker.list_models().await });
+    ///
+    /// client.shutdown();
+    /// assert!(matches!(task.await?, Err(cerebras_rs::Error::Cancelled)));
+    /// # Ok(())
+    /// # }
+    /// ```
+    pub fn shutdown(&self) {
+        self.configuration.shutdown.cancel();
+    }
+
+    /// Whether [`shutdown`](Self::shutdown) has been called on this client or a clone
+    pub fn is_shut_down(&self) -> bool {
+        self.configuration.shutdown.is_cancelled()
+    }
+
+    /// Fetch the raw response body of a `GET` request to `path`
+    ///
+    /// This is a low-level escape hatch for endpoints that return non-JSON
+    /// payloads, or for inspecting the exact bytes the server sends. The path is
+    /// resolved against the configured base URL and the configured credentials
+    /// are applied. Error statuses are mapped to the same [`Error`] variants as
+    /// the typed endpoints.
+    ///
+    /// # Example
+    /// ```rust,no_run
+    /// # use cerebras_rs::Client;
+    /// # async fn example() -> Result<(), Box<dyn std::error::Error>> {
+    /// let client = Client::from_env()?;
+    /// let body = client.get_bytes("models").await?;
+    /// println!("{}", String::from_utf8_lossy(&body));
+    /// # Ok(())
+    /// # }
+    /// ```
+    pub async fn get_bytes(&self, path: &str) -> Result<bytes::Bytes> {
+        let configuration = self.request_configuration();
+        cancellable("get_bytes", &configuration, async {
+            let uri_str = format!(
+                "{}/{}",
+                configuration.base_path.trim_end_matches('/'),
+                path.trim_start_matches('/')
+            );
+            let mut req_builder = configuration.client.get(&uri_str);
+
+            if let Some(ref user_agent) = configuration.user_agent {
+                req_builder = req_builder.header(reqwest::header::USER_AGENT, user_agent.clone());
+            }
+            req_builder = configuration.apply_auth(req_builder);
+            req_builder = configuration.apply_request_id(req_builder);
+
+            let resp = req_builder.send().await?;
+            let status = resp.status();
+            let headers = resp.headers().clone();
+            let body = configuration
+                .read_body::<()>(resp)
+                .await
+                .map_err(|e| match e {
+                    crate::apis::Error::Reqwest(e) => Error::from(e),
+                    e => Error::Api(e.to_string()),
+                })?;
+
+            if !status.is_client_error() && !status.is_server_error() {
+                Ok(body)
+            } else {
+                Err(Error::from_status(status, &String::from_utf8_lossy(&body))
+                    .with_retry_after(&headers))
+            }
+        })
+        .await
+    }
+
     /// List available models
     ///
     /// # Example
@@ -78,24 +417,63 @@ impl Client {
     /// # }
     /// ```
     pub async fn list_models(&self) -> Result<ModelList> {
-        let response = default_api::list_models(&self.configuration).await?;
-        match response.entity {
-            Some(default_api::ListModelsSuccess::Status200(models)) => Ok(models),
-            _ => Err(Error::Api("Unexpected response format".into())),
-        }
+        let configuration = self.request_configuration();
+        cancellable("list_models", &configuration, async {
+            let response = default_api::list_models(&configuration).await?;
+            match response.entity {
+                Some(default_api::ListModelsSuccess::Status200(models)) => Ok(models),
+                _ => Err(Error::Api("Unexpected response format".into())),
+            }
+        })
+        .await
+    }
+
+    /// List the ids of the available models
+    ///
+    /// A shorthand for [`list_models`](Self::list_models) when only the ids
+    /// are needed, e.g. to offer a choice of models. Models listed without an
+    /// id are skipped.
+    ///
+    /// # Example
+    /// ```rust,no_run
+    /// # use cerebras_rs::Client;
+    /// # async fn example() -> Result<(), Box<dyn std::error::Error>> {
+    /// let client = Client::from_env()?;
+    /// for id in client.list_model_ids().await? {
+    ///     println!("{}", id);
+    /// }
+    /// # Ok(())
+    /// # }
+    /// ```
+    pub async fn list_model_ids(&self) -> Result<Vec<String>> {
+        let models = self.list_models().await?;
+        Ok(models
+            .data
+            .into_iter()
+            .flatten()
+            .filter_map(|model| model.id)
+            .collect())
     }
 
     /// Retrieve details about a specific model
     pub async fn get_model(&self, model: ModelIdentifier) -> Result<Model> {
-        let response = default_api::retrieve_model(&self.configuration, model).await?;
-        match response.entity {
-            Some(default_api::RetrieveModelSuccess::Status200(model)) => Ok(model),
-            _ => Err(Error::Api("Unexpected response format".into())),
-        }
+        let configuration = self.request_configuration();
+        cancellable("retrieve_model", &configuration, async {
+            let response = default_api::retrieve_model(&configuration, model).await?;
+            match response.entity {
+                Some(default_api::RetrieveModelSuccess::Status200(model)) => Ok(model),
+                _ => Err(Error::Api("Unexpected response format".into())),
+            }
+        })
+        .await
     }
 
     /// Create a chat completion
     ///
+    /// The request is sent without streaming; see [`NonStreamingRequest`] for
+    /// how a [`ChatCompletionRequest`] with `stream` set is handled, and use
+    /// [`chat_completion_stream`](Self::chat_completion_stream) to stream.
+    ///
     /// # Example
     /// ```rust,no_run
     /// # use cerebras_rs::{Client, ChatCompletionRequest, ChatMessage, ModelIdentifier};
@@ -111,32 +489,178 @@ impl Client {
     /// };
     ///
     /// let response = client.chat_completion(request).await?;
-    /// if let Some(choices) = &response.choices {
-    ///     if let Some(first_choice) = choices.first() {
-    ///         if let Some(message) = &first_choice.message {
-    ///             println!("{}", message.content);
-    ///         }
-    ///     }
+    /// if let Some(message) = &response.require_choice()?.message {
+    ///     println!("{}", message.content);
     /// }
     /// # Ok(())
     /// # }
     /// ```
     pub async fn chat_completion(
         &self,
-        request: ChatCompletionRequest,
+        request: impl Into<NonStreamingRequest>,
     ) -> Result<CreateChatCompletionResponse> {
-        let response = default_api::create_chat_completion(&self.configuration, request).await?;
-        match response.entity {
-            Some(default_api::CreateChatCompletionSuccess::Status200(resp)) => match resp {
-                CreateChatCompletion200Response::CreateChatCompletionResponse(completion) => {
-                    Ok(completion)
-                }
-                CreateChatCompletion200Response::ChatCompletionChunk(_) => Err(Error::Api(
-                    "Unexpected streaming response for non-streaming request".into(),
-                )),
-            },
-            _ => Err(Error::Api("Unexpected response format".into())),
+        let mut response = self.chat_completion_untrimmed(request).await?;
+        if self.configuration.trim_output {
+            response.trim_whitespace();
         }
+        Ok(response)
+    }
+
+    /// [`chat_completion`](Self::chat_completion) without [`with_trim_output`](Self::with_trim_output) applied
+    ///
+    /// For callers that join several responses, which must only trim the result.
+    pub(crate) async fn chat_completion_untrimmed(
+        &self,
+        request: impl Into<NonStreamingRequest>,
+    ) -> Result<CreateChatCompletionResponse> {
+        let mut request = request.into().into_inner();
+        self.apply_default_max_tokens(&mut request.max_tokens, request.model);
+        let cache_key = self
+            .cache
+            .as_ref()
+            .and_then(|cache| Some((cache, cache.key(&self.configuration, &request)?)));
+        if let Some((cache, key)) = cache_key
+            && let Some(response) = cache.get(key)
+        {
+            return Ok(response);
+        }
+
+        let configuration = self.request_configuration();
+        let response = cancellable("create_chat_completion", &configuration, async {
+            let response = default_api::create_chat_completion(&configuration, request).await?;
+            match response.entity {
+                Some(default_api::CreateChatCompletionSuccess::Status200(resp)) => match resp {
+                    CreateChatCompletion200Response::CreateChatCompletionResponse(completion) => {
+                        Ok(completion)
+                    }
+                    CreateChatCompletion200Response::ChatCompletionChunk(_) => Err(Error::Api(
+                        "Unexpected streaming response for non-streaming request".into(),
+                    )),
+                },
+                _ => Err(Error::Api("Unexpected response format".into())),
+            }
+        })
+        .await?;
+
+        if let Some((cache, key)) = cache_key {
+            cache.insert(key, response.clone());
+        }
+        Ok(response)
+    }
+
+    /// Create a chat completion that must finish by `deadline`
+    ///
+    /// The deadline covers connecting, generation and reading the response,
+    /// and fails with [`Error::Timeout`] once it passes. Unlike a fixed
+    /// timeout, one deadline can be shared by every step of a multi-request
+    /// operation so each gets only the budget that remains.
+    ///
+    /// # Example
+    /// ```rust,no_run
+    /// # use cerebras_rs::{Client, ChatCompletionRequest, ModelIdentifier};
+    /// # use std::time::{Duration, Instant};
+    /// # async fn example() -> Result<(), Box<dyn std::error::Error>> {
+    /// let client = Client::from_env()?;
+    /// let deadline = Instant::now() + Duration::from_secs(5);
+    ///
+    /// let draft = ChatCompletionRequest::builder(ModelIdentifier::Llama3Period18b)
+    ///     .user_message("Draft a haiku about rain")
+    ///     .build();
+    /// let draft = client.chat_completion_deadline(draft, deadline).await?;
+    ///
+    /// let review = ChatCompletionRequest::builder(ModelIdentifier::Llama3Period18b)
+    ///     .user_message(format!("Improve this haiku: {}", draft.first_text()?))
+    ///     .build();
+    /// let review = client.chat_completion_deadline(review, deadline).await?;
+    /// println!("{}", review.first_text()?);
+    /// # Ok(())
+    /// # }
+    /// ```
+    pub async fn chat_completion_deadline(
+        &self,
+        request: impl Into<NonStreamingRequest>,
+        deadline: std::time::Instant,
+    ) -> Result<CreateChatCompletionResponse> {
+        tokio::time::timeout_at(deadline.into(), self.chat_completion(request))
+            .await
+            .unwrap_or(Err(Error::Timeout))
+    }
+
+    /// Send a single user message and return the reply's text
+    ///
+    /// A shortcut for one-off prompts in scripts and tests; see
+    /// [`quick_chat_with`](Self::quick_chat_with) to set the temperature and
+    /// length, and use [`Client::chat_completion`] for anything that needs
+    /// more options or history. Fails with [`Error::Api`] if the response has
+    /// no choices.
+    ///
+    /// # Example
+    /// ```rust,no_run
+    /// # use cerebras_rs::{Client, ModelIdentifier};
+    /// # async fn example() -> Result<(), Box<dyn std::error::Error>> {
+    /// let client = Client::from_env()?;
+    /// let answer = client
+    ///     .quick_chat(ModelIdentifier::Llama3Period18b, "What is the capital of France?")
+    ///     .await?;
+    /// println!("{}", answer);
+    /// # Ok(())
+    /// # }
+    /// ```
+    pub async fn quick_chat<S: Into<String>>(
+        &self,
+        model: ModelIdentifier,
+        text: S,
+    ) -> Result<String> {
+        let request = ChatCompletionRequest::new(model, vec![ChatMessage::user(text)]);
+        let response = self.chat_completion(request).await?;
+        Ok(response.first_text()?.to_string())
+    }
+
+    /// Like [`quick_chat`](Self::quick_chat), with the sampling temperature and a cap on the reply's tokens
+    pub async fn quick_chat_with<S: Into<String>>(
+        &self,
+        model: ModelIdentifier,
+        prompt: S,
+        temperature: f64,
+        max_tokens: u32,
+    ) -> Result<String> {
+        let request = ChatCompletionRequest::builder(model)
+            .user_message(prompt)
+            .temperature(temperature)
+            .max_tokens(max_tokens)
+            .build();
+        let response = self.chat_completion(request).await?;
+        Ok(response.first_text()?.to_string())
+    }
+
+    /// Send `prompt` as a single user message and return the reply's text
+    ///
+    /// The same as [`quick_chat`](Self::quick_chat); see
+    /// [`ask_with`](Self::ask_with) to set the temperature and length.
+    ///
+    /// # Example
+    /// ```rust,no_run
+    /// # use cerebras_rs::{Client, ModelIdentifier};
+    /// # async fn example() -> Result<(), Box<dyn std::error::Error>> {
+    /// let client = Client::from_env()?;
+    /// let answer = client.ask(ModelIdentifier::Llama3Period18b, "What is 2 + 2?").await?;
+    /// # Ok(())
+    /// # }
+    /// ```
+    pub async fn ask<S: Into<String>>(&self, model: ModelIdentifier, prompt: S) -> Result<String> {
+        self.quick_chat(model, prompt).await
+    }
+
+    /// The same as [`quick_chat_with`](Self::quick_chat_with)
+    pub async fn ask_with<S: Into<String>>(
+        &self,
+        model: ModelIdentifier,
+        prompt: S,
+        temperature: f64,
+        max_tokens: u32,
+    ) -> Result<String> {
+        self.quick_chat_with(model, prompt, temperature, max_tokens)
+            .await
     }
 
     /// Create a chat completion with streaming
@@ -157,7 +681,7 @@ impl Client {
     /// let mut stream = client.chat_completion_stream(request).await?;
     /// while let Some(chunk) = stream.next().await {
     ///     match chunk {
-    ///         Ok(chunk) => print!("{}", chunk.choices[0].delta.content.as_deref().unwrap_or("")),
+    ///         Ok(chunk) => print!("{}", chunk.delta_text().unwrap_or("")),
     ///         Err(e) => eprintln!("Error: {}", e),
     ///     }
     /// }
@@ -166,24 +690,168 @@ impl Client {
     /// ```
     pub async fn chat_completion_stream(
         &self,
-        mut request: ChatCompletionRequest,
+        request: impl Into<StreamingRequest>,
     ) -> Result<crate::streaming::ChatCompletionStream> {
-        request.stream = Some(true);
-        crate::streaming::ChatCompletionStream::new(&self.configuration, request).await
+        let mut request = request.into().into_inner();
+        self.apply_default_max_tokens(&mut request.max_tokens, request.model);
+        let configuration = self.request_configuration();
+        cancellable(
+            "create_chat_completion_stream",
+            &configuration,
+            crate::streaming::ChatCompletionStream::new(&configuration, request),
+        )
+        .await
+    }
+
+    /// Stream a chat completion from a background task, forwarding content tokens to a channel
+    ///
+    /// The spawned task owns the connection and drains it to the end, even if
+    /// the receiver is dropped early; the handle resolves to the assembled
+    /// response. The channel closes when the stream finishes. If the request or
+    /// stream fails, the error is sent on the channel as
+    /// [`Error::Streaming`], the channel closes, and the handle resolves to
+    /// the original error.
+    ///
+    /// # Example
+    /// ```rust,no_run
+    /// use cerebras_rs::{Client, ChatCompletionRequest, ModelIdentifier};
+    /// use futures_util::StreamExt;
+    ///
+    /// # async fn example() -> Result<(), Box<dyn std::error::Error>> {
+    /// let client = Client::from_env()?;
+    /// let request = ChatCompletionRequest::builder(ModelIdentifier::Llama3Period18b)
+    ///     .user_message("Tell me a story")
+    ///     .build();
+    ///
+    /// let (mut tokens, handle) = client.chat_completion_stream_channel(request);
+    /// while let Some(token) = tokens.next().await {
+    ///     print!("{}", token?);
+    /// }
+    /// let completion = handle.await??;
+    /// # Ok(())
+    /// # }
+    /// ```
+    pub fn chat_completion_stream_channel(
+        &self,
+        request: impl Into<StreamingRequest>,
+    ) -> (
+        futures::channel::mpsc::Receiver<Result<String>>,
+        tokio::task::JoinHandle<Result<ChatCompletion>>,
+    ) {
+        use futures::SinkExt;
+
+        let request = request.into();
+        let (mut tx, rx) = futures::channel::mpsc::channel(STREAM_CHANNEL_CAPACITY);
+        let client = self.clone();
+        let handle = tokio::spawn(async move {
+            let stream = match client.chat_completion_stream(request).await {
+                Ok(stream) => stream,
+                Err(e) => {
+                    let _ = tx.send(Err(Error::Streaming(e.to_string()))).await;
+                    return Err(e);
+                }
+            };
+
+            let (mut live, aggregate) = stream.split();
+            while let Some(chunk) = live.next().await {
+                match chunk {
+                    Ok(chunk) => {
+                        // A dropped receiver only stops forwarding; keep draining
+                        if let Some(text) = chunk.delta_text().filter(|t| !t.is_empty()) {
+                            let _ = tx.send(Ok(text.to_string())).await;
+                        }
+                    }
+                    Err(e) => {
+                        let _ = tx.send(Err(Error::Streaming(e.to_string()))).await;
+                        return Err(e);
+                    }
+                }
+            }
+            drop(tx);
+            aggregate.await
+        });
+        (rx, handle)
+    }
+
+    /// Stream the raw server-sent event bytes of a chat completion
+    ///
+    /// The body is forwarded exactly as received, including event framing and
+    /// the final `data: [DONE]`, without parsing it into chunks. Intended for
+    /// proxies relaying the stream to another client. Byte boundaries follow
+    /// the network reads, not event boundaries.
+    ///
+    /// # Example
+    /// ```rust,no_run
+    /// use cerebras_rs::{Client, ChatCompletionRequest, ModelIdentifier};
+    /// use futures_util::StreamExt;
+    /// use std::io::Write;
+    ///
+    /// # async fn example() -> Result<(), Box<dyn std::error::Error>> {
+    /// let client = Client::from_env()?;
+    /// let request = ChatCompletionRequest::builder(ModelIdentifier::Llama3Period18b)
+    ///     .user_message("Tell me a story")
+    ///     .build();
+    ///
+    /// let mut stdout = std::io::stdout();
+    /// let mut stream = Box::pin(client.chat_completion_stream_raw(request).await?);
+    /// while let Some(bytes) = stream.next().await {
+    ///     stdout.write_all(&bytes?)?;
+    /// }
+    /// # Ok(())
+    /// # }
+    /// ```
+    pub async fn chat_completion_stream_raw<R: Into<StreamingRequest>>(
+        &self,
+        request: R,
+    ) -> Result<impl Stream<Item = Result<bytes::Bytes>> + Send + use<R>> {
+        let mut request = request.into().into_inner();
+        self.apply_default_max_tokens(&mut request.max_tokens, request.model);
+        let configuration = self.request_configuration();
+        let response = cancellable(
+            "create_chat_completion_stream_raw",
+            &configuration,
+            crate::streaming::send_streaming_request(
+                &configuration,
+                EndpointCategory::Chat,
+                "chat/completions",
+                &request,
+            ),
+        )
+        .await?;
+        Ok(crate::streaming::until_shutdown(
+            response
+                .bytes_stream()
+                .map(|bytes| bytes.map_err(Error::from)),
+            configuration.shutdown.clone(),
+        ))
     }
 
     /// Create a text completion
-    pub async fn completion(&self, request: CompletionRequest) -> Result<CreateCompletionResponse> {
-        let response = default_api::create_completion(&self.configuration, request).await?;
-        match response.entity {
-            Some(default_api::CreateCompletionSuccess::Status200(resp)) => match resp {
-                CreateCompletion200Response::CreateCompletionResponse(completion) => Ok(completion),
-                CreateCompletion200Response::CompletionChunk(_) => Err(Error::Api(
-                    "Unexpected streaming response for non-streaming request".into(),
-                )),
-            },
-            _ => Err(Error::Api("Unexpected response format".into())),
+    pub async fn completion(
+        &self,
+        mut request: CompletionRequest,
+    ) -> Result<CreateCompletionResponse> {
+        self.apply_default_max_tokens(&mut request.max_tokens, request.model);
+        let configuration = self.request_configuration();
+        let mut response = cancellable("create_completion", &configuration, async {
+            let response = default_api::create_completion(&configuration, request).await?;
+            match response.entity {
+                Some(default_api::CreateCompletionSuccess::Status200(resp)) => match resp {
+                    CreateCompletion200Response::CreateCompletionResponse(completion) => {
+                        Ok(completion)
+                    }
+                    CreateCompletion200Response::CompletionChunk(_) => Err(Error::Api(
+                        "Unexpected streaming response for non-streaming request".into(),
+                    )),
+                },
+                _ => Err(Error::Api("Unexpected response format".into())),
+            }
+        })
+        .await?;
+        if self.configuration.trim_output {
+            response.trim_whitespace();
         }
+        Ok(response)
     }
 
     /// Create a text completion with streaming
@@ -192,7 +860,79 @@ impl Client {
         mut request: CompletionRequest,
     ) -> Result<crate::streaming::CompletionStream> {
         request.stream = Some(true);
-        crate::streaming::CompletionStream::new(&self.configuration, request).await
+        self.apply_default_max_tokens(&mut request.max_tokens, request.model);
+        let configuration = self.request_configuration();
+        cancellable(
+            "create_completion_stream",
+            &configuration,
+            crate::streaming::CompletionStream::new(&configuration, request),
+        )
+        .await
+    }
+
+    /// Count the prompt tokens `text` occupies for `model`, as reported by the server
+    ///
+    /// The API has no dedicated tokenization endpoint, so this sends a text
+    /// completion capped at a single output token and returns the
+    /// `prompt_tokens` from its usage. The count is exact for the given model,
+    /// but the call is billed like any other completion of that size.
+    ///
+    /// # Example
+    /// ```rust,no_run
+    /// use cerebras_rs::{Client, ModelIdentifier};
+    ///
+    /// # async fn example() -> Result<(), Box<dyn std::error::Error>> {
+    /// let client = Client::from_env()?;
+    /// let tokens = client
+    ///     .count_tokens(ModelIdentifier::Llama3Period18b, "How many tokens is this?")
+    ///     .await?;
+    /// println!("{} tokens", tokens);
+    /// # Ok(())
+    /// # }
+    /// ```
+    pub async fn count_tokens<S: Into<String>>(
+        &self,
+        model: ModelIdentifier,
+        text: S,
+    ) -> Result<u32> {
+        let request = CompletionRequest {
+            model,
+            prompt: Prompt::String(text.into()),
+            max_tokens: Some(1),
+            ..Default::default()
+        };
+        let response = self.completion(request).await?;
+        response
+            .usage
+            .and_then(|usage| usage.prompt_tokens)
+            .and_then(|tokens| u32::try_from(tokens).ok())
+            .ok_or_else(|| Error::Api("Response did not include prompt token usage".into()))
+    }
+}
+
+/// Run a request under telemetry, failing with [`Error::Cancelled`] once the client is shut down
+///
+/// Errors are tagged with the request id, if the configuration has one.
+async fn cancellable<T, F>(
+    endpoint: &'static str,
+    configuration: &Configuration,
+    future: F,
+) -> Result<T>
+where
+    F: Future<Output = Result<T>>,
+{
+    let shutdown = configuration.shutdown.clone();
+    let result = telemetry::instrument(endpoint, configuration, async move {
+        tokio::select! {
+            biased;
+            _ = shutdown.cancelled() => Err(Error::Cancelled),
+            result = future => result,
+        }
+    })
+    .await;
+    match &configuration.request_id {
+        Some(request_id) => result.map_err(|e| e.with_request_id(request_id)),
+        None => result,
     }
 }
 
@@ -206,6 +946,7 @@ impl ChatMessage {
             name: None,
             tool_calls: None,
             tool_call_id: None,
+            audio: None,
         }
     }
 
@@ -217,6 +958,7 @@ impl ChatMessage {
             name: None,
             tool_calls: None,
             tool_call_id: None,
+            audio: None,
         }
     }
 
@@ -228,6 +970,40 @@ impl ChatMessage {
             name: None,
             tool_calls: None,
             tool_call_id: None,
+            audio: None,
+        }
+    }
+
+    /// Create an assistant message that made the given tool calls
+    ///
+    /// For rebuilding a tool-use turn from stored history; each call's `id`
+    /// should match the `tool_call_id` of the tool message answering it. An
+    /// empty `calls` gives a plain assistant message.
+    ///
+    /// # Example
+    /// ```rust
+    /// use cerebras_rs::{ChatMessage, FunctionCall};
+    ///
+    /// let history = vec![
+    ///     ChatMessage::user("What's the weather in Paris?"),
+    ///     ChatMessage::assistant_with_tool_calls(
+    ///         "",
+    ///         vec![FunctionCall {
+    ///             id: Some("call_1".into()),
+    ///             name: Some("get_weather".into()),
+    ///             arguments: Some(r#"{"location": "Paris"}"#.into()),
+    ///         }],
+    ///     ),
+    ///     ChatMessage::tool("Sunny, 22°C", "call_1"),
+    /// ];
+    /// ```
+    pub fn assistant_with_tool_calls<S: Into<String>>(
+        content: S,
+        calls: Vec<FunctionCall>,
+    ) -> Self {
+        Self {
+            tool_calls: (!calls.is_empty()).then_some(calls),
+            ..Self::assistant(content)
         }
     }
 
@@ -239,10 +1015,36 @@ impl ChatMessage {
             name: None,
             tool_calls: None,
             tool_call_id: Some(tool_call_id.into()),
+            audio: None,
         }
     }
 }
 
+// Convenience constructors for ToolChoiceOption
+impl ToolChoiceOption {
+    /// Let the model decide whether to call a tool
+    pub fn auto() -> Self {
+        Self::String("auto".to_string())
+    }
+
+    /// Prevent the model from calling any tool
+    pub fn none() -> Self {
+        Self::String("none".to_string())
+    }
+
+    /// Require the model to call at least one tool
+    pub fn required() -> Self {
+        Self::String("required".to_string())
+    }
+
+    /// Force the model to call the named function
+    pub fn function<S: Into<String>>(name: S) -> Self {
+        Self::FunctionName(FunctionName {
+            name: Some(name.into()),
+        })
+    }
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
@@ -266,4 +1068,947 @@ mod tests {
         assert_eq!(user.role, Role::User);
         assert_eq!(user.content, "Hello");
     }
+
+    #[tokio::test]
+    async fn test_get_bytes() {
+        use wiremock::matchers::{header, method, path};
+        use wiremock::{Mock, MockServer, ResponseTemplate};
+
+        let server = MockServer::start().await;
+        Mock::given(method("GET"))
+            .and(path("/files/abc"))
+            .and(header("authorization", "Bearer test-key"))
+            .respond_with(ResponseTemplate::new(200).set_body_bytes(b"raw\x00bytes".to_vec()))
+            .mount(&server)
+            .await;
+        Mock::given(method("GET"))
+            .and(path("/files/missing"))
+            .respond_with(ResponseTemplate::new(404).set_body_string(r#"{"message":"gone"}"#))
+            .mount(&server)
+            .await;
+
+        let client = Client::new("test-key").with_base_url(server.uri());
+        let body = client.get_bytes("/files/abc").await.unwrap();
+        assert_eq!(&body[..], b"raw\x00bytes");
+
+        let err = client.get_bytes("files/missing").await.unwrap_err();
+        assert!(matches!(err, Error::NotFound(ref m) if m == "gone"));
+    }
+
+    #[tokio::test]
+    async fn test_with_auth_header() {
+        use wiremock::matchers::{header, method, path};
+        use wiremock::{Mock, MockServer, ResponseTemplate};
+
+        let server = MockServer::start().await;
+        Mock::given(method("GET"))
+            .and(path("/models"))
+            .and(header("api-key", "test-key"))
+            .respond_with(
+                ResponseTemplate::new(200)
+                    .set_body_json(serde_json::json!({ "object": "list", "data": [] })),
+            )
+            .mount(&server)
+            .await;
+        Mock::given(method("POST"))
+            .and(path("/chat/completions"))
+            .and(header("api-key", "test-key"))
+            .respond_with(
+                ResponseTemplate::new(200)
+                    .insert_header("content-type", "text/event-stream")
+                    .set_body_string("data: [DONE]\n\n"),
+            )
+            .mount(&server)
+            .await;
+
+        let client = Client::new("test-key")
+            .with_base_url(server.uri())
+            .with_auth_header("api-key");
+        client.list_models().await.unwrap();
+        let request = ChatCompletionRequest::builder(ModelIdentifier::Llama3Period18b)
+            .user_message("Hi")
+            .build();
+        client
+            .chat_completion_stream(request)
+            .await
+            .unwrap()
+            .collect()
+            .await
+            .unwrap();
+
+        for request in server.received_requests().await.unwrap() {
+            assert!(!request.headers.contains_key("authorization"));
+        }
+    }
+
+    #[tokio::test]
+    async fn test_chat_completion_deadline() {
+        use std::time::Instant;
+        use wiremock::matchers::{method, path};
+        use wiremock::{Mock, MockServer, ResponseTemplate};
+
+        let server = MockServer::start().await;
+        Mock::given(method("POST"))
+            .and(path("/chat/completions"))
+            .respond_with(
+                ResponseTemplate::new(200)
+                    .set_body_json(serde_json::json!({ "choices": [] }))
+                    .set_delay(Duration::from_millis(200)),
+            )
+            .mount(&server)
+            .await;
+        let client = Client::new("test-key").with_base_url(server.uri());
+        let request = ChatCompletionRequest::builder(ModelIdentifier::Llama3Period18b)
+            .user_message("Hi")
+            .build();
+
+        let deadline = Instant::now() + Duration::from_secs(5);
+        assert!(
+            client
+                .chat_completion_deadline(request.clone(), deadline)
+                .await
+                .is_ok()
+        );
+
+        let started = Instant::now();
+        let deadline = started + Duration::from_millis(50);
+        let err = client
+            .chat_completion_deadline(request.clone(), deadline)
+            .await
+            .unwrap_err();
+        assert!(matches!(err, Error::Timeout));
+        assert!(started.elapsed() < Duration::from_millis(200));
+
+        // A deadline already passed fails without waiting
+        let err = client
+            .chat_completion_deadline(request, Instant::now() - Duration::from_millis(1))
+            .await
+            .unwrap_err();
+        assert!(matches!(err, Error::Timeout));
+    }
+
+    #[tokio::test]
+    async fn test_connection_failure() {
+        // Nothing listens on the discard port
+        let client = Client::new("test-key").with_base_url("http://127.0.0.1:9".to_string());
+        let request = ChatCompletionRequest::builder(ModelIdentifier::Llama3Period18b)
+            .user_message("Hi")
+            .build();
+
+        let err = client.chat_completion(request.clone()).await.unwrap_err();
+        assert!(matches!(err, Error::Connection(_)), "{:?}", err);
+        assert!(matches!(
+            client.chat_completion_stream(request).await,
+            Err(Error::Connection(_))
+        ));
+        assert!(matches!(
+            client.list_models().await,
+            Err(Error::Connection(_))
+        ));
+    }
+
+    #[tokio::test]
+    async fn test_trim_output() {
+        use wiremock::matchers::{method, path};
+        use wiremock::{Mock, MockServer, ResponseTemplate};
+
+        let server = MockServer::start().await;
+        Mock::given(method("POST"))
+            .and(path("/chat/completions"))
+            .respond_with(ResponseTemplate::new(200).set_body_json(serde_json::json!({
+                "choices": [{ "index": 0, "message": { "role": "assistant", "content": "\n Paris \n" } }]
+            })))
+            .mount(&server)
+            .await;
+        Mock::given(method("POST"))
+            .and(path("/completions"))
+            .respond_with(
+                ResponseTemplate::new(200)
+                    .insert_header("content-type", "text/event-stream")
+                    .set_body_string(format!(
+                        "data: {}\n\ndata: {}\n\ndata: [DONE]\n\n",
+                        r#"{"choices": [{"index": 0, "text": " Once"}]}"#,
+                        r#"{"choices": [{"index": 0, "text": " upon\n"}]}"#
+                    )),
+            )
+            .mount(&server)
+            .await;
+        let client = Client::new("test-key").with_base_url(server.uri());
+        let request = ChatCompletionRequest::builder(ModelIdentifier::Llama3Period18b)
+            .user_message("Capital of France?")
+            .build();
+        let completion = CompletionRequest {
+            model: ModelIdentifier::Llama3Period18b,
+            prompt: Prompt::String("Tell me a story".into()),
+            ..Default::default()
+        };
+
+        // Off by default
+        let response = client.chat_completion(request.clone()).await.unwrap();
+        assert_eq!(response.first_text().unwrap(), "\n Paris \n");
+
+        let client = client.with_trim_output(true);
+        let response = client.chat_completion(request).await.unwrap();
+        assert_eq!(response.first_text().unwrap(), "Paris");
+
+        let stream = client.completion_stream(completion).await.unwrap();
+        let collected = stream.collect().await.unwrap();
+        let choices = collected.choices.unwrap();
+        assert_eq!(choices[0].text.as_deref(), Some("Once upon"));
+    }
+
+    #[tokio::test]
+    async fn test_shutdown_cancels_requests_and_streams() {
+        use wiremock::matchers::{method, path};
+        use wiremock::{Mock, MockServer, ResponseTemplate};
+
+        let server = MockServer::start().await;
+        Mock::given(method("POST"))
+            .and(path("/chat/completions"))
+            .respond_with(
+                ResponseTemplate::new(200)
+                    .insert_header("content-type", "text/event-stream")
+                    .set_body_string(format!(
+                        "data: {}\n\ndata: [DONE]\n\n",
+                        r#"{"choices": []}"#
+                    )),
+            )
+            .mount(&server)
+            .await;
+        Mock::given(method("GET"))
+            .and(path("/models"))
+            .respond_with(
+                ResponseTemplate::new(200)
+                    .set_body_json(serde_json::json!({ "data": [] }))
+                    .set_delay(Duration::from_secs(10)),
+            )
+            .mount(&server)
+            .await;
+        let client = Client::new("test-key").with_base_url(server.uri());
+        let request = ChatCompletionRequest::builder(ModelIdentifier::Llama3Period18b)
+            .user_message("Hi")
+            .build();
+        let mut stream = client
+            .chat_completion_stream(request.clone())
+            .await
+            .unwrap();
+
+        let worker = client.clone();
+        let pending = tokio::spawn(async move { worker.list_models().await });
+        tokio::time::sleep(Duration::from_millis(100)).await;
+        assert!(!client.is_shut_down());
+        client.shutdown();
+
+        let result = tokio::time::timeout(Duration::from_secs(2), pending)
+            .await
+            .expect("request was not cancelled")
+            .unwrap();
+        assert!(matches!(result, Err(Error::Cancelled)));
+        assert!(matches!(stream.next().await, Some(Err(Error::Cancelled))));
+        assert!(stream.next().await.is_none());
+
+        // Later requests fail without being sent
+        let sent = server.received_requests().await.unwrap().len();
+        assert!(matches!(
+            client.chat_completion(request).await,
+            Err(Error::Cancelled)
+        ));
+        assert_eq!(server.received_requests().await.unwrap().len(), sent);
+    }
+
+    #[cfg(feature = "compression")]
+    #[tokio::test]
+    async fn test_with_compression() {
+        use wiremock::matchers::{method, path};
+        use wiremock::{Mock, MockServer, ResponseTemplate};
+
+        let server = MockServer::start().await;
+        let request = ChatCompletionRequest::builder(ModelIdentifier::Llama3Period18b)
+            .user_message("Hi")
+            .build();
+
+        // Streams still parse when the server may compress them
+        let client = Client::new("test-key").with_base_url(server.uri());
+        for enabled in [true, false] {
+            let client = client.clone().with_compression(enabled);
+            server.reset().await;
+            Mock::given(method("POST"))
+                .and(path("/chat/completions"))
+                .respond_with(
+                    ResponseTemplate::new(200)
+                        .insert_header("content-type", "text/event-stream")
+                        .set_body_string("data: [DONE]\n\n"),
+                )
+                .mount(&server)
+                .await;
+            client
+                .chat_completion_stream(request.clone())
+                .await
+                .unwrap()
+                .collect()
+                .await
+                .unwrap();
+
+            let received = server.received_requests().await.unwrap();
+            let accept = received[0]
+                .headers
+                .get("accept-encoding")
+                .map(|value| value.to_str().unwrap().to_string());
+            assert_eq!(
+                accept
+                    .as_deref()
+                    .is_some_and(|value| value.contains("gzip")),
+                enabled,
+                "{:?}",
+                accept
+            );
+        }
+    }
+
+    #[tokio::test]
+    async fn test_models_base_path_override() {
+        use wiremock::matchers::{method, path};
+        use wiremock::{Mock, MockServer, ResponseTemplate};
+
+        let gateway = MockServer::start().await;
+        Mock::given(method("GET"))
+            .and(path("/models"))
+            .respond_with(
+                ResponseTemplate::new(200)
+                    .set_body_json(serde_json::json!({ "object": "list", "data": [] })),
+            )
+            .expect(1)
+            .mount(&gateway)
+            .await;
+
+        let mut configuration = Configuration::new();
+        configuration.bearer_access_token = Some("test-key".to_string());
+        configuration.base_path = "http://127.0.0.1:9".to_string();
+        configuration.models_base_path = Some(gateway.uri());
+
+        let client = Client::with_configuration(configuration);
+        let models = client.list_models().await.unwrap();
+        assert_eq!(models.data, Some(vec![]));
+        assert_eq!(
+            client
+                .configuration()
+                .base_path_for(crate::EndpointCategory::Chat),
+            "http://127.0.0.1:9"
+        );
+    }
+
+    #[test]
+    fn test_validate_key() {
+        let validate = |key: Option<&str>| {
+            let mut configuration = Configuration::new();
+            configuration.bearer_access_token = key.map(str::to_string);
+            configuration.validate_key()
+        };
+
+        assert!(validate(Some("csk-abcdefghijklmnopqrstuvwxyz0123456789")).is_ok());
+        assert!(validate(Some("future_format_key_0123456789")).is_ok());
+        for key in [
+            None,
+            Some(""),
+            Some("   "),
+            Some("csk-abcdefghijklmnop\n"),
+            Some("csk-abcdefg hijklmnop"),
+            Some("\"csk-abcdefghijklmnop\""),
+            Some("csk-short"),
+        ] {
+            assert!(
+                matches!(validate(key), Err(Error::Configuration(_))),
+                "{:?} should be rejected",
+                key
+            );
+        }
+    }
+
+    #[test]
+    fn test_add_root_certificate_rejects_garbage() {
+        let mut configuration = Configuration::new();
+        for bytes in [
+            &b"not a certificate"[..],
+            b"-----BEGIN CERTIFICATE-----\ngarbage\n-----END CERTIFICATE-----\n",
+        ] {
+            let result = configuration.add_root_certificate(bytes);
+            assert!(
+                matches!(result, Err(Error::Configuration(_))),
+                "{:?}",
+                result
+            );
+        }
+        assert!(configuration.root_certificates.is_empty());
+    }
+
+    #[tokio::test]
+    async fn test_with_http2() {
+        use wiremock::matchers::{method, path};
+        use wiremock::{Mock, MockServer, ResponseTemplate};
+
+        let server = MockServer::start().await;
+        Mock::given(method("GET"))
+            .and(path("/models"))
+            .respond_with(ResponseTemplate::new(200).set_body_json(serde_json::json!({})))
+            .mount(&server)
+            .await;
+
+        let client = Client::new("test-key")
+            .with_base_url(server.uri())
+            .with_http2();
+        assert!(client.configuration().http2_prior_knowledge);
+        // The mock server accepts h2c, so a prior-knowledge request succeeds
+        assert!(client.list_models().await.is_ok());
+    }
+
+    #[tokio::test]
+    #[cfg(any(feature = "rustls-tls", feature = "native-tls"))]
+    async fn test_with_connect_timeout() {
+        // The kernel completes the TCP handshake, but since nothing accepts
+        // the connection, the TLS handshake never gets a reply
+        let listener = tokio::net::TcpListener::bind("127.0.0.1:0").await.unwrap();
+        let timeout = Duration::from_millis(200);
+        let client = Client::new("test-key")
+            .with_base_url(format!("https://{}", listener.local_addr().unwrap()))
+            .with_connect_timeout(timeout);
+        assert_eq!(client.configuration().connect_timeout, Some(timeout));
+
+        let started = std::time::Instant::now();
+        let err = client.list_models().await.unwrap_err();
+        assert!(matches!(err, Error::Connection(_)), "{:?}", err);
+        assert!(started.elapsed() >= timeout);
+        assert!(started.elapsed() < Duration::from_secs(5));
+        drop(listener);
+    }
+
+    #[tokio::test]
+    async fn test_max_response_bytes() {
+        use wiremock::matchers::{method, path};
+        use wiremock::{Mock, MockServer, ResponseTemplate};
+
+        let server = MockServer::start().await;
+        let models = serde_json::json!({
+            "object": "list",
+            "data": [{ "id": "llama3.1-8b", "object": "model", "owned_by": "Cerebras" }]
+        });
+        Mock::given(method("GET"))
+            .and(path("/models"))
+            .respond_with(ResponseTemplate::new(200).set_body_json(&models))
+            .mount(&server)
+            .await;
+
+        let mut configuration = Configuration::new();
+        configuration.base_path = server.uri();
+        configuration.bearer_access_token = Some("test-key".to_string());
+        configuration.max_response_bytes = Some(16);
+        let client = Client::with_configuration(configuration.clone());
+        let result = client.list_models().await;
+        assert!(
+            matches!(&result, Err(Error::Api(message)) if message.contains("exceeds")),
+            "{:?}",
+            result
+        );
+        assert!(matches!(
+            client.get_bytes("models").await,
+            Err(Error::Api(_))
+        ));
+
+        configuration.max_response_bytes = Some(4096);
+        let client = Client::with_configuration(configuration);
+        assert_eq!(client.list_models().await.unwrap().data.unwrap().len(), 1);
+    }
+
+    #[tokio::test]
+    async fn test_chat_completion_stream_raw() {
+        use futures_util::StreamExt;
+        use wiremock::matchers::{method, path};
+        use wiremock::{Mock, MockServer, ResponseTemplate};
+
+        let body = "data: {\"choices\":[{\"index\":0,\"delta\":{\"content\":\"Hi\"}}]}\n\ndata: [DONE]\n\n";
+        let server = MockServer::start().await;
+        Mock::given(method("POST"))
+            .and(path("/chat/completions"))
+            .respond_with(
+                ResponseTemplate::new(200)
+                    .insert_header("content-type", "text/event-stream")
+                    .set_body_string(body),
+            )
+            .mount(&server)
+            .await;
+
+        let client = Client::new("test-key").with_base_url(server.uri());
+        let request = ChatCompletionRequest::builder(ModelIdentifier::Llama3Period18b)
+            .user_message("Hi")
+            .build();
+        let stream = client.chat_completion_stream_raw(request).await.unwrap();
+        let chunks: Vec<_> = stream.collect().await;
+        let received: Vec<u8> = chunks
+            .into_iter()
+            .flat_map(|chunk| chunk.unwrap().to_vec())
+            .collect();
+        assert_eq!(received, body.as_bytes());
+
+        let requests = server.received_requests().await.unwrap();
+        let sent: serde_json::Value = serde_json::from_slice(&requests[0].body).unwrap();
+        assert_eq!(sent["stream"], true);
+    }
+
+    #[tokio::test]
+    async fn test_chat_completion_stream_channel() {
+        use wiremock::matchers::{method, path};
+        use wiremock::{Mock, MockServer, ResponseTemplate};
+
+        let body = [
+            r#"data: {"choices":[{"index":0,"delta":{"role":"assistant","content":""}}]}"#,
+            r#"data: {"choices":[{"index":0,"delta":{"content":"Hello"}}]}"#,
+            r#"data: {"choices":[{"index":0,"delta":{"content":", world"},"finish_reason":"stop"}]}"#,
+            "data: [DONE]",
+        ]
+        .join("\n\n")
+            + "\n\n";
+        let server = MockServer::start().await;
+        Mock::given(method("POST"))
+            .and(path("/chat/completions"))
+            .respond_with(
+                ResponseTemplate::new(200)
+                    .insert_header("content-type", "text/event-stream")
+                    .set_body_string(body),
+            )
+            .mount(&server)
+            .await;
+
+        let client = Client::new("test-key").with_base_url(server.uri());
+        let request = ChatCompletionRequest::builder(ModelIdentifier::Llama3Period18b)
+            .user_message("Hi")
+            .build();
+        let (tokens, handle) = client.chat_completion_stream_channel(request.clone());
+        let tokens: Vec<_> = tokens.map(|t| t.unwrap()).collect().await;
+        assert_eq!(tokens, vec!["Hello", ", world"]);
+        let completion = handle.await.unwrap().unwrap();
+        assert_eq!(
+            completion.choices.unwrap()[0]
+                .message
+                .as_ref()
+                .unwrap()
+                .content,
+            "Hello, world"
+        );
+
+        // Failures close the channel after forwarding the error
+        let client = Client::new("test-key").with_base_url("http://127.0.0.1:9".to_string());
+        let (tokens, handle) = client.chat_completion_stream_channel(request);
+        let tokens: Vec<_> = tokens.collect().await;
+        assert_eq!(tokens.len(), 1);
+        assert!(matches!(tokens[0], Err(Error::Streaming(_))));
+        assert!(handle.await.unwrap().is_err());
+    }
+
+    #[tokio::test]
+    async fn test_response_cache() {
+        use wiremock::matchers::{method, path};
+        use wiremock::{Mock, MockServer, ResponseTemplate};
+
+        let server = MockServer::start().await;
+        Mock::given(method("POST"))
+            .and(path("/chat/completions"))
+            .respond_with(ResponseTemplate::new(200).set_body_json(serde_json::json!({
+                "id": "chatcmpl-1",
+                "choices": [{ "index": 0, "message": { "role": "assistant", "content": "4" } }]
+            })))
+            .mount(&server)
+            .await;
+
+        let client = Client::new("test-key")
+            .with_base_url(server.uri())
+            .with_response_cache(10, Duration::from_secs(60));
+        let request = ChatCompletionRequest::builder(ModelIdentifier::Llama3Period18b)
+            .user_message("What is 2 + 2?")
+            .temperature(0.0)
+            .build();
+
+        let first = client.chat_completion(request.clone()).await.unwrap();
+        let second = client
+            .clone()
+            .chat_completion(request.clone())
+            .await
+            .unwrap();
+        assert_eq!(first, second);
+        assert_eq!(server.received_requests().await.unwrap().len(), 1);
+
+        // Sampled requests always go to the API
+        let mut sampled = request;
+        sampled.temperature = Some(0.7);
+        client.chat_completion(sampled.clone()).await.unwrap();
+        client.chat_completion(sampled).await.unwrap();
+        assert_eq!(server.received_requests().await.unwrap().len(), 3);
+    }
+
+    #[tokio::test]
+    async fn test_quick_chat() {
+        use wiremock::matchers::{body_partial_json, method, path};
+        use wiremock::{Mock, MockServer, ResponseTemplate};
+
+        let server = MockServer::start().await;
+        Mock::given(method("POST"))
+            .and(path("/chat/completions"))
+            .and(body_partial_json(serde_json::json!({
+                "model": "llama3.1-8b",
+                "messages": [{ "role": "user", "content": "Hi" }]
+            })))
+            .respond_with(ResponseTemplate::new(200).set_body_json(serde_json::json!({
+                "choices": [{
+                    "index": 0,
+                    "message": { "role": "assistant", "content": "Hello!" },
+                    "finish_reason": "stop"
+                }]
+            })))
+            .mount(&server)
+            .await;
+
+        let client = Client::new("test-key").with_base_url(server.uri());
+        let reply = client
+            .quick_chat(ModelIdentifier::Llama3Period18b, "Hi")
+            .await
+            .unwrap();
+        assert_eq!(reply, "Hello!");
+    }
+
+    #[test]
+    fn test_model_aliases() {
+        use std::collections::HashMap;
+
+        assert_eq!(
+            "llama-3.3-70b".parse::<ModelIdentifier>().unwrap(),
+            ModelIdentifier::Llama3Period370b
+        );
+        assert!(matches!(
+            "gpt-4".parse::<ModelIdentifier>(),
+            Err(Error::InvalidModel(ref name)) if name == "gpt-4"
+        ));
+        // Wire names agree with serde and round-trip through Display
+        for model in ModelIdentifier::ALL {
+            assert_eq!(model.to_string().parse::<ModelIdentifier>().unwrap(), model);
+            assert_eq!(serde_json::to_value(model).unwrap(), model.as_str());
+        }
+
+        let client = Client::new("test-key")
+            .with_model_aliases(HashMap::from([
+                ("default".to_string(), ModelIdentifier::Llama3Period370b),
+                ("fast".to_string(), ModelIdentifier::Llama3Period18b),
+            ]))
+            .with_model_aliases(HashMap::from([(
+                "default".to_string(),
+                ModelIdentifier::Qwen332b,
+            )]));
+        assert_eq!(
+            client.resolve_model("default").unwrap(),
+            ModelIdentifier::Qwen332b
+        );
+        assert_eq!(
+            client.resolve_model("fast").unwrap(),
+            ModelIdentifier::Llama3Period18b
+        );
+        assert_eq!(
+            client.resolve_model("llama3.1-8b").unwrap(),
+            ModelIdentifier::Llama3Period18b
+        );
+        assert!(matches!(
+            client.resolve_model("turbo"),
+            Err(Error::InvalidModel(_))
+        ));
+    }
+
+    #[tokio::test]
+    async fn test_default_max_tokens() {
+        use wiremock::matchers::{method, path};
+        use wiremock::{Mock, MockServer, ResponseTemplate};
+
+        let server = MockServer::start().await;
+        Mock::given(method("POST"))
+            .and(path("/chat/completions"))
+            .respond_with(ResponseTemplate::new(200).set_body_json(serde_json::json!({
+                "choices": [{
+                    "index": 0,
+                    "message": { "role": "assistant", "content": "Hi" },
+                    "finish_reason": "stop"
+                }]
+            })))
+            .mount(&server)
+            .await;
+
+        let client = Client::new("test-key")
+            .with_base_url(server.uri())
+            .with_default_max_tokens(256)
+            .with_model_max_tokens(ModelIdentifier::Qwen332b, 1024);
+        let request = |model, max_tokens: Option<u32>| {
+            let mut request = ChatCompletionRequest::builder(model)
+                .user_message("Hi")
+                .build();
+            request.max_tokens = max_tokens;
+            request
+        };
+        for request in [
+            request(ModelIdentifier::Llama3Period18b, None),
+            request(ModelIdentifier::Qwen332b, None),
+            request(ModelIdentifier::Llama3Period18b, Some(10)),
+        ] {
+            client.chat_completion(request).await.unwrap();
+        }
+
+        let sent: Vec<_> = server
+            .received_requests()
+            .await
+            .unwrap()
+            .iter()
+            .map(|r| {
+                serde_json::from_slice::<serde_json::Value>(&r.body).unwrap()["max_tokens"].clone()
+            })
+            .collect();
+        assert_eq!(sent, vec![256, 1024, 10]);
+        assert_eq!(
+            Client::new("test-key")
+                .configuration()
+                .max_tokens_for(ModelIdentifier::Qwen332b),
+            None
+        );
+    }
+
+    #[tokio::test]
+    async fn test_list_model_ids() {
+        use wiremock::matchers::{method, path};
+        use wiremock::{Mock, MockServer, ResponseTemplate};
+
+        let server = MockServer::start().await;
+        Mock::given(method("GET"))
+            .and(path("/models"))
+            .respond_with(ResponseTemplate::new(200).set_body_json(serde_json::json!({
+                "object": "list",
+                "data": [
+                    { "id": "llama3.1-8b", "object": "model" },
+                    { "object": "model" },
+                    { "id": "qwen-3-32b", "object": "model" }
+                ]
+            })))
+            .mount(&server)
+            .await;
+
+        let client = Client::new("test-key").with_base_url(server.uri());
+        let ids = client.list_model_ids().await.unwrap();
+        assert_eq!(ids, vec!["llama3.1-8b", "qwen-3-32b"]);
+    }
+
+    #[tokio::test]
+    async fn test_quick_chat_with() {
+        use wiremock::matchers::{body_partial_json, method, path};
+        use wiremock::{Mock, MockServer, ResponseTemplate};
+
+        let server = MockServer::start().await;
+        Mock::given(method("POST"))
+            .and(path("/chat/completions"))
+            .and(body_partial_json(serde_json::json!({
+                "messages": [{ "role": "user", "content": "Hi" }],
+                "temperature": 0.0,
+                "max_tokens": 16
+            })))
+            .respond_with(ResponseTemplate::new(200).set_body_json(serde_json::json!({
+                "choices": [{
+                    "index": 0,
+                    "message": { "role": "assistant", "content": "Hello!" },
+                    "finish_reason": "stop"
+                }]
+            })))
+            .mount(&server)
+            .await;
+        Mock::given(method("POST"))
+            .and(path("/chat/completions"))
+            .respond_with(
+                ResponseTemplate::new(200).set_body_json(serde_json::json!({ "choices": [] })),
+            )
+            .mount(&server)
+            .await;
+
+        let client = Client::new("test-key").with_base_url(server.uri());
+        let reply = client
+            .quick_chat_with(ModelIdentifier::Llama3Period18b, "Hi", 0.0, 16)
+            .await
+            .unwrap();
+        assert_eq!(reply, "Hello!");
+        let reply = client
+            .ask_with(
+                ModelIdentifier::Llama3Period18b,
+                String::from("Hi"),
+                0.0,
+                16,
+            )
+            .await
+            .unwrap();
+        assert_eq!(reply, "Hello!");
+
+        let err = client
+            .quick_chat(ModelIdentifier::Llama3Period18b, "Hi")
+            .await
+            .unwrap_err();
+        assert!(matches!(err, Error::Api(_)));
+        let err = client
+            .ask(ModelIdentifier::Llama3Period18b, "Hi")
+            .await
+            .unwrap_err();
+        assert!(matches!(err, Error::Api(_)));
+    }
+
+    #[tokio::test]
+    async fn test_count_tokens() {
+        use wiremock::matchers::{body_partial_json, method, path};
+        use wiremock::{Mock, MockServer, ResponseTemplate};
+
+        let server = MockServer::start().await;
+        Mock::given(method("POST"))
+            .and(path("/completions"))
+            .and(body_partial_json(serde_json::json!({
+                "prompt": "count me",
+                "max_tokens": 1
+            })))
+            .respond_with(ResponseTemplate::new(200).set_body_json(serde_json::json!({
+                "choices": [{ "index": 0, "text": "!", "finish_reason": "length" }],
+                "usage": { "prompt_tokens": 3, "completion_tokens": 1, "total_tokens": 4 }
+            })))
+            .mount(&server)
+            .await;
+
+        let client = Client::new("test-key").with_base_url(server.uri());
+        let tokens = client
+            .count_tokens(ModelIdentifier::Llama3Period18b, "count me")
+            .await
+            .unwrap();
+        assert_eq!(tokens, 3);
+    }
+
+    #[tokio::test]
+    async fn test_request_id_header() {
+        use wiremock::matchers::{header, header_exists, method, path};
+        use wiremock::{Mock, MockServer, ResponseTemplate};
+
+        let server = MockServer::start().await;
+        Mock::given(method("GET"))
+            .and(path("/models"))
+            .and(header("x-client-request-id", "trace-123"))
+            .respond_with(ResponseTemplate::new(200).set_body_json(serde_json::json!({})))
+            .expect(1)
+            .mount(&server)
+            .await;
+        Mock::given(method("GET"))
+            .and(path("/models"))
+            .and(header_exists("x-client-request-id"))
+            .respond_with(ResponseTemplate::new(200).set_body_json(serde_json::json!({})))
+            .expect(2)
+            .mount(&server)
+            .await;
+
+        let client = Client::new("test-key")
+            .with_base_url(server.uri())
+            .with_request_id_generation(true);
+        client
+            .with_request_id("trace-123")
+            .list_models()
+            .await
+            .unwrap();
+        client.list_models().await.unwrap();
+        client.list_models().await.unwrap();
+
+        let requests = server.received_requests().await.unwrap();
+        let generated: Vec<_> = requests[1..]
+            .iter()
+            .map(|r| r.headers.get("x-client-request-id").unwrap().clone())
+            .collect();
+        assert_ne!(generated[0], generated[1]);
+        assert!(uuid::Uuid::parse_str(generated[0].to_str().unwrap()).is_ok());
+    }
+
+    #[tokio::test]
+    async fn test_request_id_in_errors() {
+        use wiremock::matchers::{method, path};
+        use wiremock::{Mock, MockServer, ResponseTemplate};
+
+        let server = MockServer::start().await;
+        Mock::given(method("GET"))
+            .and(path("/models"))
+            .respond_with(
+                ResponseTemplate::new(404).set_body_json(serde_json::json!({ "message": "gone" })),
+            )
+            .mount(&server)
+            .await;
+        let client = Client::new("test-key").with_base_url(server.uri());
+
+        let err = client
+            .with_request_id("trace-123")
+            .list_models()
+            .await
+            .unwrap_err();
+        assert_eq!(err.request_id(), Some("trace-123"));
+        assert!(err.to_string().contains("trace-123"), "{}", err);
+        assert!(matches!(err.root(), Error::NotFound(m) if m == "gone"));
+        assert!(matches!(err.into_root(), Error::NotFound(_)));
+
+        // Generated ids are the ones sent
+        let err = client
+            .clone()
+            .with_request_id_generation(true)
+            .list_models()
+            .await
+            .unwrap_err();
+        let requests = server.received_requests().await.unwrap();
+        let sent = requests[1].headers.get("x-client-request-id").unwrap();
+        assert_eq!(err.request_id(), Some(sent.to_str().unwrap()));
+
+        // Without an id errors are returned as they were
+        let err = client.list_models().await.unwrap_err();
+        assert!(matches!(err, Error::NotFound(_)));
+        assert_eq!(err.request_id(), None);
+    }
+
+    #[test]
+    fn test_assistant_with_tool_calls() {
+        let call = FunctionCall {
+            id: Some("call_1".into()),
+            name: Some("add".into()),
+            arguments: Some(r#"{"a": 1}"#.into()),
+        };
+        let message = ChatMessage::assistant_with_tool_calls("Adding", vec![call]);
+        assert_eq!(
+            serde_json::to_value(&message).unwrap(),
+            serde_json::json!({
+                "role": "assistant",
+                "content": "Adding",
+                "tool_calls": [{ "id": "call_1", "name": "add", "arguments": "{\"a\": 1}" }]
+            })
+        );
+        let parsed: ChatMessage =
+            serde_json::from_value(serde_json::to_value(&message).unwrap()).unwrap();
+        assert_eq!(parsed, message);
+
+        assert_eq!(
+            ChatMessage::assistant_with_tool_calls("Hi", Vec::new()),
+            ChatMessage::assistant("Hi")
+        );
+    }
+
+    #[test]
+    fn test_tool_choice_helpers() {
+        assert_eq!(
+            serde_json::to_value(ToolChoiceOption::auto()).unwrap(),
+            "auto"
+        );
+        assert_eq!(
+            serde_json::to_value(ToolChoiceOption::none()).unwrap(),
+            "none"
+        );
+        assert_eq!(
+            serde_json::to_value(ToolChoiceOption::required()).unwrap(),
+            "required"
+        );
+        assert_eq!(
+            serde_json::to_value(ToolChoiceOption::function("get_weather")).unwrap(),
+            serde_json::json!({ "name": "get_weather" })
+        );
+    }
 }
@@ -0,0 +1,158 @@
+//! A chat history bound to a model, for multi-turn sessions
+
+use serde::{Deserialize, Serialize};
+
+use crate::{
+    Client, Result,
+    models::{ChatCompletionRequest, ChatMessage, ModelIdentifier},
+    tokens,
+};
+
+/// An ordered chat history for one model
+///
+/// # Example
+/// ```rust
+/// use cerebras_rs::{ChatMessage, Client, Conversation, ModelIdentifier};
+///
+/// let client = Client::new("test-key").with_context_window(ModelIdentifier::Llama3Period18b, 8192);
+///
+/// let mut conversation = Conversation::new(ModelIdentifier::Llama3Period18b);
+/// conversation.push(ChatMessage::system("You are a helpful assistant"));
+/// conversation.push(ChatMessage::user("What is the capital of France?"));
+///
+/// let remaining = conversation.remaining_budget(&client).unwrap();
+/// assert!(remaining < 8192);
+/// ```
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct Conversation {
+    model: ModelIdentifier,
+    messages: Vec<ChatMessage>,
+}
+
+impl Conversation {
+    /// Start an empty conversation with `model`
+    pub fn new(model: ModelIdentifier) -> Self {
+        Self {
+            model,
+            messages: Vec::new(),
+        }
+    }
+
+    /// The model the conversation is held with
+    pub fn model(&self) -> ModelIdentifier {
+        self.model
+    }
+
+    /// The messages so far, oldest first
+    pub fn messages(&self) -> &[ChatMessage] {
+        &self.messages
+    }
+
+    /// Append a message to the history
+    pub fn push(&mut self, message: ChatMessage) {
+        self.messages.push(message);
+    }
+
+    /// A request carrying the full history, ready to send
+    pub fn request(&self) -> ChatCompletionRequest {
+        ChatCompletionRequest::new(self.model, self.messages.clone())
+    }
+
+    /// Serialize the model and history as pretty-printed JSON
+    ///
+    /// Save the result to resume the session later with
+    /// [`from_json`](Self::from_json), or attach it to a bug report to replay
+    /// the exact history.
+    ///
+    /// # Example
+    /// ```rust
+    /// use cerebras_rs::{ChatMessage, Conversation, ModelIdentifier};
+    ///
+    /// let mut conversation = Conversation::new(ModelIdentifier::Llama3Period18b);
+    /// conversation.push(ChatMessage::user("Hello"));
+    ///
+    /// let saved = conversation.to_json()?;
+    /// assert_eq!(Conversation::from_json(&saved)?, conversation);
+    /// # Ok::<(), cerebras_rs::Error>(())
+    /// ```
+    pub fn to_json(&self) -> Result<String> {
+        Ok(serde_json::to_string_pretty(self)?)
+    }
+
+    /// Restore a conversation saved with [`to_json`](Self::to_json)
+    pub fn from_json(json: &str) -> Result<Self> {
+        Ok(serde_json::from_str(json)?)
+    }
+
+    /// Estimated prompt tokens for the history; see [`tokens::estimate_message_tokens`]
+    pub fn estimated_tokens(&self) -> u32 {
+        tokens::estimate_message_tokens(&self.messages)
+    }
+
+    /// Estimated tokens left in the model's context window
+    ///
+    /// Returns `None` if `client` has no context window configured for the
+    /// model (see [`Client::with_context_window`]). Reaches zero once the
+    /// history is estimated to fill the window; since the estimate is a
+    /// heuristic, leave some headroom before deciding to trim or summarize.
+    pub fn remaining_budget(&self, client: &Client) -> Option<u32> {
+        let window = client.context_window(self.model)?;
+        Some(window.saturating_sub(self.estimated_tokens()))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_remaining_budget() {
+        let mut conversation = Conversation::new(ModelIdentifier::Llama3Period18b);
+        conversation.push(ChatMessage::user("a".repeat(400)));
+        assert_eq!(conversation.estimated_tokens(), 104);
+
+        let client = Client::new("test-key");
+        assert_eq!(conversation.remaining_budget(&client), None);
+
+        let client = client.with_context_window(ModelIdentifier::Llama3Period18b, 1000);
+        assert_eq!(conversation.remaining_budget(&client), Some(896));
+
+        let client = client.with_context_window(ModelIdentifier::Llama3Period18b, 100);
+        assert_eq!(conversation.remaining_budget(&client), Some(0));
+
+        let request = conversation.request();
+        assert_eq!(request.model, ModelIdentifier::Llama3Period18b);
+        assert_eq!(request.messages, conversation.messages());
+    }
+
+    #[test]
+    fn test_json_round_trip() {
+        use crate::models::FunctionCall;
+
+        let mut conversation = Conversation::new(ModelIdentifier::Qwen332b);
+        conversation.push(ChatMessage::system("You can call tools"));
+        conversation.push(ChatMessage::user("What's 2 + 3?"));
+        let mut call = ChatMessage::assistant("");
+        call.tool_calls = Some(vec![FunctionCall {
+            id: Some("call_1".into()),
+            name: Some("add".into()),
+            arguments: Some(r#"{"a": 2, "b": 3}"#.into()),
+        }]);
+        conversation.push(call);
+        conversation.push(ChatMessage::tool("5", "call_1"));
+
+        let json = conversation.to_json().unwrap();
+        let restored = Conversation::from_json(&json).unwrap();
+        assert_eq!(restored, conversation);
+        assert_eq!(restored.model(), ModelIdentifier::Qwen332b);
+        assert_eq!(
+            restored.messages()[3].tool_call_id.as_deref(),
+            Some("call_1")
+        );
+
+        assert!(matches!(
+            Conversation::from_json("{\"messages\": []}"),
+            Err(crate::Error::Serialization(_))
+        ));
+    }
+}
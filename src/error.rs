@@ -10,7 +10,13 @@ pub type Result<T> = std::result::Result<T, Error>;
 pub enum Error {
     /// HTTP request failed
     #[error("HTTP request failed: {0}")]
-    Http(#[from] reqwest::Error),
+    Http(reqwest::Error),
+
+    /// The API couldn't be reached: DNS resolution, the TCP connection or the TLS handshake failed
+    ///
+    /// Nothing was sent, so the request is always safe to retry.
+    #[error("Connection failed: {0}")]
+    Connection(String),
 
     /// API returned an error response
     #[error("API error: {0}")]
@@ -33,6 +39,18 @@ pub enum Error {
     #[error("Serialization error: {0}")]
     Serialization(#[from] serde_json::Error),
 
+    /// A stream event didn't match the expected schema
+    ///
+    /// Carries the start of the offending payload, truncated to 256
+    /// characters, to help diagnose schema mismatches.
+    #[error("Failed to parse {payload:?}: {source}")]
+    Decode {
+        /// The underlying parse error
+        source: serde_json::Error,
+        /// The start of the payload that failed to parse
+        payload: String,
+    },
+
     /// Configuration error
     #[error("Configuration error: {0}")]
     Configuration(String),
@@ -41,7 +59,9 @@ pub enum Error {
     #[error("Invalid model identifier: {0}")]
     InvalidModel(String),
 
-    /// Rate limit exceeded
+    /// Rate limit exceeded, with the seconds until it resets from the `Retry-After` header
+    ///
+    /// `0` when the API didn't say.
     #[error("Rate limit exceeded. Please retry after {0} seconds")]
     RateLimit(u64),
 
@@ -57,6 +77,17 @@ pub enum Error {
     #[error("Invalid request: {0}")]
     InvalidRequest(String),
 
+    /// The prompt and requested completion exceed the model's context window
+    #[error("Context length exceeded: {0}")]
+    ContextLengthExceeded(String),
+
+    /// The account has run out of credits or hit its billing limit
+    #[error("Quota exceeded: {message}")]
+    QuotaExceeded {
+        /// Error message returned by the API
+        message: String,
+    },
+
     /// Server error
     #[error("Server error: {0}")]
     ServerError(String),
@@ -69,41 +100,260 @@ pub enum Error {
     #[error("Request timed out")]
     Timeout,
 
+    /// The client was shut down while the request was in flight, or before it was sent
+    #[error("Request cancelled: the client has been shut down")]
+    Cancelled,
+
     /// Unknown error
     #[error("Unknown error occurred")]
     Unknown,
+
+    /// A request sent with a client request id failed with `source`
+    ///
+    /// Only returned when the client has a request id, set with
+    /// [`Client::with_request_id`](crate::Client::with_request_id) or
+    /// generated with
+    /// [`Client::with_request_id_generation`](crate::Client::with_request_id_generation).
+    /// Code matching on error variants directly won't see the underlying
+    /// error once request ids are enabled; match on [`root`](Self::root) to
+    /// handle it whether or not it was tagged.
+    #[error("{source} (request id {request_id})")]
+    WithRequestId {
+        /// The `X-Client-Request-Id` the request was sent with
+        request_id: String,
+        /// The error the request failed with
+        source: Box<Error>,
+    },
+}
+
+/// Maximum number of characters of a payload kept in [`Error::Decode`]
+const PAYLOAD_SNIPPET_CHARS: usize = 256;
+
+impl Error {
+    /// The client request id the failed request was sent with, if any
+    ///
+    /// # Example
+    /// ```rust,no_run
+    /// use cerebras_rs::{Client, Error};
+    ///
+    /// # async fn example() -> Result<(), Box<dyn std::error::Error>> {
+    /// let client = Client::from_env()?.with_request_id_generation(true);
+    /// if let Err(error) = client.list_models().await {
+    ///     eprintln!("request {:?} failed: {}", error.request_id(), error);
+    ///     if let Error::RateLimit(seconds) = error.root() {
+    ///         println!("retry in {} seconds", seconds);
+    ///     }
+    /// }
+    /// # Ok(())
+    /// # }
+    /// ```
+    pub fn request_id(&self) -> Option<&str> {
+        match self {
+            Error::WithRequestId { request_id, .. } => Some(request_id),
+            _ => None,
+        }
+    }
+
+    /// The underlying error, looking through [`Error::WithRequestId`]
+    pub fn root(&self) -> &Error {
+        match self {
+            Error::WithRequestId { source, .. } => source.root(),
+            error => error,
+        }
+    }
+
+    /// Like [`root`](Self::root), taking ownership
+    pub fn into_root(self) -> Error {
+        match self {
+            Error::WithRequestId { source, .. } => source.into_root(),
+            error => error,
+        }
+    }
+
+    /// Tag the error with the request id it was sent with, unless it already is
+    pub(crate) fn with_request_id(self, request_id: &str) -> Self {
+        match self {
+            Error::WithRequestId { .. } => self,
+            source => Error::WithRequestId {
+                request_id: request_id.to_string(),
+                source: Box::new(source),
+            },
+        }
+    }
+
+    /// Wrap a parse error together with a truncated copy of the payload that caused it
+    pub(crate) fn decode(source: serde_json::Error, payload: &str) -> Self {
+        let payload = match payload.char_indices().nth(PAYLOAD_SNIPPET_CHARS) {
+            Some((end, _)) => format!("{}...", &payload[..end]),
+            None => payload.to_string(),
+        };
+        Error::Decode { source, payload }
+    }
+
+    /// Map an HTTP error status and its body to the matching error variant
+    ///
+    /// The body may be the error detail itself or wrapped in an `error`
+    /// envelope. Statuses without a dedicated variant map to
+    /// [`Error::ApiError`] when the body carries a type or code.
+    pub(crate) fn from_status(status: reqwest::StatusCode, content: &str) -> Self {
+        let detail = parse_error_body(content);
+        let is_context_length = detail.as_ref().is_some_and(is_context_length_error);
+        let is_quota = detail.as_ref().is_some_and(is_quota_error);
+        let message = detail.as_ref().and_then(|d| d.message.clone());
+
+        match status.as_u16() {
+            402 => Error::QuotaExceeded {
+                message: message.unwrap_or_else(|| "Payment required".to_string()),
+            },
+            403 | 429 if is_quota => Error::QuotaExceeded {
+                message: message.unwrap_or_else(|| "Quota exceeded".to_string()),
+            },
+            400 if is_context_length => Error::ContextLengthExceeded(
+                message.unwrap_or_else(|| "Context length exceeded".to_string()),
+            ),
+            400 => Error::InvalidRequest(message.unwrap_or_else(|| "Bad request".to_string())),
+            401 => Error::Authentication,
+            404 => Error::NotFound(message.unwrap_or_else(|| "Resource not found".to_string())),
+            422 => {
+                Error::InvalidRequest(message.unwrap_or_else(|| "Invalid parameters".to_string()))
+            }
+            429 => Error::RateLimit(0),
+            500..=599 => {
+                Error::ServerError(message.unwrap_or_else(|| "Internal server error".to_string()))
+            }
+            _ => match detail {
+                Some(detail) if detail.r#type.is_some() || detail.code.is_some() => {
+                    Error::from_detail(detail)
+                }
+                _ => Error::Api(format!("HTTP {}: {}", status, content)),
+            },
+        }
+    }
+
+    /// Fill in a rate limit's reset time from the response's `Retry-After` header
+    ///
+    /// The header may give either a number of seconds or an HTTP date. Other
+    /// errors, and rate limits that already carry a wait, are returned as is.
+    pub(crate) fn with_retry_after(self, headers: &reqwest::header::HeaderMap) -> Self {
+        match self {
+            Error::RateLimit(0) => Error::RateLimit(retry_after(headers).unwrap_or(0)),
+            e => e,
+        }
+    }
+
+    /// Map an error body received without a meaningful status, e.g. mid-stream
+    pub(crate) fn from_detail(detail: crate::models::ErrorDetail) -> Self {
+        if is_context_length_error(&detail) {
+            return Error::ContextLengthExceeded(
+                detail
+                    .message
+                    .unwrap_or_else(|| "Context length exceeded".to_string()),
+            );
+        }
+
+        if is_quota_error(&detail) {
+            return Error::QuotaExceeded {
+                message: detail
+                    .message
+                    .unwrap_or_else(|| "Quota exceeded".to_string()),
+            };
+        }
+
+        let error_type = detail.r#type.unwrap_or_default();
+        let code = detail.code.unwrap_or_default();
+        if error_type.contains("rate_limit") || code.contains("rate_limit") {
+            return Error::RateLimit(0);
+        }
+        if error_type.contains("authentication") || code == "invalid_api_key" {
+            return Error::Authentication;
+        }
+        Error::ApiError {
+            error_type,
+            code,
+            message: detail.message.unwrap_or_default(),
+            param: detail.param,
+        }
+    }
+}
+
+/// Parse an error response body, either bare or inside an `error` envelope
+fn parse_error_body(content: &str) -> Option<crate::models::ErrorDetail> {
+    #[derive(serde::Deserialize)]
+    struct Envelope {
+        error: crate::models::ErrorDetail,
+    }
+
+    match serde_json::from_str::<Envelope>(content) {
+        Ok(Envelope { error }) => Some(error),
+        Err(_) => serde_json::from_str(content).ok(),
+    }
+}
+
+/// Seconds to wait according to a `Retry-After` header, if it has a valid one
+fn retry_after(headers: &reqwest::header::HeaderMap) -> Option<u64> {
+    let value = headers
+        .get(reqwest::header::RETRY_AFTER)?
+        .to_str()
+        .ok()?
+        .trim();
+    if let Ok(seconds) = value.parse() {
+        return Some(seconds);
+    }
+    let reset = chrono::DateTime::parse_from_rfc2822(value).ok()?;
+    let remaining = reset.timestamp() - chrono::Utc::now().timestamp();
+    Some(remaining.max(0) as u64)
+}
+
+/// Whether an error body reports that the context window was exceeded
+fn is_context_length_error(detail: &crate::models::ErrorDetail) -> bool {
+    if detail.code.as_deref() == Some("context_length_exceeded") {
+        return true;
+    }
+    detail.message.as_deref().is_some_and(|message| {
+        let message = message.to_lowercase();
+        message.contains("context length") || message.contains("context window")
+    })
+}
+
+/// Whether an error body reports that the account's credits or billing quota are exhausted
+fn is_quota_error(detail: &crate::models::ErrorDetail) -> bool {
+    let code = detail.code.as_deref().unwrap_or_default();
+    let error_type = detail.r#type.as_deref().unwrap_or_default();
+    ["quota", "billing", "credit"]
+        .iter()
+        .any(|keyword| code.contains(keyword) || error_type.contains(keyword))
+}
+
+impl From<reqwest::Error> for Error {
+    /// Classify connect-phase failures as [`Error::Connection`], keeping the cause chain
+    fn from(err: reqwest::Error) -> Self {
+        if !err.is_connect() {
+            return Error::Http(err);
+        }
+        let mut message = err.to_string();
+        let mut source = std::error::Error::source(&err);
+        while let Some(cause) = source {
+            message.push_str(": ");
+            message.push_str(&cause.to_string());
+            source = cause.source();
+        }
+        Error::Connection(message)
+    }
 }
 
 impl From<crate::apis::Error<crate::apis::default_api::CreateChatCompletionError>> for Error {
     fn from(err: crate::apis::Error<crate::apis::default_api::CreateChatCompletionError>) -> Self {
         use crate::apis::Error as ApiError;
-        use crate::apis::default_api::CreateChatCompletionError;
 
         match err {
-            ApiError::Reqwest(e) => Error::Http(e),
+            ApiError::Reqwest(e) => Error::from(e),
             ApiError::Serde(e) => Error::Serialization(e),
             ApiError::Io(e) => Error::Api(format!("IO error: {}", e)),
+            // The typed error entities are untagged and any error body matches the
+            // first variant, so map from the actual status code instead
             ApiError::ResponseError(response) => {
-                match response.entity {
-                    Some(CreateChatCompletionError::Status400(detail)) => Error::InvalidRequest(
-                        detail.message.unwrap_or_else(|| "Bad request".to_string()),
-                    ),
-                    Some(CreateChatCompletionError::Status401(_)) => Error::Authentication,
-                    Some(CreateChatCompletionError::Status422(detail)) => Error::InvalidRequest(
-                        detail
-                            .message
-                            .unwrap_or_else(|| "Invalid parameters".to_string()),
-                    ),
-                    Some(CreateChatCompletionError::Status429(_detail)) => {
-                        Error::RateLimit(0) // Could parse from headers if available
-                    }
-                    Some(CreateChatCompletionError::Status500(detail)) => Error::ServerError(
-                        detail
-                            .message
-                            .unwrap_or_else(|| "Internal server error".to_string()),
-                    ),
-                    _ => Error::Api(format!("HTTP {}: {}", response.status, response.content)),
-                }
+                Error::from_status(response.status, &response.content)
+                    .with_retry_after(&response.headers)
             }
         }
     }
@@ -112,30 +362,17 @@ impl From<crate::apis::Error<crate::apis::default_api::CreateChatCompletionError
 impl From<crate::apis::Error<crate::apis::default_api::CreateCompletionError>> for Error {
     fn from(err: crate::apis::Error<crate::apis::default_api::CreateCompletionError>) -> Self {
         use crate::apis::Error as ApiError;
-        use crate::apis::default_api::CreateCompletionError;
 
         match err {
-            ApiError::Reqwest(e) => Error::Http(e),
+            ApiError::Reqwest(e) => Error::from(e),
             ApiError::Serde(e) => Error::Serialization(e),
             ApiError::Io(e) => Error::Api(format!("IO error: {}", e)),
-            ApiError::ResponseError(response) => match response.entity {
-                Some(CreateCompletionError::Status400(detail)) => Error::InvalidRequest(
-                    detail.message.unwrap_or_else(|| "Bad request".to_string()),
-                ),
-                Some(CreateCompletionError::Status401(_)) => Error::Authentication,
-                Some(CreateCompletionError::Status422(detail)) => Error::InvalidRequest(
-                    detail
-                        .message
-                        .unwrap_or_else(|| "Invalid parameters".to_string()),
-                ),
-                Some(CreateCompletionError::Status429(_detail)) => Error::RateLimit(0),
-                Some(CreateCompletionError::Status500(detail)) => Error::ServerError(
-                    detail
-                        .message
-                        .unwrap_or_else(|| "Internal server error".to_string()),
-                ),
-                _ => Error::Api(format!("HTTP {}: {}", response.status, response.content)),
-            },
+            // The typed error entities are untagged and any error body matches the
+            // first variant, so map from the actual status code instead
+            ApiError::ResponseError(response) => {
+                Error::from_status(response.status, &response.content)
+                    .with_retry_after(&response.headers)
+            }
         }
     }
 }
@@ -143,22 +380,17 @@ impl From<crate::apis::Error<crate::apis::default_api::CreateCompletionError>> f
 impl From<crate::apis::Error<crate::apis::default_api::ListModelsError>> for Error {
     fn from(err: crate::apis::Error<crate::apis::default_api::ListModelsError>) -> Self {
         use crate::apis::Error as ApiError;
-        use crate::apis::default_api::ListModelsError;
 
         match err {
-            ApiError::Reqwest(e) => Error::Http(e),
+            ApiError::Reqwest(e) => Error::from(e),
             ApiError::Serde(e) => Error::Serialization(e),
             ApiError::Io(e) => Error::Api(format!("IO error: {}", e)),
-            ApiError::ResponseError(response) => match response.entity {
-                Some(ListModelsError::Status401(_)) => Error::Authentication,
-                Some(ListModelsError::Status429(_)) => Error::RateLimit(0),
-                Some(ListModelsError::Status500(detail)) => Error::ServerError(
-                    detail
-                        .message
-                        .unwrap_or_else(|| "Internal server error".to_string()),
-                ),
-                _ => Error::Api(format!("HTTP {}: {}", response.status, response.content)),
-            },
+            // The typed error entities are untagged and any error body matches the
+            // first variant, so map from the actual status code instead
+            ApiError::ResponseError(response) => {
+                Error::from_status(response.status, &response.content)
+                    .with_retry_after(&response.headers)
+            }
         }
     }
 }
@@ -166,27 +398,17 @@ impl From<crate::apis::Error<crate::apis::default_api::ListModelsError>> for Err
 impl From<crate::apis::Error<crate::apis::default_api::RetrieveModelError>> for Error {
     fn from(err: crate::apis::Error<crate::apis::default_api::RetrieveModelError>) -> Self {
         use crate::apis::Error as ApiError;
-        use crate::apis::default_api::RetrieveModelError;
 
         match err {
-            ApiError::Reqwest(e) => Error::Http(e),
+            ApiError::Reqwest(e) => Error::from(e),
             ApiError::Serde(e) => Error::Serialization(e),
             ApiError::Io(e) => Error::Api(format!("IO error: {}", e)),
-            ApiError::ResponseError(response) => match response.entity {
-                Some(RetrieveModelError::Status401(_)) => Error::Authentication,
-                Some(RetrieveModelError::Status404(detail)) => Error::NotFound(
-                    detail
-                        .message
-                        .unwrap_or_else(|| "Model not found".to_string()),
-                ),
-                Some(RetrieveModelError::Status429(_)) => Error::RateLimit(0),
-                Some(RetrieveModelError::Status500(detail)) => Error::ServerError(
-                    detail
-                        .message
-                        .unwrap_or_else(|| "Internal server error".to_string()),
-                ),
-                _ => Error::Api(format!("HTTP {}: {}", response.status, response.content)),
-            },
+            // The typed error entities are untagged and any error body matches the
+            // first variant, so map from the actual status code instead
+            ApiError::ResponseError(response) => {
+                Error::from_status(response.status, &response.content)
+                    .with_retry_after(&response.headers)
+            }
         }
     }
 }
@@ -209,4 +431,132 @@ mod tests {
             "Rate limit exceeded. Please retry after 60 seconds"
         );
     }
+
+    #[test]
+    fn test_from_status() {
+        let err = Error::from_status(
+            reqwest::StatusCode::NOT_FOUND,
+            r#"{"message": "No such file"}"#,
+        );
+        assert!(matches!(err, Error::NotFound(ref m) if m == "No such file"));
+
+        let err = Error::from_status(reqwest::StatusCode::UNAUTHORIZED, "");
+        assert!(matches!(err, Error::Authentication));
+
+        let err = Error::from_status(reqwest::StatusCode::BAD_GATEWAY, "not json");
+        assert!(matches!(err, Error::ServerError(ref m) if m == "Internal server error"));
+
+        let err = Error::from_status(
+            reqwest::StatusCode::BAD_REQUEST,
+            r#"{"code": "context_length_exceeded", "message": "Too long"}"#,
+        );
+        assert!(matches!(err, Error::ContextLengthExceeded(ref m) if m == "Too long"));
+
+        let err = Error::from_status(
+            reqwest::StatusCode::BAD_REQUEST,
+            r#"{"message": "Please reduce the length: maximum context length is 8192 tokens"}"#,
+        );
+        assert!(matches!(err, Error::ContextLengthExceeded(_)));
+
+        let err = Error::from_status(reqwest::StatusCode::IM_A_TEAPOT, "teapot");
+        assert!(matches!(err, Error::Api(ref m) if m.contains("teapot")));
+    }
+
+    #[test]
+    fn test_decode_truncates_payload() {
+        let source = serde_json::from_str::<u32>("x").unwrap_err();
+        let err = Error::decode(source, r#"{"choices": "oops"}"#);
+        assert!(
+            matches!(err, Error::Decode { ref payload, .. } if payload == r#"{"choices": "oops"}"#)
+        );
+        assert!(err.to_string().contains("oops"));
+
+        let source = serde_json::from_str::<u32>("x").unwrap_err();
+        let long = "é".repeat(PAYLOAD_SNIPPET_CHARS + 10);
+        let Error::Decode { payload, .. } = Error::decode(source, &long) else {
+            panic!("expected a decode error");
+        };
+        assert_eq!(payload.chars().count(), PAYLOAD_SNIPPET_CHARS + 3);
+        assert!(payload.ends_with("é..."));
+    }
+
+    #[test]
+    fn test_from_status_error_envelope() {
+        let err = Error::from_status(
+            reqwest::StatusCode::UNPROCESSABLE_ENTITY,
+            r#"{"error": {"type": "invalid_request_error", "message": "temperature must be <= 2"}}"#,
+        );
+        assert!(matches!(err, Error::InvalidRequest(ref m) if m == "temperature must be <= 2"));
+
+        let err = Error::from_status(
+            reqwest::StatusCode::CONFLICT,
+            r#"{"error": {"type": "conflict", "code": "busy", "message": "Try later", "param": "model"}}"#,
+        );
+        assert!(matches!(
+            err,
+            Error::ApiError { ref code, ref param, .. } if code == "busy" && param.as_deref() == Some("model")
+        ));
+    }
+
+    #[test]
+    fn test_quota_errors() {
+        let billing = r#"{
+            "type": "billing_error",
+            "code": "insufficient_quota",
+            "message": "You have run out of credits"
+        }"#;
+        let detail: crate::models::ErrorDetail = serde_json::from_str(billing).unwrap();
+        assert!(is_quota_error(&detail));
+
+        for status in [
+            reqwest::StatusCode::PAYMENT_REQUIRED,
+            reqwest::StatusCode::FORBIDDEN,
+            reqwest::StatusCode::TOO_MANY_REQUESTS,
+        ] {
+            let err = Error::from_status(status, billing);
+            assert!(
+                matches!(err, Error::QuotaExceeded { ref message } if message == "You have run out of credits")
+            );
+        }
+        assert!(matches!(
+            Error::from_detail(detail),
+            Error::QuotaExceeded { .. }
+        ));
+
+        // Ordinary rate limiting and permission errors are unaffected
+        let err = Error::from_status(
+            reqwest::StatusCode::TOO_MANY_REQUESTS,
+            r#"{"code": "rate_limit_exceeded"}"#,
+        );
+        assert!(matches!(err, Error::RateLimit(_)));
+        let err = Error::from_status(reqwest::StatusCode::FORBIDDEN, "teapot");
+        assert!(matches!(err, Error::Api(ref m) if m.contains("teapot")));
+    }
+
+    #[test]
+    fn test_retry_after() {
+        let rate_limit = |retry_after: &str| {
+            let mut headers = reqwest::header::HeaderMap::new();
+            headers.insert(reqwest::header::RETRY_AFTER, retry_after.parse().unwrap());
+            Error::from_status(reqwest::StatusCode::TOO_MANY_REQUESTS, "")
+                .with_retry_after(&headers)
+        };
+
+        assert!(matches!(rate_limit("30"), Error::RateLimit(30)));
+        let in_a_minute = (chrono::Utc::now() + chrono::Duration::seconds(60)).to_rfc2822();
+        assert!(matches!(
+            rate_limit(&in_a_minute),
+            Error::RateLimit(58..=60)
+        ));
+        assert!(matches!(
+            rate_limit("Wed, 21 Oct 2015 07:28:00 GMT"),
+            Error::RateLimit(0)
+        ));
+        assert!(matches!(rate_limit("soon"), Error::RateLimit(0)));
+
+        // Without the header the wait stays unknown
+        let err = Error::from_status(reqwest::StatusCode::TOO_MANY_REQUESTS, "")
+            .with_retry_after(&reqwest::header::HeaderMap::new());
+        assert!(matches!(err, Error::RateLimit(0)));
+    }
 }
@@ -99,10 +99,13 @@
 //!     
 //!     match client.chat_completion(request).await {
 //!         Ok(response) => println!("Success!"),
-//!         Err(Error::RateLimit(retry_after)) => {
-//!             println!("Rate limited, retry after {} seconds", retry_after);
+//!         // `root` looks through the request id errors may be tagged with
+//!         Err(e) => match e.root() {
+//!             Error::RateLimit(retry_after) => {
+//!                 println!("Rate limited, retry after {} seconds", retry_after);
+//!             }
+//!             _ => eprintln!("Error: {}", e),
 //!         },
-//!         Err(e) => eprintln!("Error: {}", e),
 //!     }
 //!     
 //!     Ok(())
@@ -157,14 +160,8 @@
 //!     while let Some(chunk) = stream.next().await {
 //!         match chunk {
 //!             Ok(chunk) => {
-//!                 if let Some(choices) = &chunk.choices {
-//!                     if let Some(choice) = choices.first() {
-//!                         if let Some(delta) = &choice.delta {
-//!                             if let Some(content) = &delta.content {
-//!                                 print!("{}", content);
-//!                             }
-//!                         }
-//!                     }
+//!                 if let Some(content) = chunk.delta_text() {
+//!                     print!("{}", content);
 //!                 }
 //!             }
 //!             Err(e) => eprintln!("Error: {}", e),
@@ -235,6 +232,7 @@
 //! ```
 
 extern crate base64;
+extern crate bytes;
 extern crate chrono;
 extern crate reqwest;
 extern crate serde;
@@ -258,7 +256,7 @@ pub mod apis;
 pub mod models;
 
 // Re-export commonly used types at the crate root
-pub use apis::configuration::{ApiKey, Configuration};
+pub use apis::configuration::{ApiKey, Configuration, EndpointCategory, REQUEST_ID_HEADER};
 pub use apis::default_api as api;
 
 // Re-export all models at the crate root for convenience
@@ -274,6 +272,49 @@ pub mod builders;
 // Streaming support
 pub mod streaming;
 
+// Adaptive requests that recover from context and length limits
+pub mod adaptive;
+
+// Concurrent batches of requests
+pub mod batch;
+
+// Response caching
+pub mod cache;
+
+// Retry utilities
+pub mod retry;
+pub use retry::Backoff;
+
+// Tool call dispatch
+pub mod tools;
+pub use tools::{ToolDispatcher, ToolRun};
+
+// Recording and replaying HTTP interactions for tests
+#[cfg(feature = "vcr")]
+pub mod vcr;
+
+// Multi-turn chat history
+pub mod conversation;
+pub use conversation::Conversation;
+
+// Offline token estimates
+pub mod tokens;
+
+// Client-side request validation
+mod validation;
+pub use validation::RoleOrdering;
+
+// Request composition helpers
+mod requests;
+pub use requests::{MessageMerge, NonStreamingRequest, StreamingRequest};
+
+// Response convenience helpers
+mod responses;
+pub use responses::ObjectType;
+
+// Request tracing
+mod telemetry;
+
 // Error handling
 mod error;
 pub use error::{Error, Result};
@@ -282,19 +323,47 @@ pub use error::{Error, Result};
 pub mod prelude {
     //! The prelude module provides convenient imports for common usage.
     //!
+    //! Besides the core request and client types, it brings in the
+    //! higher-level helpers: tool dispatch, stream adapters and metrics,
+    //! adaptive requests, and [`StreamExt`] for consuming streams.
+    //!
     //! # Example
-    //! ```rust
+    //! ```rust,no_run
     //! use cerebras_rs::prelude::*;
+    //!
+    //! # async fn example() -> Result<()> {
+    //! let client = Client::from_env()?;
+    //! let request = ChatCompletionRequest::builder(ModelIdentifier::Llama3Period18b)
+    //!     .user_message("Tell me a story")
+    //!     .build();
+    //!
+    //! let tokens = client.chat_completion_stream(request).await?.text_stream();
+    //! let mut sentences = Box::pin(ops::buffer_until_sentence(tokens));
+    //! while let Some(sentence) = sentences.next().await {
+    //!     println!("{}", sentence?);
+    //! }
+    //! # Ok(())
+    //! # }
     //! ```
 
     pub use crate::{
-        ChatCompletionRequest, ChatMessage, Client, CompletionRequest, Error, ModelIdentifier,
-        Result,
+        ChatCompletionRequest, ChatMessage, Client, CompletionRequest, Conversation, Error,
+        MessageMerge, ModelIdentifier, Result, ToolChoiceOption,
     };
 
-    pub use crate::streaming::{ChatCompletionStream, CompletionStream};
+    pub use crate::streaming::{
+        ChatCompletionStream, CompletionStream, StreamMetrics, TokenTimings, ops,
+    };
+
+    pub use crate::builders::{ChatCompletionBuilder, CompletionBuilder, Sampling};
+
+    pub use crate::tools::{ToolDispatcher, ToolRun};
+
+    pub use crate::adaptive::{AdaptiveOptions, TrimStrategy};
+
+    pub use crate::retry::Backoff;
 
-    pub use crate::builders::{ChatCompletionBuilder, CompletionBuilder};
+    pub use futures_util::StreamExt;
 }
 
 // Version information
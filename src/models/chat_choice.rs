@@ -19,6 +19,9 @@ pub struct ChatChoice {
     pub message: Option<models::ChatMessage>,
     #[serde(rename = "finish_reason", skip_serializing_if = "Option::is_none")]
     pub finish_reason: Option<FinishReason>,
+    /// The stop sequence that ended generation, when `finish_reason` is `stop`
+    #[serde(rename = "matched_stop", skip_serializing_if = "Option::is_none")]
+    pub matched_stop: Option<serde_json::Value>,
 }
 
 impl ChatChoice {
@@ -27,6 +30,7 @@ impl ChatChoice {
             index: None,
             message: None,
             finish_reason: None,
+            matched_stop: None,
         }
     }
 }
@@ -41,6 +45,9 @@ pub enum FinishReason {
     ToolCalls,
     #[serde(rename = "content_filter")]
     ContentFilter,
+    /// Any finish reason not known to this version of the SDK
+    #[serde(rename = "unknown", other)]
+    Unknown,
 }
 
 impl Default for FinishReason {
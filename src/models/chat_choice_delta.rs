@@ -41,6 +41,9 @@ pub enum FinishReason {
     ToolCalls,
     #[serde(rename = "content_filter")]
     ContentFilter,
+    /// Any finish reason not known to this version of the SDK
+    #[serde(rename = "unknown", other)]
+    Unknown,
 }
 
 impl Default for FinishReason {
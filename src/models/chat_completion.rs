@@ -18,7 +18,7 @@ pub struct ChatCompletion {
     #[serde(rename = "object", skip_serializing_if = "Option::is_none")]
     pub object: Option<Object>,
     #[serde(rename = "created", skip_serializing_if = "Option::is_none")]
-    pub created: Option<i32>,
+    pub created: Option<i64>,
     #[serde(rename = "model", skip_serializing_if = "Option::is_none")]
     pub model: Option<String>,
     #[serde(rename = "system_fingerprint", skip_serializing_if = "Option::is_none")]
@@ -18,13 +18,16 @@ pub struct ChatCompletionChunk {
     #[serde(rename = "object", skip_serializing_if = "Option::is_none")]
     pub object: Option<Object>,
     #[serde(rename = "created", skip_serializing_if = "Option::is_none")]
-    pub created: Option<i32>,
+    pub created: Option<i64>,
     #[serde(rename = "model", skip_serializing_if = "Option::is_none")]
     pub model: Option<String>,
     #[serde(rename = "system_fingerprint", skip_serializing_if = "Option::is_none")]
     pub system_fingerprint: Option<String>,
     #[serde(rename = "choices", skip_serializing_if = "Option::is_none")]
     pub choices: Option<Vec<models::ChatChoiceDelta>>,
+    /// Token usage so far, sent when `stream_options.include_usage` is set
+    #[serde(rename = "usage", skip_serializing_if = "Option::is_none")]
+    pub usage: Option<models::Usage>,
 }
 
 impl ChatCompletionChunk {
@@ -36,13 +39,14 @@ impl ChatCompletionChunk {
             model: None,
             system_fingerprint: None,
             choices: None,
+            usage: None,
         }
     }
 }
 ///
 #[derive(Clone, Copy, Debug, Eq, PartialEq, Ord, PartialOrd, Hash, Serialize, Deserialize)]
 pub enum Object {
-    #[serde(rename = "chat.completion.chunk")]
+    #[serde(rename = "chat.completion.chunk", other)]
     ChatPeriodCompletionPeriodChunk,
 }
 
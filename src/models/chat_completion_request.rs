@@ -27,9 +27,24 @@ pub struct ChatCompletionRequest {
     /// Nucleus sampling parameter
     #[serde(rename = "top_p", skip_serializing_if = "Option::is_none")]
     pub top_p: Option<f64>,
+    /// Only sample from the `top_k` most likely tokens
+    #[serde(rename = "top_k", skip_serializing_if = "Option::is_none")]
+    pub top_k: Option<u32>,
+    /// Penalize tokens in proportion to how often they have appeared so far
+    #[serde(rename = "frequency_penalty", skip_serializing_if = "Option::is_none")]
+    pub frequency_penalty: Option<f64>,
+    /// Penalize tokens that have appeared at all so far
+    #[serde(rename = "presence_penalty", skip_serializing_if = "Option::is_none")]
+    pub presence_penalty: Option<f64>,
+    /// Sample deterministically, so repeated requests with the same seed return the same result
+    #[serde(rename = "seed", skip_serializing_if = "Option::is_none")]
+    pub seed: Option<i64>,
     /// If set, partial message deltas will be sent
     #[serde(rename = "stream", skip_serializing_if = "Option::is_none")]
     pub stream: Option<bool>,
+    /// Options for streamed responses; only valid when `stream` is set
+    #[serde(rename = "stream_options", skip_serializing_if = "Option::is_none")]
+    pub stream_options: Option<models::StreamOptions>,
     #[serde(rename = "stop", skip_serializing_if = "Option::is_none")]
     pub stop: Option<models::StopCondition>,
     #[serde(rename = "response_format", skip_serializing_if = "Option::is_none")]
@@ -38,6 +53,12 @@ pub struct ChatCompletionRequest {
     pub tools: Option<Vec<models::Tool>>,
     #[serde(rename = "tool_choice", skip_serializing_if = "Option::is_none")]
     pub tool_choice: Option<models::ToolChoiceOption>,
+    /// Output types the model should generate; defaults to text only
+    #[serde(rename = "modalities", skip_serializing_if = "Option::is_none")]
+    pub modalities: Option<Vec<models::Modality>>,
+    /// Let the server drop the oldest messages when the prompt is too long
+    #[serde(rename = "prompt_truncation", skip_serializing_if = "Option::is_none")]
+    pub prompt_truncation: Option<models::PromptTruncation>,
 }
 
 impl ChatCompletionRequest {
@@ -51,11 +72,18 @@ impl ChatCompletionRequest {
             max_tokens: None,
             temperature: None,
             top_p: None,
+            top_k: None,
+            frequency_penalty: None,
+            presence_penalty: None,
+            seed: None,
             stream: None,
+            stream_options: None,
             stop: None,
             response_format: None,
             tools: None,
             tool_choice: None,
+            modalities: None,
+            prompt_truncation: None,
         }
     }
 }
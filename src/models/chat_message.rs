@@ -16,7 +16,9 @@ pub struct ChatMessage {
     #[serde(rename = "role")]
     pub role: Role,
     /// The contents of the message
-    #[serde(rename = "content")]
+    ///
+    /// Empty when the API sends `null` or omits it, e.g. on assistant messages that only call tools.
+    #[serde(rename = "content", default, deserialize_with = "null_as_empty")]
     pub content: String,
     /// The name of the author of this message
     #[serde(rename = "name", skip_serializing_if = "Option::is_none")]
@@ -26,6 +28,16 @@ pub struct ChatMessage {
     /// Tool call that this message is responding to
     #[serde(rename = "tool_call_id", skip_serializing_if = "Option::is_none")]
     pub tool_call_id: Option<String>,
+    /// Audio output, present when audio was requested with `modalities`
+    #[serde(rename = "audio", skip_serializing_if = "Option::is_none")]
+    pub audio: Option<models::ChatMessageAudio>,
+}
+
+fn null_as_empty<'de, D>(deserializer: D) -> Result<String, D::Error>
+where
+    D: serde::Deserializer<'de>,
+{
+    Option::<String>::deserialize(deserializer).map(Option::unwrap_or_default)
 }
 
 impl ChatMessage {
@@ -36,6 +48,7 @@ impl ChatMessage {
             name: None,
             tool_calls: None,
             tool_call_id: None,
+            audio: None,
         }
     }
 }
@@ -0,0 +1,39 @@
+/*
+ * Cerebras Inference API
+ *
+ * The Cerebras Inference API offers developers a low-latency solution for AI model inference  powered by Cerebras Wafer-Scale Engines and CS-3 systems. The API provides access to  high-performance language models with unprecedented speed for AI inference workloads.
+ *
+ * The version of the OpenAPI document: 1.0.0
+ * Contact: support@cerebras.ai
+ * Generated by: https://openapi-generator.tech
+ */
+
+use crate::models;
+use serde::{Deserialize, Serialize};
+
+#[derive(Clone, Default, Debug, PartialEq, Serialize, Deserialize)]
+pub struct ChatMessageAudio {
+    /// Identifier of the audio response
+    #[serde(rename = "id", skip_serializing_if = "Option::is_none")]
+    pub id: Option<String>,
+    /// Base64-encoded audio bytes
+    #[serde(rename = "data", skip_serializing_if = "Option::is_none")]
+    pub data: Option<String>,
+    /// Unix timestamp after which the audio can no longer be referenced
+    #[serde(rename = "expires_at", skip_serializing_if = "Option::is_none")]
+    pub expires_at: Option<i64>,
+    /// Transcript of the generated audio
+    #[serde(rename = "transcript", skip_serializing_if = "Option::is_none")]
+    pub transcript: Option<String>,
+}
+
+impl ChatMessageAudio {
+    pub fn new() -> ChatMessageAudio {
+        ChatMessageAudio {
+            id: None,
+            data: None,
+            expires_at: None,
+            transcript: None,
+        }
+    }
+}
@@ -19,6 +19,12 @@ pub struct CompletionChoice {
     pub text: Option<String>,
     #[serde(rename = "finish_reason", skip_serializing_if = "Option::is_none")]
     pub finish_reason: Option<FinishReason>,
+    /// Log probabilities of the generated tokens, present when requested with `logprobs`
+    #[serde(rename = "logprobs", skip_serializing_if = "Option::is_none")]
+    pub logprobs: Option<models::CompletionLogprobs>,
+    /// The stop sequence that ended generation, when `finish_reason` is `stop`
+    #[serde(rename = "matched_stop", skip_serializing_if = "Option::is_none")]
+    pub matched_stop: Option<serde_json::Value>,
 }
 
 impl CompletionChoice {
@@ -27,6 +33,8 @@ impl CompletionChoice {
             index: None,
             text: None,
             finish_reason: None,
+            logprobs: None,
+            matched_stop: None,
         }
     }
 }
@@ -37,6 +45,15 @@ pub enum FinishReason {
     Stop,
     #[serde(rename = "length")]
     Length,
+    /// The model called a tool
+    #[serde(rename = "tool_calls")]
+    ToolCalls,
+    /// Content was omitted by a content filter
+    #[serde(rename = "content_filter")]
+    ContentFilter,
+    /// Any finish reason not known to this version of the SDK
+    #[serde(rename = "unknown", other)]
+    Unknown,
 }
 
 impl Default for FinishReason {
@@ -37,6 +37,15 @@ pub enum FinishReason {
     Stop,
     #[serde(rename = "length")]
     Length,
+    /// The model called a tool
+    #[serde(rename = "tool_calls")]
+    ToolCalls,
+    /// Content was omitted by a content filter
+    #[serde(rename = "content_filter")]
+    ContentFilter,
+    /// Any finish reason not known to this version of the SDK
+    #[serde(rename = "unknown", other)]
+    Unknown,
 }
 
 impl Default for FinishReason {
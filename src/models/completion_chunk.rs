@@ -18,11 +18,14 @@ pub struct CompletionChunk {
     #[serde(rename = "object", skip_serializing_if = "Option::is_none")]
     pub object: Option<Object>,
     #[serde(rename = "created", skip_serializing_if = "Option::is_none")]
-    pub created: Option<i32>,
+    pub created: Option<i64>,
     #[serde(rename = "model", skip_serializing_if = "Option::is_none")]
     pub model: Option<String>,
     #[serde(rename = "choices", skip_serializing_if = "Option::is_none")]
     pub choices: Option<Vec<models::CompletionChoiceDelta>>,
+    /// Token usage, sent when `stream_options.include_usage` is set
+    #[serde(rename = "usage", skip_serializing_if = "Option::is_none")]
+    pub usage: Option<models::Usage>,
 }
 
 impl CompletionChunk {
@@ -33,13 +36,14 @@ impl CompletionChunk {
             created: None,
             model: None,
             choices: None,
+            usage: None,
         }
     }
 }
 ///
 #[derive(Clone, Copy, Debug, Eq, PartialEq, Ord, PartialOrd, Hash, Serialize, Deserialize)]
 pub enum Object {
-    #[serde(rename = "text_completion.chunk")]
+    #[serde(rename = "text_completion.chunk", other)]
     TextCompletionPeriodChunk,
 }
 
@@ -0,0 +1,40 @@
+/*
+ * Cerebras Inference API
+ *
+ * The Cerebras Inference API offers developers a low-latency solution for AI model inference  powered by Cerebras Wafer-Scale Engines and CS-3 systems. The API provides access to  high-performance language models with unprecedented speed for AI inference workloads.
+ *
+ * The version of the OpenAPI document: 1.0.0
+ * Contact: support@cerebras.ai
+ * Generated by: https://openapi-generator.tech
+ */
+
+use crate::models;
+use serde::{Deserialize, Serialize};
+use std::collections::HashMap;
+
+#[derive(Clone, Default, Debug, PartialEq, Serialize, Deserialize)]
+pub struct CompletionLogprobs {
+    /// The generated tokens
+    #[serde(rename = "tokens", skip_serializing_if = "Option::is_none")]
+    pub tokens: Option<Vec<String>>,
+    /// The log probability of each token; `null` where unavailable
+    #[serde(rename = "token_logprobs", skip_serializing_if = "Option::is_none")]
+    pub token_logprobs: Option<Vec<Option<f64>>>,
+    /// The most likely alternatives at each position, mapped to their log probabilities
+    #[serde(rename = "top_logprobs", skip_serializing_if = "Option::is_none")]
+    pub top_logprobs: Option<Vec<Option<HashMap<String, f64>>>>,
+    /// The character offset of each token in the returned text
+    #[serde(rename = "text_offset", skip_serializing_if = "Option::is_none")]
+    pub text_offset: Option<Vec<i32>>,
+}
+
+impl CompletionLogprobs {
+    pub fn new() -> CompletionLogprobs {
+        CompletionLogprobs {
+            tokens: None,
+            token_logprobs: None,
+            top_logprobs: None,
+            text_offset: None,
+        }
+    }
+}
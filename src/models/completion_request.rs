@@ -24,13 +24,28 @@ pub struct CompletionRequest {
     pub temperature: Option<f64>,
     #[serde(rename = "top_p", skip_serializing_if = "Option::is_none")]
     pub top_p: Option<f64>,
+    /// Penalize tokens in proportion to how often they have appeared so far
+    #[serde(rename = "frequency_penalty", skip_serializing_if = "Option::is_none")]
+    pub frequency_penalty: Option<f64>,
+    /// Penalize tokens that have appeared at all so far
+    #[serde(rename = "presence_penalty", skip_serializing_if = "Option::is_none")]
+    pub presence_penalty: Option<f64>,
     #[serde(rename = "stream", skip_serializing_if = "Option::is_none")]
     pub stream: Option<bool>,
+    /// Options for streamed responses; only valid when `stream` is set
+    #[serde(rename = "stream_options", skip_serializing_if = "Option::is_none")]
+    pub stream_options: Option<models::StreamOptions>,
     #[serde(rename = "stop", skip_serializing_if = "Option::is_none")]
     pub stop: Option<models::StopCondition>,
     /// Return raw tokens instead of text
     #[serde(rename = "return_raw_tokens", skip_serializing_if = "Option::is_none")]
     pub return_raw_tokens: Option<bool>,
+    /// The suffix that comes after the generated text, used for fill-in-the-middle. Only supported by models trained for infilling
+    #[serde(rename = "suffix", skip_serializing_if = "Option::is_none")]
+    pub suffix: Option<String>,
+    /// Include the log probabilities of this many most likely tokens at each position
+    #[serde(rename = "logprobs", skip_serializing_if = "Option::is_none")]
+    pub logprobs: Option<u8>,
 }
 
 impl CompletionRequest {
@@ -41,9 +56,14 @@ impl CompletionRequest {
             max_tokens: None,
             temperature: None,
             top_p: None,
+            frequency_penalty: None,
+            presence_penalty: None,
             stream: None,
+            stream_options: None,
             stop: None,
             return_raw_tokens: None,
+            suffix: None,
+            logprobs: None,
         }
     }
 }
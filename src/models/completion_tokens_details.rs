@@ -0,0 +1,27 @@
+/*
+ * Cerebras Inference API
+ *
+ * The Cerebras Inference API offers developers a low-latency solution for AI model inference  powered by Cerebras Wafer-Scale Engines and CS-3 systems. The API provides access to  high-performance language models with unprecedented speed for AI inference workloads.
+ *
+ * The version of the OpenAPI document: 1.0.0
+ * Contact: support@cerebras.ai
+ * Generated by: https://openapi-generator.tech
+ */
+
+use crate::models;
+use serde::{Deserialize, Serialize};
+
+#[derive(Clone, Default, Debug, PartialEq, Serialize, Deserialize)]
+pub struct CompletionTokensDetails {
+    /// Tokens spent on reasoning before the final answer
+    #[serde(rename = "reasoning_tokens", skip_serializing_if = "Option::is_none")]
+    pub reasoning_tokens: Option<i32>,
+}
+
+impl CompletionTokensDetails {
+    pub fn new() -> CompletionTokensDetails {
+        CompletionTokensDetails {
+            reasoning_tokens: None,
+        }
+    }
+}
@@ -18,9 +18,14 @@ pub struct CreateChatCompletionResponse {
     #[serde(rename = "object", skip_serializing_if = "Option::is_none")]
     pub object: Option<String>,
     #[serde(rename = "created", skip_serializing_if = "Option::is_none")]
-    pub created: Option<i32>,
+    pub created: Option<i64>,
     #[serde(rename = "model", skip_serializing_if = "Option::is_none")]
     pub model: Option<String>,
+    #[serde(rename = "system_fingerprint", skip_serializing_if = "Option::is_none")]
+    pub system_fingerprint: Option<String>,
+    /// Seed the response was sampled with, echoed by servers that report it
+    #[serde(rename = "seed", skip_serializing_if = "Option::is_none")]
+    pub seed: Option<i64>,
     #[serde(rename = "choices", skip_serializing_if = "Option::is_none")]
     pub choices: Option<Vec<models::ChatChoice>>,
     #[serde(rename = "usage", skip_serializing_if = "Option::is_none")]
@@ -36,6 +41,8 @@ impl CreateChatCompletionResponse {
             object: None,
             created: None,
             model: None,
+            system_fingerprint: None,
+            seed: None,
             choices: None,
             usage: None,
             time_info: None,
@@ -18,9 +18,11 @@ pub struct CreateCompletionResponse {
     #[serde(rename = "object", skip_serializing_if = "Option::is_none")]
     pub object: Option<String>,
     #[serde(rename = "created", skip_serializing_if = "Option::is_none")]
-    pub created: Option<i32>,
+    pub created: Option<i64>,
     #[serde(rename = "model", skip_serializing_if = "Option::is_none")]
     pub model: Option<String>,
+    #[serde(rename = "system_fingerprint", skip_serializing_if = "Option::is_none")]
+    pub system_fingerprint: Option<String>,
     #[serde(rename = "choices", skip_serializing_if = "Option::is_none")]
     pub choices: Option<Vec<models::CompletionChoice>>,
     #[serde(rename = "usage", skip_serializing_if = "Option::is_none")]
@@ -36,6 +38,7 @@ impl CreateCompletionResponse {
             object: None,
             created: None,
             model: None,
+            system_fingerprint: None,
             choices: None,
             usage: None,
             time_info: None,
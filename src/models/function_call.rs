@@ -13,6 +13,9 @@ use serde::{Deserialize, Serialize};
 
 #[derive(Clone, Default, Debug, PartialEq, Serialize, Deserialize)]
 pub struct FunctionCall {
+    /// The ID of the tool call, referenced by the `tool_call_id` of the tool message answering it
+    #[serde(rename = "id", skip_serializing_if = "Option::is_none")]
+    pub id: Option<String>,
     /// The name of the function
     #[serde(rename = "name", skip_serializing_if = "Option::is_none")]
     pub name: Option<String>,
@@ -24,6 +27,7 @@ pub struct FunctionCall {
 impl FunctionCall {
     pub fn new() -> FunctionCall {
         FunctionCall {
+            id: None,
             name: None,
             arguments: None,
         }
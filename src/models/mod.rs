@@ -10,6 +10,8 @@ pub mod chat_completion_request;
 pub use self::chat_completion_request::ChatCompletionRequest;
 pub mod chat_message;
 pub use self::chat_message::ChatMessage;
+pub mod chat_message_audio;
+pub use self::chat_message_audio::ChatMessageAudio;
 pub mod chat_message_delta;
 pub use self::chat_message_delta::ChatMessageDelta;
 pub mod completion;
@@ -18,8 +20,12 @@ pub mod completion_choice;
 pub use self::completion_choice::CompletionChoice;
 pub mod completion_choice_delta;
 pub use self::completion_choice_delta::CompletionChoiceDelta;
+pub mod completion_tokens_details;
+pub use self::completion_tokens_details::CompletionTokensDetails;
 pub mod completion_chunk;
 pub use self::completion_chunk::CompletionChunk;
+pub mod completion_logprobs;
+pub use self::completion_logprobs::CompletionLogprobs;
 pub mod completion_request;
 pub use self::completion_request::CompletionRequest;
 pub mod create_chat_completion_200_response;
@@ -42,16 +48,22 @@ pub mod json_schema;
 pub use self::json_schema::JsonSchema;
 pub mod model;
 pub use self::model::Model;
+pub mod modality;
+pub use self::modality::Modality;
 pub mod model_identifier;
 pub use self::model_identifier::ModelIdentifier;
 pub mod model_list;
 pub use self::model_list::ModelList;
 pub mod prompt;
 pub use self::prompt::Prompt;
+pub mod prompt_truncation;
+pub use self::prompt_truncation::PromptTruncation;
 pub mod response_format;
 pub use self::response_format::ResponseFormat;
 pub mod stop_condition;
 pub use self::stop_condition::StopCondition;
+pub mod stream_options;
+pub use self::stream_options::StreamOptions;
 pub mod time_info;
 pub use self::time_info::TimeInfo;
 pub mod tool;
@@ -20,7 +20,7 @@ pub struct Model {
     pub object: Option<Object>,
     /// Unix timestamp of when the model was created
     #[serde(rename = "created", skip_serializing_if = "Option::is_none")]
-    pub created: Option<i32>,
+    pub created: Option<i64>,
     /// Organization that owns the model
     #[serde(rename = "owned_by", skip_serializing_if = "Option::is_none")]
     pub owned_by: Option<String>,
@@ -39,7 +39,7 @@ impl Model {
 ///
 #[derive(Clone, Copy, Debug, Eq, PartialEq, Ord, PartialOrd, Hash, Serialize, Deserialize)]
 pub enum Object {
-    #[serde(rename = "model")]
+    #[serde(rename = "model", other)]
     Model,
 }
 
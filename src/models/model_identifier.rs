@@ -26,18 +26,46 @@ pub enum ModelIdentifier {
     DeepseekR1DistillLlama70b,
 }
 
-impl std::fmt::Display for ModelIdentifier {
-    fn fmt(&self, f: &mut std::fmt::Formatter) -> std::fmt::Result {
+impl ModelIdentifier {
+    /// Every model identifier, in declaration order
+    pub const ALL: [ModelIdentifier; 5] = [
+        Self::Llama4Scout17b16eInstruct,
+        Self::Llama3Period18b,
+        Self::Llama3Period370b,
+        Self::Qwen332b,
+        Self::DeepseekR1DistillLlama70b,
+    ];
+
+    /// The wire name, e.g. `"llama3.1-8b"`
+    pub fn as_str(self) -> &'static str {
         match self {
-            Self::Llama4Scout17b16eInstruct => write!(f, "llama-4-scout-17b-16e-instruct"),
-            Self::Llama3Period18b => write!(f, "llama3.1-8b"),
-            Self::Llama3Period370b => write!(f, "llama-3.3-70b"),
-            Self::Qwen332b => write!(f, "qwen-3-32b"),
-            Self::DeepseekR1DistillLlama70b => write!(f, "deepseek-r1-distill-llama-70b"),
+            Self::Llama4Scout17b16eInstruct => "llama-4-scout-17b-16e-instruct",
+            Self::Llama3Period18b => "llama3.1-8b",
+            Self::Llama3Period370b => "llama-3.3-70b",
+            Self::Qwen332b => "qwen-3-32b",
+            Self::DeepseekR1DistillLlama70b => "deepseek-r1-distill-llama-70b",
         }
     }
 }
 
+impl std::fmt::Display for ModelIdentifier {
+    fn fmt(&self, f: &mut std::fmt::Formatter) -> std::fmt::Result {
+        f.write_str(self.as_str())
+    }
+}
+
+// Parse model identifiers from their wire names, e.g. "llama3.1-8b"
+impl std::str::FromStr for ModelIdentifier {
+    type Err = crate::Error;
+
+    fn from_str(name: &str) -> Result<Self, Self::Err> {
+        Self::ALL
+            .into_iter()
+            .find(|model| model.as_str() == name)
+            .ok_or_else(|| crate::Error::InvalidModel(name.to_string()))
+    }
+}
+
 impl Default for ModelIdentifier {
     fn default() -> ModelIdentifier {
         Self::Llama4Scout17b16eInstruct
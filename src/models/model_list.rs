@@ -30,7 +30,7 @@ impl ModelList {
 ///
 #[derive(Clone, Copy, Debug, Eq, PartialEq, Ord, PartialOrd, Hash, Serialize, Deserialize)]
 pub enum Object {
-    #[serde(rename = "list")]
+    #[serde(rename = "list", other)]
     List,
 }
 
@@ -16,6 +16,8 @@ use serde::{Deserialize, Serialize};
 pub enum Prompt {
     String(String),
     Array(Vec<String>),
+    Tokens(Vec<u32>),
+    TokenArrays(Vec<Vec<u32>>),
 }
 
 impl Default for Prompt {
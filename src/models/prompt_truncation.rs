@@ -0,0 +1,36 @@
+/*
+ * Cerebras Inference API
+ *
+ * The Cerebras Inference API offers developers a low-latency solution for AI model inference  powered by Cerebras Wafer-Scale Engines and CS-3 systems. The API provides access to  high-performance language models with unprecedented speed for AI inference workloads.
+ *
+ * The version of the OpenAPI document: 1.0.0
+ * Contact: support@cerebras.ai
+ * Generated by: https://openapi-generator.tech
+ */
+
+use crate::models;
+use serde::{Deserialize, Serialize};
+
+/// How the server should handle a prompt that exceeds the context window
+#[derive(Clone, Copy, Debug, Eq, PartialEq, Ord, PartialOrd, Hash, Serialize, Deserialize)]
+pub enum PromptTruncation {
+    #[serde(rename = "AUTO")]
+    Auto,
+    #[serde(rename = "OFF")]
+    Off,
+}
+
+impl std::fmt::Display for PromptTruncation {
+    fn fmt(&self, f: &mut std::fmt::Formatter) -> std::fmt::Result {
+        match self {
+            Self::Auto => write!(f, "AUTO"),
+            Self::Off => write!(f, "OFF"),
+        }
+    }
+}
+
+impl Default for PromptTruncation {
+    fn default() -> PromptTruncation {
+        Self::Off
+    }
+}
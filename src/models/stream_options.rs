@@ -0,0 +1,27 @@
+/*
+ * Cerebras Inference API
+ *
+ * The Cerebras Inference API offers developers a low-latency solution for AI model inference  powered by Cerebras Wafer-Scale Engines and CS-3 systems. The API provides access to  high-performance language models with unprecedented speed for AI inference workloads.
+ *
+ * The version of the OpenAPI document: 1.0.0
+ * Contact: support@cerebras.ai
+ * Generated by: https://openapi-generator.tech
+ */
+
+use crate::models;
+use serde::{Deserialize, Serialize};
+
+#[derive(Clone, Default, Debug, PartialEq, Serialize, Deserialize)]
+pub struct StreamOptions {
+    /// Report token usage in the streamed chunks
+    #[serde(rename = "include_usage", skip_serializing_if = "Option::is_none")]
+    pub include_usage: Option<bool>,
+}
+
+impl StreamOptions {
+    pub fn new() -> StreamOptions {
+        StreamOptions {
+            include_usage: None,
+        }
+    }
+}
@@ -27,7 +27,7 @@ pub struct TimeInfo {
     pub total_time: Option<f64>,
     /// Unix timestamp when the response was created
     #[serde(rename = "created", skip_serializing_if = "Option::is_none")]
-    pub created: Option<i32>,
+    pub created: Option<i64>,
 }
 
 impl TimeInfo {
@@ -19,6 +19,11 @@ pub struct Usage {
     pub completion_tokens: Option<i32>,
     #[serde(rename = "total_tokens", skip_serializing_if = "Option::is_none")]
     pub total_tokens: Option<i32>,
+    #[serde(
+        rename = "completion_tokens_details",
+        skip_serializing_if = "Option::is_none"
+    )]
+    pub completion_tokens_details: Option<models::CompletionTokensDetails>,
 }
 
 impl Usage {
@@ -27,6 +32,7 @@ impl Usage {
             prompt_tokens: None,
             completion_tokens: None,
             total_tokens: None,
+            completion_tokens_details: None,
         }
     }
 }
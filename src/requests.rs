@@ -0,0 +1,249 @@
+//! Convenience helpers for composing requests
+
+use std::ops::Deref;
+
+use crate::models::ChatCompletionRequest;
+
+/// How [`ChatCompletionRequest::merge`] combines the two message lists
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum MessageMerge {
+    /// Keep the base messages and append the override messages after them
+    #[default]
+    Append,
+    /// Discard the base messages and use only the override messages
+    Replace,
+}
+
+impl ChatCompletionRequest {
+    /// Overlay `overrides` onto `base`, e.g. per-call input onto a template
+    ///
+    /// Every optional field set in `overrides` wins; unset fields fall back to
+    /// `base`. Collection-valued fields such as `tools` and `stop` are replaced
+    /// as a whole, not combined. The model is always taken from `overrides`.
+    /// Messages are combined according to `messages`.
+    ///
+    /// # Example
+    /// ```rust
+    /// use cerebras_rs::{ChatCompletionRequest, MessageMerge, ModelIdentifier};
+    ///
+    /// let template = ChatCompletionRequest::builder(ModelIdentifier::Llama3Period18b)
+    ///     .system_message("You are a terse assistant")
+    ///     .temperature(0.2)
+    ///     .max_tokens(100)
+    ///     .build();
+    /// let call = ChatCompletionRequest::builder(ModelIdentifier::Llama3Period18b)
+    ///     .user_message("Summarize Hamlet")
+    ///     .max_tokens(50)
+    ///     .build();
+    ///
+    /// let request = ChatCompletionRequest::merge(template, call, MessageMerge::Append);
+    /// assert_eq!(request.messages.len(), 2);
+    /// assert_eq!(request.temperature, Some(0.2));
+    /// assert_eq!(request.max_tokens, Some(50));
+    /// ```
+    pub fn merge(
+        base: ChatCompletionRequest,
+        overrides: ChatCompletionRequest,
+        messages: MessageMerge,
+    ) -> ChatCompletionRequest {
+        let messages = match messages {
+            MessageMerge::Append => {
+                let mut combined = base.messages;
+                combined.extend(overrides.messages);
+                combined
+            }
+            MessageMerge::Replace => overrides.messages,
+        };
+
+        ChatCompletionRequest {
+            model: overrides.model,
+            messages,
+            max_tokens: overrides.max_tokens.or(base.max_tokens),
+            temperature: overrides.temperature.or(base.temperature),
+            top_p: overrides.top_p.or(base.top_p),
+            top_k: overrides.top_k.or(base.top_k),
+            frequency_penalty: overrides.frequency_penalty.or(base.frequency_penalty),
+            presence_penalty: overrides.presence_penalty.or(base.presence_penalty),
+            seed: overrides.seed.or(base.seed),
+            stream: overrides.stream.or(base.stream),
+            stream_options: overrides.stream_options.or(base.stream_options),
+            stop: overrides.stop.or(base.stop),
+            response_format: overrides.response_format.or(base.response_format),
+            tools: overrides.tools.or(base.tools),
+            tool_choice: overrides.tool_choice.or(base.tool_choice),
+            modalities: overrides.modalities.or(base.modalities),
+            prompt_truncation: overrides.prompt_truncation.or(base.prompt_truncation),
+        }
+    }
+}
+
+/// A chat request that will be streamed, accepted by [`Client::chat_completion_stream`](crate::Client::chat_completion_stream)
+///
+/// Produced by [`ChatCompletionBuilder::build_streaming`](crate::builders::ChatCompletionBuilder::build_streaming)
+/// or converted from a plain [`ChatCompletionRequest`]; either way `stream`
+/// is set to `true`. There is deliberately no conversion to
+/// [`NonStreamingRequest`], so passing one to
+/// [`Client::chat_completion`](crate::Client::chat_completion) fails to compile:
+///
+/// ```rust,compile_fail
+/// # use cerebras_rs::{Client, ChatCompletionRequest, ModelIdentifier};
+/// # async fn example(client: Client) {
+/// let request = ChatCompletionRequest::builder(ModelIdentifier::Llama3Period18b)
+///     .user_message("Hi")
+///     .build_streaming();
+/// client.chat_completion(request).await;
+/// # }
+/// ```
+#[derive(Debug, Clone, PartialEq)]
+pub struct StreamingRequest(ChatCompletionRequest);
+
+/// A chat request that will be answered in one response, accepted by [`Client::chat_completion`](crate::Client::chat_completion)
+///
+/// Converted from a plain [`ChatCompletionRequest`], with `stream` and
+/// `stream_options` cleared. Converting a request with `stream` set to
+/// `true` is a bug, since it would quietly be sent without streaming: it
+/// panics in debug builds. Send such requests with
+/// [`Client::chat_completion_stream`](crate::Client::chat_completion_stream).
+#[derive(Debug, Clone, PartialEq)]
+pub struct NonStreamingRequest(ChatCompletionRequest);
+
+impl StreamingRequest {
+    /// Unwrap the underlying request
+    pub fn into_inner(self) -> ChatCompletionRequest {
+        self.0
+    }
+}
+
+impl NonStreamingRequest {
+    /// Unwrap the underlying request
+    pub fn into_inner(self) -> ChatCompletionRequest {
+        self.0
+    }
+}
+
+impl From<ChatCompletionRequest> for StreamingRequest {
+    fn from(mut request: ChatCompletionRequest) -> Self {
+        request.stream = Some(true);
+        Self(request)
+    }
+}
+
+impl From<ChatCompletionRequest> for NonStreamingRequest {
+    fn from(mut request: ChatCompletionRequest) -> Self {
+        debug_assert_ne!(
+            request.stream,
+            Some(true),
+            "streaming request passed where a non-streaming one is expected; \
+             use Client::chat_completion_stream"
+        );
+        request.stream = None;
+        request.stream_options = None;
+        Self(request)
+    }
+}
+
+impl Deref for StreamingRequest {
+    type Target = ChatCompletionRequest;
+
+    fn deref(&self) -> &ChatCompletionRequest {
+        &self.0
+    }
+}
+
+impl Deref for NonStreamingRequest {
+    type Target = ChatCompletionRequest;
+
+    fn deref(&self) -> &ChatCompletionRequest {
+        &self.0
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::models::{ChatMessage, ModelIdentifier, StopCondition, ToolChoiceOption};
+
+    fn base() -> ChatCompletionRequest {
+        ChatCompletionRequest {
+            model: ModelIdentifier::Llama3Period18b,
+            messages: vec![ChatMessage::system("sys")],
+            max_tokens: Some(100),
+            temperature: Some(0.2),
+            stop: Some(StopCondition::String("END".into())),
+            tool_choice: Some(ToolChoiceOption::auto()),
+            ..Default::default()
+        }
+    }
+
+    #[test]
+    fn test_merge_field_precedence() {
+        let overrides = ChatCompletionRequest {
+            model: ModelIdentifier::Llama3Period370b,
+            messages: vec![ChatMessage::user("hi")],
+            max_tokens: Some(10),
+            top_p: Some(0.9),
+            ..Default::default()
+        };
+
+        let merged = ChatCompletionRequest::merge(base(), overrides, MessageMerge::Append);
+        assert_eq!(merged.model, ModelIdentifier::Llama3Period370b);
+        assert_eq!(merged.max_tokens, Some(10));
+        assert_eq!(merged.temperature, Some(0.2));
+        assert_eq!(merged.top_p, Some(0.9));
+        assert_eq!(merged.stop, Some(StopCondition::String("END".into())));
+        assert_eq!(merged.tool_choice, Some(ToolChoiceOption::auto()));
+        assert!(merged.stream.is_none());
+    }
+
+    #[test]
+    fn test_merge_messages() {
+        let overrides = || {
+            ChatCompletionRequest::new(
+                ModelIdentifier::Llama3Period18b,
+                vec![ChatMessage::user("hi")],
+            )
+        };
+
+        let appended = ChatCompletionRequest::merge(base(), overrides(), MessageMerge::Append);
+        let contents: Vec<_> = appended
+            .messages
+            .iter()
+            .map(|m| m.content.as_str())
+            .collect();
+        assert_eq!(contents, vec!["sys", "hi"]);
+
+        let replaced = ChatCompletionRequest::merge(base(), overrides(), MessageMerge::Replace);
+        let contents: Vec<_> = replaced
+            .messages
+            .iter()
+            .map(|m| m.content.as_str())
+            .collect();
+        assert_eq!(contents, vec!["hi"]);
+    }
+
+    #[test]
+    fn test_streaming_newtypes() {
+        let request = ChatCompletionRequest::builder(ModelIdentifier::Llama3Period18b)
+            .user_message("Hi")
+            .stream(false)
+            .build();
+
+        let non_streaming = NonStreamingRequest::from(request.clone());
+        assert_eq!(non_streaming.stream, None);
+        assert_eq!(non_streaming.messages.len(), 1);
+
+        let streaming = StreamingRequest::from(non_streaming.into_inner());
+        assert_eq!(streaming.into_inner().stream, Some(true));
+    }
+
+    #[test]
+    #[cfg(debug_assertions)]
+    #[should_panic(expected = "use Client::chat_completion_stream")]
+    fn test_streaming_request_as_non_streaming() {
+        let request = ChatCompletionRequest::builder(ModelIdentifier::Llama3Period18b)
+            .user_message("Hi")
+            .stream(true)
+            .build();
+        let _ = NonStreamingRequest::from(request);
+    }
+}
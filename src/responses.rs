@@ -0,0 +1,1212 @@
+//! Convenience helpers for inspecting API responses
+
+use std::collections::HashMap;
+
+use serde::de::DeserializeOwned;
+
+use chrono::{DateTime, Utc};
+
+use crate::{Error, Result};
+
+use crate::models::{
+    ChatChoice, ChatCompletion, ChatCompletionChunk, ChatMessage, ChatMessageAudio, Completion,
+    CompletionChoice, CompletionChunk, CreateChatCompletionResponse, CreateCompletionResponse,
+    FunctionCall, Model, ModelList, TimeInfo, Usage, chat_choice, chat_choice_delta,
+    chat_completion, chat_completion_chunk, chat_message, completion, completion_choice,
+    completion_chunk, model, model_list,
+};
+
+/// The `object` discriminator of any API response, e.g. `"chat.completion"`
+///
+/// Each response model has its own `Object` enum; this covers all of them so
+/// logging and routing code can inspect responses uniformly, via the
+/// `object_type` method on each response type.
+///
+/// # Example
+/// ```rust
+/// use cerebras_rs::{CreateChatCompletionResponse, ObjectType};
+///
+/// let response: CreateChatCompletionResponse =
+///     serde_json::from_str(r#"{"object": "chat.completion"}"#).unwrap();
+/// assert_eq!(response.object_type(), Some(ObjectType::ChatCompletion));
+/// assert_eq!(ObjectType::ChatCompletion.as_str(), "chat.completion");
+/// ```
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, serde::Serialize, serde::Deserialize)]
+pub enum ObjectType {
+    /// `chat.completion`
+    #[serde(rename = "chat.completion")]
+    ChatCompletion,
+    /// `chat.completion.chunk`
+    #[serde(rename = "chat.completion.chunk")]
+    ChatCompletionChunk,
+    /// `text_completion`
+    #[serde(rename = "text_completion")]
+    TextCompletion,
+    /// `text_completion.chunk`
+    #[serde(rename = "text_completion.chunk")]
+    TextCompletionChunk,
+    /// `model`
+    #[serde(rename = "model")]
+    Model,
+    /// `list`
+    #[serde(rename = "list")]
+    List,
+    /// A discriminator this version of the SDK doesn't know
+    #[serde(rename = "unknown", other)]
+    Unknown,
+}
+
+impl ObjectType {
+    /// Parse a wire discriminator, mapping unrecognized values to [`ObjectType::Unknown`]
+    pub fn from_wire(object: &str) -> Self {
+        match object {
+            "chat.completion" => Self::ChatCompletion,
+            "chat.completion.chunk" => Self::ChatCompletionChunk,
+            "text_completion" => Self::TextCompletion,
+            "text_completion.chunk" => Self::TextCompletionChunk,
+            "model" => Self::Model,
+            "list" => Self::List,
+            _ => Self::Unknown,
+        }
+    }
+
+    /// The wire discriminator, e.g. `"chat.completion"`
+    pub fn as_str(self) -> &'static str {
+        match self {
+            Self::ChatCompletion => "chat.completion",
+            Self::ChatCompletionChunk => "chat.completion.chunk",
+            Self::TextCompletion => "text_completion",
+            Self::TextCompletionChunk => "text_completion.chunk",
+            Self::Model => "model",
+            Self::List => "list",
+            Self::Unknown => "unknown",
+        }
+    }
+}
+
+impl std::fmt::Display for ObjectType {
+    fn fmt(&self, f: &mut std::fmt::Formatter) -> std::fmt::Result {
+        f.write_str(self.as_str())
+    }
+}
+
+/// Implement `object_type` for models with a typed `Object` enum
+macro_rules! typed_object_type {
+    ($($ty:ty => $object:ty { $($variant:ident => $kind:ident),* $(,)? }),* $(,)?) => {$(
+        impl $object {
+            /// The wire discriminator, e.g. `"chat.completion"`
+            pub fn as_str(&self) -> &'static str {
+                self.object_type().as_str()
+            }
+
+            /// The discriminator as an [`ObjectType`]
+            pub fn object_type(&self) -> ObjectType {
+                match self {
+                    $(Self::$variant => ObjectType::$kind,)*
+                }
+            }
+        }
+
+        impl $ty {
+            /// The response's `object` discriminator; see [`ObjectType`]
+            pub fn object_type(&self) -> Option<ObjectType> {
+                self.object.as_ref().map(|object| object.object_type())
+            }
+        }
+    )*};
+}
+
+typed_object_type!(
+    ChatCompletion => chat_completion::Object { ChatPeriodCompletion => ChatCompletion },
+    ChatCompletionChunk => chat_completion_chunk::Object {
+        ChatPeriodCompletionPeriodChunk => ChatCompletionChunk,
+    },
+    Completion => completion::Object { TextCompletion => TextCompletion },
+    CompletionChunk => completion_chunk::Object {
+        TextCompletionPeriodChunk => TextCompletionChunk,
+    },
+    Model => model::Object { Model => Model },
+    ModelList => model_list::Object { List => List },
+);
+
+impl CreateChatCompletionResponse {
+    /// The response's `object` discriminator; see [`ObjectType`]
+    pub fn object_type(&self) -> Option<ObjectType> {
+        self.object.as_deref().map(ObjectType::from_wire)
+    }
+}
+
+impl CreateCompletionResponse {
+    /// The response's `object` discriminator; see [`ObjectType`]
+    pub fn object_type(&self) -> Option<ObjectType> {
+        self.object.as_deref().map(ObjectType::from_wire)
+    }
+}
+
+/// Implement `created_at` for types with a `created` Unix timestamp
+macro_rules! created_at {
+    ($($ty:ty),* $(,)?) => {$(
+        impl $ty {
+            /// The `created` Unix timestamp as a UTC date and time
+            ///
+            /// Returns `None` if the field is absent or out of range.
+            pub fn created_at(&self) -> Option<DateTime<Utc>> {
+                DateTime::from_timestamp(self.created?, 0)
+            }
+        }
+    )*};
+}
+
+created_at!(
+    ChatCompletion,
+    ChatCompletionChunk,
+    Completion,
+    CompletionChunk,
+    CreateChatCompletionResponse,
+    CreateCompletionResponse,
+    Model,
+    TimeInfo,
+);
+
+/// Implement `tool_calls` and `has_tool_calls` for chat responses, streamed or not
+macro_rules! tool_calls {
+    ($($ty:ty),* $(,)?) => {$(
+        impl $ty {
+            /// The tool calls in the first choice's message, or none
+            ///
+            /// # Example
+            /// ```rust
+            /// use cerebras_rs::CreateChatCompletionResponse;
+            ///
+            /// let response: CreateChatCompletionResponse = serde_json::from_str(
+            ///     r#"{"choices": [{"message": {"role": "assistant", "content": "",
+            ///         "tool_calls": [{"id": "call_1", "name": "get_weather", "arguments": "{}"}]}}]}"#,
+            /// ).unwrap();
+            /// assert!(response.has_tool_calls());
+            /// for call in response.tool_calls() {
+            ///     println!("{:?}({:?})", call.name, call.arguments);
+            /// }
+            /// ```
+            pub fn tool_calls(&self) -> Vec<&FunctionCall> {
+                self.choices
+                    .iter()
+                    .flatten()
+                    .next()
+                    .and_then(|choice| choice.message.as_ref())
+                    .and_then(|message| message.tool_calls.as_ref())
+                    .map(|calls| calls.iter().collect())
+                    .unwrap_or_default()
+            }
+
+            /// Whether the first choice's message requests any tool calls
+            pub fn has_tool_calls(&self) -> bool {
+                !self.tool_calls().is_empty()
+            }
+        }
+    )*};
+}
+
+tool_calls!(ChatCompletion, CreateChatCompletionResponse);
+
+/// Implement `was_truncated` for responses, streamed or not, given their choice's finish reason enum
+macro_rules! was_truncated {
+    ($($ty:ty => $reason:ident),* $(,)?) => {$(
+        impl $ty {
+            /// Whether the first choice stopped because it hit `max_tokens` or the context limit
+            ///
+            /// A truncated reply can be continued by sending it back as an
+            /// assistant message, as
+            /// [`AdaptiveOptions::continue_on_length`](crate::adaptive::AdaptiveOptions::continue_on_length)
+            /// does.
+            pub fn was_truncated(&self) -> bool {
+                self.choices
+                    .iter()
+                    .flatten()
+                    .next()
+                    .is_some_and(|choice| choice.finish_reason == Some($reason::FinishReason::Length))
+            }
+        }
+    )*};
+}
+
+was_truncated!(
+    ChatCompletion => chat_choice,
+    CreateChatCompletionResponse => chat_choice,
+    Completion => completion_choice,
+    CreateCompletionResponse => completion_choice,
+);
+
+/// Implement `trim_whitespace` for responses, streamed or not, given the field holding each choice's text
+macro_rules! trim_whitespace {
+    ($($ty:ty => |$choice:ident| $text:expr),* $(,)?) => {$(
+        impl $ty {
+            /// Trim leading and trailing whitespace from the text of every choice
+            pub(crate) fn trim_whitespace(&mut self) {
+                for $choice in self.choices.iter_mut().flatten() {
+                    if let Some(text) = $text {
+                        let trimmed = text.trim();
+                        if trimmed.len() != text.len() {
+                            *text = trimmed.to_string();
+                        }
+                    }
+                }
+            }
+        }
+    )*};
+}
+
+trim_whitespace!(
+    ChatCompletion => |choice| choice.message.as_mut().map(|message| &mut message.content),
+    CreateChatCompletionResponse => |choice| choice.message.as_mut().map(|message| &mut message.content),
+    Completion => |choice| choice.text.as_mut(),
+    CreateCompletionResponse => |choice| choice.text.as_mut(),
+);
+
+impl ChatMessageAudio {
+    /// The `expires_at` Unix timestamp as a UTC date and time
+    pub fn expiry(&self) -> Option<DateTime<Utc>> {
+        DateTime::from_timestamp(self.expires_at?, 0)
+    }
+}
+
+impl ChatCompletionChunk {
+    /// Content of the first choice's delta, if any
+    ///
+    /// # Example
+    /// ```rust
+    /// use cerebras_rs::ChatCompletionChunk;
+    ///
+    /// let chunk: ChatCompletionChunk = serde_json::from_str(
+    ///     r#"{"choices": [{"index": 0, "delta": {"content": "Hello"}}]}"#,
+    /// ).unwrap();
+    /// assert_eq!(chunk.delta_text(), Some("Hello"));
+    /// assert_eq!(ChatCompletionChunk::default().delta_text(), None);
+    /// ```
+    pub fn delta_text(&self) -> Option<&str> {
+        self.choices
+            .as_ref()?
+            .first()?
+            .delta
+            .as_ref()?
+            .content
+            .as_deref()
+    }
+
+    /// Finish reason of the first choice, present on the final chunk
+    pub fn finish_reason(&self) -> Option<&chat_choice_delta::FinishReason> {
+        self.choices.as_ref()?.first()?.finish_reason.as_ref()
+    }
+}
+
+impl ChatChoice {
+    /// Position of the choice among the `n` requested, as assigned by the server
+    ///
+    /// Choices without an index are treated as the first.
+    pub fn index(&self) -> u32 {
+        self.index.and_then(|i| u32::try_from(i).ok()).unwrap_or(0)
+    }
+
+    /// The stop sequence that ended generation
+    ///
+    /// Only reported by some deployments; `None` when the field is absent,
+    /// is not a string (e.g. a stop token id), or generation finished for
+    /// another reason.
+    ///
+    /// # Example
+    /// ```rust
+    /// use cerebras_rs::ChatChoice;
+    ///
+    /// let choice: ChatChoice =
+    ///     serde_json::from_str(r#"{"finish_reason": "stop", "matched_stop": "5"}"#).unwrap();
+    /// assert_eq!(choice.matched_stop(), Some("5"));
+    /// ```
+    pub fn matched_stop(&self) -> Option<&str> {
+        if self.finish_reason != Some(chat_choice::FinishReason::Stop) {
+            return None;
+        }
+        self.matched_stop.as_ref()?.as_str()
+    }
+}
+
+impl CompletionChoice {
+    /// Position of the choice among the `n` requested; see [`ChatChoice::index`]
+    pub fn index(&self) -> u32 {
+        self.index.and_then(|i| u32::try_from(i).ok()).unwrap_or(0)
+    }
+
+    /// The stop sequence that ended generation
+    ///
+    /// See [`ChatChoice::matched_stop`].
+    pub fn matched_stop(&self) -> Option<&str> {
+        if self.finish_reason != Some(completion_choice::FinishReason::Stop) {
+            return None;
+        }
+        self.matched_stop.as_ref()?.as_str()
+    }
+}
+
+impl Usage {
+    /// Tokens the model spent reasoning, as reported in `completion_tokens_details`
+    ///
+    /// These are included in `completion_tokens`; subtract them to get the
+    /// length of the visible answer.
+    pub fn reasoning_tokens(&self) -> Option<i32> {
+        self.completion_tokens_details.as_ref()?.reasoning_tokens
+    }
+}
+
+impl CreateChatCompletionResponse {
+    /// The seed the response was sampled with, if the server echoed it
+    ///
+    /// Compare with the seed sent in the request, and `system_fingerprint`
+    /// with earlier responses, to check that an output is reproducible.
+    ///
+    /// # Example
+    /// ```rust
+    /// use cerebras_rs::CreateChatCompletionResponse;
+    ///
+    /// let response: CreateChatCompletionResponse =
+    ///     serde_json::from_str(r#"{"seed": 42, "system_fingerprint": "fp_1"}"#).unwrap();
+    /// assert_eq!(response.seed(), Some(42));
+    /// ```
+    pub fn seed(&self) -> Option<i64> {
+        self.seed
+    }
+
+    /// The first choice, or an error if the response has none
+    ///
+    /// The API can return an empty `choices` array, e.g. when the output was
+    /// filtered. This turns that case into [`Error::Api`] so it can be
+    /// propagated with `?`.
+    ///
+    /// # Example
+    /// ```rust
+    /// use cerebras_rs::CreateChatCompletionResponse;
+    ///
+    /// let response: CreateChatCompletionResponse =
+    ///     serde_json::from_str(r#"{"choices": []}"#).unwrap();
+    /// assert!(response.require_choice().is_err());
+    /// ```
+    pub fn require_choice(&self) -> Result<&ChatChoice> {
+        self.choices
+            .as_ref()
+            .and_then(|choices| choices.first())
+            .ok_or_else(|| Error::Api("Response contained no choices".into()))
+    }
+
+    /// The choice the server assigned `index`
+    ///
+    /// # Example
+    /// ```rust
+    /// use cerebras_rs::CreateChatCompletionResponse;
+    ///
+    /// let response: CreateChatCompletionResponse = serde_json::from_str(
+    ///     r#"{"choices": [{"index": 1, "message": {"role": "assistant", "content": "b"}},
+    ///                     {"index": 0, "message": {"role": "assistant", "content": "a"}}]}"#,
+    /// ).unwrap();
+    /// assert_eq!(response.choice(1).unwrap().message.as_ref().unwrap().content, "b");
+    /// assert!(response.choice(2).is_none());
+    /// ```
+    pub fn choice(&self, index: u32) -> Option<&ChatChoice> {
+        self.choices
+            .as_ref()?
+            .iter()
+            .find(|choice| choice.index() == index)
+    }
+
+    /// The choices ordered by their server-assigned index
+    ///
+    /// Servers normally list choices in order already; this doesn't rely on it.
+    pub fn choices_by_index(&self) -> Vec<&ChatChoice> {
+        let mut choices: Vec<_> = self.choices.iter().flatten().collect();
+        choices.sort_by_key(|choice| choice.index());
+        choices
+    }
+
+    /// The text of the first choice's message
+    ///
+    /// Fails with [`Error::Api`] if the response has no choices or the first
+    /// choice has no message.
+    ///
+    /// # Example
+    /// ```rust
+    /// use cerebras_rs::CreateChatCompletionResponse;
+    ///
+    /// let response: CreateChatCompletionResponse = serde_json::from_str(
+    ///     r#"{"choices": [{"message": {"role": "assistant", "content": "Paris"}}]}"#,
+    /// ).unwrap();
+    /// assert_eq!(response.first_text()?, "Paris");
+    /// # Ok::<(), cerebras_rs::Error>(())
+    /// ```
+    pub fn first_text(&self) -> Result<&str> {
+        self.require_choice()?
+            .message
+            .as_ref()
+            .map(|message| message.content.as_str())
+            .ok_or_else(|| Error::Api("Response contained no message".into()))
+    }
+
+    /// Extract JSON from the first choice's message content
+    ///
+    /// Handles content wrapped in markdown code fences (```` ```json ````) or
+    /// surrounded by explanatory prose by returning the first balanced JSON
+    /// object or array that parses successfully.
+    ///
+    /// # Example
+    /// ```rust
+    /// use cerebras_rs::{ChatChoice, ChatMessage, CreateChatCompletionResponse};
+    ///
+    /// let response = CreateChatCompletionResponse {
+    ///     choices: Some(vec![ChatChoice {
+    ///         message: Some(ChatMessage::assistant(
+    ///             "Sure! Here it is:\n```json\n{\"city\": \"Paris\"}\n```",
+    ///         )),
+    ///         ..Default::default()
+    ///     }]),
+    ///     ..Default::default()
+    /// };
+    ///
+    /// assert_eq!(response.extract_json().unwrap()["city"], "Paris");
+    /// ```
+    pub fn extract_json(&self) -> Option<serde_json::Value> {
+        let content = &self.choices.as_ref()?.first()?.message.as_ref()?.content;
+        extract_json(content)
+    }
+
+    /// The first choice's content with a trailing stop sequence removed
+    ///
+    /// Servers differ on whether the matched stop sequence is included in the
+    /// output; this normalizes to excluding it. If several of `stops` match,
+    /// the longest is removed. Content cut off by `max_tokens` is returned
+    /// as-is, since its ending isn't a stop sequence. Returns `None` if the
+    /// response has no message.
+    ///
+    /// # Example
+    /// ```rust
+    /// use cerebras_rs::CreateChatCompletionResponse;
+    ///
+    /// let response: CreateChatCompletionResponse = serde_json::from_str(
+    ///     r#"{"choices": [{"message": {"role": "assistant", "content": "1, 2, 3, 4, 5"}, "finish_reason": "stop"}]}"#,
+    /// ).unwrap();
+    /// assert_eq!(response.strip_stop(&["5"]), Some("1, 2, 3, 4, "));
+    /// ```
+    pub fn strip_stop<S: AsRef<str>>(&self, stops: &[S]) -> Option<&str> {
+        let choice = self.choices.as_ref()?.first()?;
+        let content = choice.message.as_ref()?.content.as_str();
+        if choice.finish_reason == Some(chat_choice::FinishReason::Length) {
+            return Some(content);
+        }
+        let longest = stops
+            .iter()
+            .map(AsRef::as_ref)
+            .filter(|stop| !stop.is_empty() && content.ends_with(stop))
+            .max_by_key(|stop| stop.len());
+        Some(match longest {
+            Some(stop) => &content[..content.len() - stop.len()],
+            None => content,
+        })
+    }
+
+    /// Deserialize the JSON in the first choice's message content
+    ///
+    /// The JSON is located as in [`extract_json`](Self::extract_json). If the
+    /// response was cut off by `max_tokens` before the JSON was closed, fails
+    /// with [`Error::InvalidRequest`] so the limit can be raised, rather than
+    /// with a parse error. Fails with [`Error::Api`] if there is no JSON, and
+    /// with [`Error::Serialization`] if the value doesn't match `T`.
+    pub fn parse_json<T: DeserializeOwned>(&self) -> Result<T> {
+        parse_choice_json(self.require_choice()?)
+    }
+
+    /// The first choice's message, ready to append to the conversation history
+    ///
+    /// The message keeps its content and any `tool_calls`, so the tool results
+    /// answering them can follow it in the next request. Its role is always
+    /// `assistant`, and an empty `tool_calls` list is dropped.
+    ///
+    /// # Example
+    /// ```rust,no_run
+    /// use cerebras_rs::{ChatCompletionRequest, Client, ModelIdentifier};
+    ///
+    /// # async fn example() -> Result<(), Box<dyn std::error::Error>> {
+    /// let client = Client::from_env()?;
+    /// let mut request = ChatCompletionRequest::builder(ModelIdentifier::Llama3Period18b)
+    ///     .user_message("Name a colour")
+    ///     .build();
+    ///
+    /// let response = client.chat_completion(request.clone()).await?;
+    /// request.messages.extend(response.to_assistant_message());
+    /// # Ok(())
+    /// # }
+    /// ```
+    pub fn to_assistant_message(&self) -> Option<ChatMessage> {
+        let mut message = self.choices.as_ref()?.first()?.message.clone()?;
+        message.role = chat_message::Role::Assistant;
+        if message.tool_calls.as_ref().is_some_and(Vec::is_empty) {
+            message.tool_calls = None;
+        }
+        Some(message)
+    }
+
+    /// The choice with the highest `score`
+    ///
+    /// Useful when several samples were requested: score each choice, e.g. by
+    /// length or a domain-specific heuristic, and keep the best one. Choices
+    /// scoring `NaN` are skipped; on a tie the choice with the lowest index
+    /// wins.
+    ///
+    /// # Example
+    /// ```rust
+    /// use cerebras_rs::{ChatChoice, ChatMessage, CreateChatCompletionResponse};
+    ///
+    /// let choice = |content: &str| ChatChoice {
+    ///     message: Some(ChatMessage::assistant(content)),
+    ///     ..Default::default()
+    /// };
+    /// let response = CreateChatCompletionResponse {
+    ///     choices: Some(vec![choice("Short"), choice("A longer answer")]),
+    ///     ..Default::default()
+    /// };
+    ///
+    /// let longest = response
+    ///     .best_by(|c| c.message.as_ref().map_or(0.0, |m| m.content.len() as f64))
+    ///     .unwrap();
+    /// assert_eq!(longest.message.as_ref().unwrap().content, "A longer answer");
+    /// ```
+    pub fn best_by<F>(&self, score: F) -> Option<&ChatChoice>
+    where
+        F: Fn(&ChatChoice) -> f64,
+    {
+        self.choices_by_index()
+            .into_iter()
+            .map(|choice| (score(choice), choice))
+            .filter(|(score, _)| !score.is_nan())
+            .fold(
+                None,
+                |best: Option<(f64, &ChatChoice)>, (score, choice)| match best {
+                    Some((best_score, _)) if best_score >= score => best,
+                    _ => Some((score, choice)),
+                },
+            )
+            .map(|(_, choice)| choice)
+    }
+
+    /// Count how many responses were served by each backend build
+    ///
+    /// Keys are the distinct `system_fingerprint` values; responses without a
+    /// fingerprint are not counted. More than one key means the requests
+    /// landed on different backend configurations.
+    ///
+    /// # Example
+    /// ```rust
+    /// use cerebras_rs::CreateChatCompletionResponse;
+    ///
+    /// let response = |fingerprint: &str| CreateChatCompletionResponse {
+    ///     system_fingerprint: Some(fingerprint.to_string()),
+    ///     ..Default::default()
+    /// };
+    /// let responses = [response("fp_a"), response("fp_a"), response("fp_b")];
+    ///
+    /// let histogram = CreateChatCompletionResponse::fingerprint_histogram(&responses);
+    /// assert_eq!(histogram["fp_a"], 2);
+    /// ```
+    pub fn fingerprint_histogram(
+        responses: &[CreateChatCompletionResponse],
+    ) -> HashMap<String, usize> {
+        let mut histogram = HashMap::new();
+        for fingerprint in responses
+            .iter()
+            .filter_map(|r| r.system_fingerprint.as_ref())
+        {
+            *histogram.entry(fingerprint.clone()).or_insert(0) += 1;
+        }
+        histogram
+    }
+}
+
+/// Deserialize the JSON in a choice's content, reporting truncation by `max_tokens`
+pub(crate) fn parse_choice_json<T: DeserializeOwned>(choice: &ChatChoice) -> Result<T> {
+    let content = choice
+        .message
+        .as_ref()
+        .map(|message| message.content.as_str())
+        .unwrap_or_default();
+    if choice.finish_reason == Some(chat_choice::FinishReason::Length) && !json_complete(content) {
+        return Err(Error::InvalidRequest(
+            "response truncated before JSON completed; raise max_tokens".into(),
+        ));
+    }
+    let value =
+        extract_json(content).ok_or_else(|| Error::Api("Response contained no JSON".into()))?;
+    serde_json::from_value(value).map_err(Error::Serialization)
+}
+
+/// Whether the first JSON object or array in `text` has been closed
+///
+/// Candidates that close but don't parse are skipped, as in [`extract_json`],
+/// but a complete value nested inside one that is still open doesn't count.
+pub(crate) fn json_complete(text: &str) -> bool {
+    let mut offset = 0;
+    while let Some(start) = text[offset..].find(['{', '[']).map(|i| offset + i) {
+        match balanced_end(&text[start..]) {
+            None => return false,
+            Some(end)
+                if serde_json::from_str::<serde_json::Value>(&text[start..start + end]).is_ok() =>
+            {
+                return true;
+            }
+            Some(_) => offset = start + 1,
+        }
+    }
+    false
+}
+
+/// Find the first JSON object or array in `text`, ignoring fences and prose
+pub(crate) fn extract_json(text: &str) -> Option<serde_json::Value> {
+    let text = strip_code_fence(text).unwrap_or(text);
+    if let Ok(value @ (serde_json::Value::Object(_) | serde_json::Value::Array(_))) =
+        serde_json::from_str::<serde_json::Value>(text.trim())
+    {
+        return Some(value);
+    }
+
+    let mut offset = 0;
+    while let Some(start) = text[offset..].find(['{', '[']).map(|i| offset + i) {
+        if let Some(end) = balanced_end(&text[start..])
+            && let Ok(value) = serde_json::from_str(&text[start..start + end])
+        {
+            return Some(value);
+        }
+        offset = start + 1;
+    }
+    None
+}
+
+/// Return the body of the first markdown code fence in `text`, if any
+fn strip_code_fence(text: &str) -> Option<&str> {
+    let start = text.find("```")?;
+    let after_ticks = &text[start + 3..];
+    // Skip the optional language tag on the opening line
+    let body_start = after_ticks.find('\n')? + 1;
+    let body = &after_ticks[body_start..];
+    let end = body.find("```")?;
+    Some(&body[..end])
+}
+
+/// Byte length of the balanced JSON value starting at the beginning of `text`
+///
+/// `text` must start with `{` or `[`. Brackets inside string literals are
+/// ignored. Returns `None` if the value is never closed.
+pub(crate) fn balanced_end(text: &str) -> Option<usize> {
+    let mut depth = 0usize;
+    let mut in_string = false;
+    let mut escaped = false;
+
+    for (i, c) in text.char_indices() {
+        if in_string {
+            match c {
+                _ if escaped => escaped = false,
+                '\\' => escaped = true,
+                '"' => in_string = false,
+                _ => {}
+            }
+            continue;
+        }
+        match c {
+            '"' => in_string = true,
+            '{' | '[' => depth += 1,
+            '}' | ']' => {
+                depth = depth.checked_sub(1)?;
+                if depth == 0 {
+                    return Some(i + 1);
+                }
+            }
+            _ => {}
+        }
+    }
+    None
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use serde_json::json;
+
+    #[test]
+    fn test_usage_reasoning_tokens() {
+        let usage: Usage = serde_json::from_value(json!({
+            "prompt_tokens": 10,
+            "completion_tokens": 50,
+            "total_tokens": 60,
+            "completion_tokens_details": { "reasoning_tokens": 42 }
+        }))
+        .unwrap();
+        assert_eq!(usage.reasoning_tokens(), Some(42));
+
+        let usage: Usage = serde_json::from_value(json!({ "completion_tokens": 5 })).unwrap();
+        assert_eq!(usage.reasoning_tokens(), None);
+    }
+
+    #[test]
+    fn test_chunk_accessors() {
+        let chunk: ChatCompletionChunk = serde_json::from_value(json!({
+            "choices": [{ "index": 0, "delta": {}, "finish_reason": "stop" }]
+        }))
+        .unwrap();
+        assert_eq!(chunk.delta_text(), None);
+        assert_eq!(
+            chunk.finish_reason(),
+            Some(&chat_choice_delta::FinishReason::Stop)
+        );
+
+        let empty: ChatCompletionChunk = serde_json::from_value(json!({ "choices": [] })).unwrap();
+        assert_eq!(empty.delta_text(), None);
+        assert_eq!(empty.finish_reason(), None);
+    }
+
+    #[test]
+    fn test_matched_stop() {
+        let choice: CompletionChoice = serde_json::from_value(json!({
+            "text": "1, 2, 3, 4, ",
+            "finish_reason": "stop",
+            "matched_stop": "5"
+        }))
+        .unwrap();
+        assert_eq!(choice.matched_stop(), Some("5"));
+
+        // Token ids, missing fields and other finish reasons yield nothing
+        let choice: ChatChoice =
+            serde_json::from_value(json!({ "finish_reason": "stop", "matched_stop": 128009 }))
+                .unwrap();
+        assert_eq!(choice.matched_stop(), None);
+        let choice: ChatChoice =
+            serde_json::from_value(json!({ "finish_reason": "length", "matched_stop": "5" }))
+                .unwrap();
+        assert_eq!(choice.matched_stop(), None);
+        assert_eq!(ChatChoice::default().matched_stop(), None);
+    }
+
+    #[test]
+    fn test_require_choice() {
+        let missing = CreateChatCompletionResponse::default();
+        assert!(matches!(missing.require_choice(), Err(Error::Api(_))));
+
+        let empty: CreateChatCompletionResponse =
+            serde_json::from_value(json!({ "choices": [] })).unwrap();
+        assert!(matches!(empty.require_choice(), Err(Error::Api(_))));
+
+        let response: CreateChatCompletionResponse = serde_json::from_value(json!({
+            "choices": [{ "index": 0, "message": { "role": "assistant", "content": "Hi" } }]
+        }))
+        .unwrap();
+        let choice = response.require_choice().unwrap();
+        assert_eq!(choice.message.as_ref().unwrap().content, "Hi");
+    }
+
+    #[test]
+    fn test_to_assistant_message() {
+        let response: CreateChatCompletionResponse = serde_json::from_value(json!({
+            "choices": [{
+                "index": 0,
+                "message": {
+                    "role": "assistant",
+                    "content": "",
+                    "tool_calls": [{ "id": "call_1", "name": "get_weather", "arguments": "{}" }]
+                },
+                "finish_reason": "tool_calls"
+            }]
+        }))
+        .unwrap();
+        let message = response.to_assistant_message().unwrap();
+        assert_eq!(message.role, chat_message::Role::Assistant);
+        assert_eq!(message.tool_calls.unwrap()[0].id.as_deref(), Some("call_1"));
+
+        let mut response = CreateChatCompletionResponse {
+            choices: Some(vec![ChatChoice {
+                message: Some(ChatMessage::assistant("Blue")),
+                ..Default::default()
+            }]),
+            ..Default::default()
+        };
+        response.choices.as_mut().unwrap()[0]
+            .message
+            .as_mut()
+            .unwrap()
+            .tool_calls = Some(Vec::new());
+        let message = response.to_assistant_message().unwrap();
+        assert_eq!(message.content, "Blue");
+        assert_eq!(message.tool_calls, None);
+
+        assert_eq!(
+            CreateChatCompletionResponse::default().to_assistant_message(),
+            None
+        );
+    }
+
+    #[test]
+    fn test_was_truncated() {
+        let chat = |reason: &str| -> CreateChatCompletionResponse {
+            serde_json::from_value(json!({
+                "choices": [{ "message": { "role": "assistant", "content": "..." }, "finish_reason": reason }]
+            }))
+            .unwrap()
+        };
+        assert!(chat("length").was_truncated());
+        assert!(!chat("stop").was_truncated());
+        assert!(!chat("tool_calls").was_truncated());
+        assert!(!CreateChatCompletionResponse::default().was_truncated());
+
+        let completion = |reason: &str| -> CreateCompletionResponse {
+            serde_json::from_value(json!({
+                "choices": [{ "text": "...", "finish_reason": reason }]
+            }))
+            .unwrap()
+        };
+        assert!(completion("length").was_truncated());
+        assert!(!completion("stop").was_truncated());
+
+        // Only the first choice counts
+        let mixed: CreateCompletionResponse = serde_json::from_value(json!({
+            "choices": [
+                { "index": 0, "text": "done", "finish_reason": "stop" },
+                { "index": 1, "text": "cut", "finish_reason": "length" }
+            ]
+        }))
+        .unwrap();
+        assert!(!mixed.was_truncated());
+    }
+
+    #[test]
+    fn test_tool_calls() {
+        let response: CreateChatCompletionResponse = serde_json::from_value(json!({
+            "choices": [{
+                "index": 0,
+                "message": {
+                    "role": "assistant",
+                    "content": "",
+                    "tool_calls": [
+                        { "id": "call_1", "name": "add", "arguments": "{}" },
+                        { "id": "call_2", "name": "ping" }
+                    ]
+                }
+            }]
+        }))
+        .unwrap();
+        assert!(response.has_tool_calls());
+        let names: Vec<_> = response
+            .tool_calls()
+            .iter()
+            .map(|call| call.name.as_deref())
+            .collect();
+        assert_eq!(names, vec![Some("add"), Some("ping")]);
+
+        let plain = CreateChatCompletionResponse {
+            choices: Some(vec![ChatChoice {
+                message: Some(ChatMessage::assistant("Blue")),
+                ..Default::default()
+            }]),
+            ..Default::default()
+        };
+        assert!(plain.tool_calls().is_empty());
+        assert!(!plain.has_tool_calls());
+        assert!(!CreateChatCompletionResponse::default().has_tool_calls());
+        assert!(!ChatCompletion::default().has_tool_calls());
+    }
+
+    #[test]
+    fn test_best_by() {
+        let response: CreateChatCompletionResponse = serde_json::from_value(json!({
+            "choices": [
+                { "index": 0, "message": { "role": "assistant", "content": "b" } },
+                { "index": 1, "message": { "role": "assistant", "content": "a" } },
+                { "index": 2, "message": { "role": "assistant", "content": "c" } },
+                { "index": 3, "message": { "role": "assistant", "content": "a" } }
+            ]
+        }))
+        .unwrap();
+        let score = |choice: &ChatChoice| match choice.message.as_ref().unwrap().content.as_str() {
+            "a" => 2.0,
+            "b" => 1.0,
+            _ => f64::NAN,
+        };
+        assert_eq!(response.best_by(score).unwrap().index, Some(1));
+        assert!(response.best_by(|_| f64::NAN).is_none());
+        assert!(
+            CreateChatCompletionResponse::default()
+                .best_by(|_| 0.0)
+                .is_none()
+        );
+    }
+
+    #[test]
+    fn test_choices_by_index() {
+        let response: CreateChatCompletionResponse = serde_json::from_value(json!({
+            "choices": [
+                { "index": 2, "message": { "role": "assistant", "content": "c" } },
+                { "index": 0, "message": { "role": "assistant", "content": "a" } },
+                { "index": 1, "message": { "role": "assistant", "content": "a" } }
+            ]
+        }))
+        .unwrap();
+        let indices: Vec<_> = response
+            .choices_by_index()
+            .iter()
+            .map(|c| c.index())
+            .collect();
+        assert_eq!(indices, vec![0, 1, 2]);
+        assert_eq!(response.choice(2).unwrap().index, Some(2));
+
+        // Ties go to the lowest index, not the first listed
+        let score =
+            |choice: &ChatChoice| f64::from(choice.message.as_ref().unwrap().content == "a");
+        assert_eq!(response.best_by(score).unwrap().index(), 0);
+        assert_eq!(ChatChoice::default().index(), 0);
+    }
+
+    #[test]
+    fn test_tolerates_unknown_fields_and_values() {
+        let response: CreateChatCompletionResponse = serde_json::from_value(json!({
+            "id": "chatcmpl-1",
+            "object": "chat.completion",
+            "brand_new_field": { "nested": true },
+            "choices": [{
+                "index": 0,
+                "message": {
+                    "role": "assistant",
+                    "content": null,
+                    "refusal": null,
+                    "tool_calls": [{ "id": "call_1", "name": "ping", "arguments": "{}" }]
+                },
+                "finish_reason": "something_new",
+                "extra": 1
+            }],
+            "usage": { "prompt_tokens": 3, "total_tokens": 3, "cached_tokens": 0 }
+        }))
+        .unwrap();
+        let choice = &response.choices.as_ref().unwrap()[0];
+        assert_eq!(
+            choice.finish_reason,
+            Some(chat_choice::FinishReason::Unknown)
+        );
+        assert_eq!(choice.message.as_ref().unwrap().content, "");
+
+        let chunk: ChatCompletionChunk = serde_json::from_value(json!({
+            "object": "chat.completion.chunk.v2",
+            "service_tier": "default",
+            "choices": [{
+                "index": 0,
+                "delta": { "content": "Hi", "reasoning": "" },
+                "finish_reason": "paused"
+            }]
+        }))
+        .unwrap();
+        assert_eq!(chunk.delta_text(), Some("Hi"));
+        assert_eq!(
+            chunk.finish_reason(),
+            Some(&chat_choice_delta::FinishReason::Unknown)
+        );
+    }
+
+    #[test]
+    fn test_fingerprint_histogram() {
+        let responses: Vec<CreateChatCompletionResponse> = ["fp_1", "fp_2", "fp_1", "", "fp_1"]
+            .iter()
+            .map(|fp| CreateChatCompletionResponse {
+                system_fingerprint: (!fp.is_empty()).then(|| fp.to_string()),
+                ..Default::default()
+            })
+            .collect();
+
+        let histogram = CreateChatCompletionResponse::fingerprint_histogram(&responses);
+        assert_eq!(histogram.len(), 2);
+        assert_eq!(histogram["fp_1"], 3);
+        assert_eq!(histogram["fp_2"], 1);
+        assert!(CreateChatCompletionResponse::fingerprint_histogram(&[]).is_empty());
+    }
+
+    #[test]
+    fn test_extract_plain_json() {
+        assert_eq!(extract_json(r#"{"a": 1}"#), Some(json!({"a": 1})));
+        assert_eq!(extract_json("  [1, 2, 3]\n"), Some(json!([1, 2, 3])));
+    }
+
+    #[test]
+    fn test_extract_fenced_json() {
+        let text = "Here you go:\n```json\n{\"name\": \"Ada\", \"tags\": [\"x\"]}\n```\nEnjoy!";
+        assert_eq!(
+            extract_json(text),
+            Some(json!({"name": "Ada", "tags": ["x"]}))
+        );
+
+        let text = "```\n[{\"id\": 1}]\n```";
+        assert_eq!(extract_json(text), Some(json!([{"id": 1}])));
+    }
+
+    #[test]
+    fn test_extract_prose_wrapped_json() {
+        let text =
+            r#"The answer is {"result": "a } inside a string", "n": {"m": 2}} as requested."#;
+        assert_eq!(
+            extract_json(text),
+            Some(json!({"result": "a } inside a string", "n": {"m": 2}}))
+        );
+    }
+
+    #[test]
+    fn test_extract_skips_invalid_candidates() {
+        let text = r#"Use [brackets] like {this} or: {"valid": true}"#;
+        assert_eq!(extract_json(text), Some(json!({"valid": true})));
+    }
+
+    #[test]
+    fn test_extract_no_json() {
+        assert_eq!(extract_json("No JSON here"), None);
+        assert_eq!(extract_json(r#"{"unterminated": "#), None);
+    }
+
+    #[test]
+    fn test_created_at() {
+        let model: Model =
+            serde_json::from_str(r#"{"id": "llama3.1-8b", "created": 4102444800}"#).unwrap();
+        assert_eq!(
+            model.created_at().unwrap().to_rfc3339(),
+            "2100-01-01T00:00:00+00:00"
+        );
+        assert_eq!(Model::default().created_at(), None);
+
+        let time_info = TimeInfo {
+            created: Some(i64::MAX),
+            ..Default::default()
+        };
+        assert_eq!(time_info.created_at(), None);
+    }
+
+    #[test]
+    fn test_parse_json_truncation() {
+        #[derive(Debug, serde::Deserialize)]
+        struct City {
+            name: String,
+        }
+
+        let response = |content: &str, finish_reason: &str| -> CreateChatCompletionResponse {
+            serde_json::from_value(json!({
+                "choices": [{
+                    "index": 0,
+                    "message": { "role": "assistant", "content": content },
+                    "finish_reason": finish_reason
+                }]
+            }))
+            .unwrap()
+        };
+
+        let city: City = response(r#"{"name": "Paris"}"#, "stop")
+            .parse_json()
+            .unwrap();
+        assert_eq!(city.name, "Paris");
+
+        // A complete nested value doesn't hide the truncated outer one
+        let truncated = response(r#"{"name": "Paris", "info": {"a": 1}, "po"#, "length");
+        assert!(matches!(
+            truncated.parse_json::<City>(),
+            Err(Error::InvalidRequest(_))
+        ));
+
+        // Only the prose after the JSON was cut off
+        let city: City = response(r#"{"name": "Paris"} The capital of"#, "length")
+            .parse_json()
+            .unwrap();
+        assert_eq!(city.name, "Paris");
+
+        assert!(matches!(
+            response("No JSON", "stop").parse_json::<City>(),
+            Err(Error::Api(_))
+        ));
+        assert!(matches!(
+            response(r#"{"city": 1}"#, "stop").parse_json::<City>(),
+            Err(Error::Serialization(_))
+        ));
+    }
+
+    #[test]
+    fn test_json_complete() {
+        assert!(json_complete(r#"Here: {"a": [1, 2]}"#));
+        assert!(!json_complete(r#"{"a": {"b": 1}"#));
+        assert!(json_complete(r#"{oops} then {"a": 1}"#));
+        assert!(!json_complete("no JSON"));
+    }
+
+    #[test]
+    fn test_object_types() {
+        fn assert_wire<T: serde::Serialize>(object: T, as_str: &str) {
+            assert_eq!(serde_json::to_value(object).unwrap(), json!(as_str));
+        }
+        assert_wire(
+            chat_completion::Object::ChatPeriodCompletion,
+            chat_completion::Object::ChatPeriodCompletion.as_str(),
+        );
+        assert_wire(
+            chat_completion_chunk::Object::ChatPeriodCompletionPeriodChunk,
+            chat_completion_chunk::Object::ChatPeriodCompletionPeriodChunk.as_str(),
+        );
+        assert_wire(
+            completion::Object::TextCompletion,
+            completion::Object::TextCompletion.as_str(),
+        );
+        assert_wire(
+            completion_chunk::Object::TextCompletionPeriodChunk,
+            completion_chunk::Object::TextCompletionPeriodChunk.as_str(),
+        );
+        assert_wire(model::Object::Model, model::Object::Model.as_str());
+        assert_wire(model_list::Object::List, model_list::Object::List.as_str());
+
+        for kind in [
+            ObjectType::ChatCompletion,
+            ObjectType::ChatCompletionChunk,
+            ObjectType::TextCompletion,
+            ObjectType::TextCompletionChunk,
+            ObjectType::Model,
+            ObjectType::List,
+        ] {
+            assert_eq!(ObjectType::from_wire(kind.as_str()), kind);
+            assert_wire(kind, kind.as_str());
+        }
+        assert_eq!(ObjectType::from_wire("batch"), ObjectType::Unknown);
+        let unknown: ObjectType = serde_json::from_value(json!("batch")).unwrap();
+        assert_eq!(unknown, ObjectType::Unknown);
+
+        let chunk: ChatCompletionChunk =
+            serde_json::from_value(json!({ "object": "chat.completion.chunk" })).unwrap();
+        assert_eq!(chunk.object_type(), Some(ObjectType::ChatCompletionChunk));
+        assert_eq!(ModelList::default().object_type(), None);
+    }
+
+    #[test]
+    fn test_strip_stop() {
+        let response = |content: &str, finish_reason: &str| -> CreateChatCompletionResponse {
+            serde_json::from_value(json!({
+                "choices": [{
+                    "index": 0,
+                    "message": { "role": "assistant", "content": content },
+                    "finish_reason": finish_reason
+                }]
+            }))
+            .unwrap()
+        };
+
+        let stops = ["END".to_string(), "\nEND".to_string()];
+        assert_eq!(
+            response("Done\nEND", "stop").strip_stop(&stops),
+            Some("Done")
+        );
+        assert_eq!(response("Done", "stop").strip_stop(&stops), Some("Done"));
+        assert_eq!(
+            response("THE END", "length").strip_stop(&stops),
+            Some("THE END")
+        );
+        assert_eq!(response("abc", "stop").strip_stop(&[""]), Some("abc"));
+        assert_eq!(
+            CreateChatCompletionResponse::default().strip_stop(&stops),
+            None
+        );
+    }
+}
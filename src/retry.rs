@@ -0,0 +1,322 @@
+//! Retry utilities for building custom retry loops around [`Client`](crate::Client) calls
+
+use std::collections::hash_map::RandomState;
+use std::hash::{BuildHasher, Hasher};
+use std::time::Duration;
+
+use crate::{Client, Error, NonStreamingRequest, Result, models::CreateChatCompletionResponse};
+
+/// Longest wait [`Client::chat_completion_waiting`] accepts before retrying
+pub const MAX_RATE_LIMIT_WAIT: Duration = Duration::from_secs(60);
+
+/// Wait used by [`Client::chat_completion_waiting`] when the API doesn't say how long to wait
+pub const DEFAULT_RATE_LIMIT_WAIT: Duration = Duration::from_secs(1);
+
+/// Exponential backoff schedule yielding the delay before each retry
+///
+/// The `n`-th delay is `base * 2^n`, capped at `max`. With a non-zero
+/// `jitter` fraction, each delay is randomly reduced by up to that fraction so
+/// concurrent clients don't retry in lockstep. The iterator is unbounded; use
+/// [`Iterator::take`] to limit the number of attempts.
+///
+/// # Example
+/// ```rust,no_run
+/// use cerebras_rs::{Client, Error, retry::Backoff};
+/// use std::time::Duration;
+///
+/// # async fn example() -> Result<(), Box<dyn std::error::Error>> {
+/// let client = Client::from_env()?;
+/// let mut backoff = Backoff::new(Duration::from_millis(250), Duration::from_secs(8), 0.2).take(5);
+///
+/// let models = loop {
+///     match client.list_models().await {
+///         Ok(models) => break models,
+///         Err(e) if matches!(
+///             e.root(),
+///             Error::RateLimit(_) | Error::ServerError(_) | Error::Connection(_)
+///         ) => match backoff.next() {
+///             Some(delay) => tokio::time::sleep(delay).await,
+///             None => return Err("out of retries".into()),
+///         },
+///         Err(e) => return Err(e.into()),
+///     }
+/// };
+/// # Ok(())
+/// # }
+/// ```
+#[derive(Debug, Clone)]
+pub struct Backoff {
+    base: Duration,
+    max: Duration,
+    jitter: f64,
+    attempt: u32,
+}
+
+impl Backoff {
+    /// Create a schedule starting at `base`, capped at `max`, with the given jitter fraction
+    ///
+    /// `jitter` is clamped to `0.0..=1.0`; `0.0`, or a NaN or infinite
+    /// value, disables jitter.
+    pub fn new(base: Duration, max: Duration, jitter: f64) -> Self {
+        Self {
+            base,
+            max,
+            jitter: if jitter.is_finite() {
+                jitter.clamp(0.0, 1.0)
+            } else {
+                0.0
+            },
+            attempt: 0,
+        }
+    }
+
+    /// Number of delays yielded so far
+    pub fn attempt(&self) -> u32 {
+        self.attempt
+    }
+
+    /// Restart the schedule from `base`
+    pub fn reset(&mut self) {
+        self.attempt = 0;
+    }
+
+    /// Delay for the given attempt before jitter is applied
+    fn nominal_delay(&self, attempt: u32) -> Duration {
+        let factor = 2u32.checked_pow(attempt).unwrap_or(u32::MAX);
+        self.base
+            .checked_mul(factor)
+            .map_or(self.max, |delay| delay.min(self.max))
+    }
+}
+
+impl Default for Backoff {
+    fn default() -> Self {
+        Self::new(Duration::from_millis(500), Duration::from_secs(30), 0.25)
+    }
+}
+
+impl Iterator for Backoff {
+    type Item = Duration;
+
+    fn next(&mut self) -> Option<Duration> {
+        let delay = self.nominal_delay(self.attempt);
+        self.attempt = self.attempt.saturating_add(1);
+
+        if self.jitter == 0.0 {
+            return Some(delay);
+        }
+        Some(delay.mul_f64(1.0 - self.jitter * random_unit()))
+    }
+}
+
+impl Client {
+    /// Send a chat completion, waiting out a rate limit and retrying once
+    ///
+    /// On [`Error::RateLimit`], sleeps for the number of seconds the error
+    /// reports, or [`DEFAULT_RATE_LIMIT_WAIT`] if it reports none, and sends
+    /// the request again. The wait is capped at [`MAX_RATE_LIMIT_WAIT`] so a
+    /// long reset window can't stall the caller indefinitely; if the retry is
+    /// also rate limited, that error is returned. Other errors are returned
+    /// immediately. For more attempts or other retryable errors, build a loop
+    /// around [`Backoff`].
+    ///
+    /// # Example
+    /// ```rust,no_run
+    /// use cerebras_rs::{ChatCompletionRequest, Client, ModelIdentifier};
+    ///
+    /// # async fn example() -> Result<(), Box<dyn std::error::Error>> {
+    /// let client = Client::from_env()?;
+    /// let request = ChatCompletionRequest::builder(ModelIdentifier::Llama3Period18b)
+    ///     .user_message("Hello!")
+    ///     .build();
+    ///
+    /// let response = client.chat_completion_waiting(request).await?;
+    /// # Ok(())
+    /// # }
+    /// ```
+    pub async fn chat_completion_waiting(
+        &self,
+        request: impl Into<NonStreamingRequest>,
+    ) -> Result<CreateChatCompletionResponse> {
+        let request = request.into();
+        let result = self.chat_completion(request.clone()).await;
+        if let Err(e) = &result
+            && let Error::RateLimit(seconds) = *e.root()
+        {
+            tokio::time::sleep(rate_limit_wait(seconds)).await;
+            return self.chat_completion(request).await;
+        }
+        result
+    }
+}
+
+/// How long to wait after a rate limit reporting `seconds` until reset
+fn rate_limit_wait(seconds: u64) -> Duration {
+    match seconds {
+        0 => DEFAULT_RATE_LIMIT_WAIT,
+        seconds => Duration::from_secs(seconds).min(MAX_RATE_LIMIT_WAIT),
+    }
+}
+
+/// Random number in `0.0..1.0`, sourced from the standard library's hasher seeds
+fn random_unit() -> f64 {
+    let bits = RandomState::new().build_hasher().finish();
+    (bits >> 11) as f64 / (1u64 << 53) as f64
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_backoff_without_jitter() {
+        let delays: Vec<_> =
+            Backoff::new(Duration::from_millis(100), Duration::from_millis(500), 0.0)
+                .take(5)
+                .collect();
+        assert_eq!(
+            delays,
+            vec![
+                Duration::from_millis(100),
+                Duration::from_millis(200),
+                Duration::from_millis(400),
+                Duration::from_millis(500),
+                Duration::from_millis(500),
+            ]
+        );
+    }
+
+    #[test]
+    fn test_backoff_jitter_bounds() {
+        let backoff = Backoff::new(Duration::from_secs(1), Duration::from_secs(60), 0.5);
+        for (attempt, delay) in backoff.take(6).enumerate() {
+            let nominal = Duration::from_secs(1 << attempt);
+            assert!(delay <= nominal);
+            assert!(delay >= nominal / 2);
+        }
+
+        // Non-finite jitter disables jitter rather than panicking
+        for jitter in [f64::NAN, f64::INFINITY, f64::NEG_INFINITY] {
+            let mut backoff = Backoff::new(Duration::from_secs(1), Duration::from_secs(60), jitter);
+            assert_eq!(backoff.next(), Some(Duration::from_secs(1)));
+        }
+    }
+
+    #[test]
+    fn test_backoff_overflow_and_reset() {
+        let mut backoff = Backoff::new(Duration::from_secs(1), Duration::from_secs(10), 0.0);
+        assert_eq!(backoff.nominal_delay(200), Duration::from_secs(10));
+
+        backoff.next();
+        backoff.next();
+        assert_eq!(backoff.attempt(), 2);
+        backoff.reset();
+        assert_eq!(backoff.next(), Some(Duration::from_secs(1)));
+    }
+
+    #[test]
+    fn test_rate_limit_wait() {
+        assert_eq!(rate_limit_wait(0), DEFAULT_RATE_LIMIT_WAIT);
+        assert_eq!(rate_limit_wait(5), Duration::from_secs(5));
+        assert_eq!(rate_limit_wait(3600), MAX_RATE_LIMIT_WAIT);
+    }
+
+    #[tokio::test]
+    async fn test_chat_completion_waiting() {
+        use crate::models::{ChatCompletionRequest, ModelIdentifier};
+        use wiremock::matchers::{method, path};
+        use wiremock::{Mock, MockServer, ResponseTemplate};
+
+        let server = MockServer::start().await;
+        Mock::given(method("POST"))
+            .and(path("/chat/completions"))
+            .respond_with(ResponseTemplate::new(429))
+            .up_to_n_times(1)
+            .with_priority(1)
+            .mount(&server)
+            .await;
+        Mock::given(method("POST"))
+            .and(path("/chat/completions"))
+            .respond_with(ResponseTemplate::new(200).set_body_json(serde_json::json!({
+                "choices": [{
+                    "index": 0,
+                    "message": { "role": "assistant", "content": "Hello" },
+                    "finish_reason": "stop"
+                }]
+            })))
+            .up_to_n_times(1)
+            .with_priority(2)
+            .mount(&server)
+            .await;
+        Mock::given(method("POST"))
+            .and(path("/chat/completions"))
+            .respond_with(ResponseTemplate::new(429))
+            .with_priority(3)
+            .mount(&server)
+            .await;
+
+        let client = Client::new("test-key").with_base_url(server.uri());
+        let request = ChatCompletionRequest::builder(ModelIdentifier::Llama3Period18b)
+            .user_message("Hi")
+            .build();
+        let response = client
+            .chat_completion_waiting(request.clone())
+            .await
+            .unwrap();
+        let message = response.require_choice().unwrap().message.as_ref().unwrap();
+        assert_eq!(message.content, "Hello");
+
+        // Only one retry is made
+        let err = client
+            .chat_completion_waiting(request.clone())
+            .await
+            .unwrap_err();
+        assert!(matches!(err, Error::RateLimit(_)));
+        assert_eq!(server.received_requests().await.unwrap().len(), 4);
+
+        // Rate limits tagged with a request id are still waited out
+        let client = client.with_request_id_generation(true);
+        let err = client.chat_completion_waiting(request).await.unwrap_err();
+        assert!(err.request_id().is_some());
+        assert!(matches!(err.root(), Error::RateLimit(_)));
+        assert_eq!(server.received_requests().await.unwrap().len(), 6);
+    }
+
+    #[tokio::test]
+    async fn test_chat_completion_waiting_honours_retry_after() {
+        use crate::models::{ChatCompletionRequest, ModelIdentifier};
+        use wiremock::matchers::{method, path};
+        use wiremock::{Mock, MockServer, ResponseTemplate};
+
+        let server = MockServer::start().await;
+        Mock::given(method("POST"))
+            .and(path("/chat/completions"))
+            .respond_with(ResponseTemplate::new(429).insert_header("retry-after", "2"))
+            .up_to_n_times(1)
+            .with_priority(1)
+            .mount(&server)
+            .await;
+        Mock::given(method("POST"))
+            .and(path("/chat/completions"))
+            .respond_with(ResponseTemplate::new(200).set_body_json(serde_json::json!({
+                "choices": [{
+                    "index": 0,
+                    "message": { "role": "assistant", "content": "Hello" },
+                    "finish_reason": "stop"
+                }]
+            })))
+            .with_priority(2)
+            .mount(&server)
+            .await;
+
+        let client = Client::new("test-key").with_base_url(server.uri());
+        let request = ChatCompletionRequest::builder(ModelIdentifier::Llama3Period18b)
+            .user_message("Hi")
+            .build();
+        let started = std::time::Instant::now();
+        client.chat_completion_waiting(request).await.unwrap();
+        // Longer than DEFAULT_RATE_LIMIT_WAIT, so the header was used
+        assert!(started.elapsed() >= Duration::from_secs(2));
+        assert_eq!(server.received_requests().await.unwrap().len(), 2);
+    }
+}
@@ -0,0 +1,398 @@
+//! Incremental aggregation of streamed chunks into complete responses
+
+use std::collections::BTreeMap;
+
+use crate::models::*;
+
+/// Folds chat completion chunks into a single [`ChatCompletion`]
+///
+/// Deltas are grouped by their choice `index`, so a stream with `n > 1`
+/// yields one choice per index, in index order. Streamed tool calls are
+/// reassembled from their fragments.
+#[derive(Debug, Default)]
+pub(crate) struct ChatCompletionAccumulator {
+    id: Option<String>,
+    model: Option<String>,
+    created: Option<i64>,
+    choices: BTreeMap<i32, ChatChoiceState>,
+    usage: Option<Usage>,
+}
+
+/// The part of one chat choice received so far
+#[derive(Debug, Default)]
+struct ChatChoiceState {
+    role: Option<chat_message::Role>,
+    content: String,
+    tool_calls: Vec<FunctionCall>,
+    finish_reason: Option<chat_choice::FinishReason>,
+}
+
+impl ChatChoiceState {
+    /// Merge a streamed tool call fragment into the calls received so far
+    ///
+    /// A fragment carrying a new `id` starts a call; one without an `id`, or
+    /// with the `id` of an earlier call, continues that call, its `arguments`
+    /// appended to what has arrived.
+    fn push_tool_call(&mut self, fragment: FunctionCall) {
+        let existing = match &fragment.id {
+            Some(id) => self
+                .tool_calls
+                .iter_mut()
+                .find(|call| call.id.as_ref() == Some(id)),
+            None => self.tool_calls.last_mut(),
+        };
+        let Some(call) = existing else {
+            self.tool_calls.push(fragment);
+            return;
+        };
+        if call.name.is_none() {
+            call.name = fragment.name;
+        }
+        if let Some(arguments) = fragment.arguments {
+            call.arguments
+                .get_or_insert_with(String::new)
+                .push_str(&arguments);
+        }
+    }
+}
+
+impl ChatCompletionAccumulator {
+    /// Add a chunk to the aggregate
+    pub(crate) fn push(&mut self, chunk: ChatCompletionChunk) {
+        if self.id.is_none() && chunk.id.is_some() {
+            self.id = chunk.id;
+        }
+        if self.model.is_none() && chunk.model.is_some() {
+            self.model = chunk.model;
+        }
+        if self.created.is_none() && chunk.created.is_some() {
+            self.created = chunk.created;
+        }
+        // Usage may be reported as a running total, so the latest wins
+        if chunk.usage.is_some() {
+            self.usage = chunk.usage;
+        }
+
+        for choice in chunk.choices.into_iter().flatten() {
+            let state = self.choices.entry(choice.index.unwrap_or(0)).or_default();
+            if let Some(delta) = choice.delta {
+                // The role is sent once, usually on the first delta
+                if state.role.is_none()
+                    && let Some(role) = delta.role
+                {
+                    state.role = Some(match role {
+                        chat_message_delta::Role::System => chat_message::Role::System,
+                        chat_message_delta::Role::User => chat_message::Role::User,
+                        chat_message_delta::Role::Assistant => chat_message::Role::Assistant,
+                        chat_message_delta::Role::Tool => chat_message::Role::Tool,
+                    });
+                }
+                if let Some(content) = delta.content {
+                    state.content.push_str(&content);
+                }
+                for fragment in delta.tool_calls.into_iter().flatten() {
+                    state.push_tool_call(fragment);
+                }
+            }
+            if let Some(finish_reason) = choice.finish_reason {
+                state.finish_reason = Some(match finish_reason {
+                    chat_choice_delta::FinishReason::Stop => chat_choice::FinishReason::Stop,
+                    chat_choice_delta::FinishReason::Length => chat_choice::FinishReason::Length,
+                    chat_choice_delta::FinishReason::ToolCalls => {
+                        chat_choice::FinishReason::ToolCalls
+                    }
+                    chat_choice_delta::FinishReason::ContentFilter => {
+                        chat_choice::FinishReason::ContentFilter
+                    }
+                    chat_choice_delta::FinishReason::Unknown => chat_choice::FinishReason::Unknown,
+                });
+            }
+        }
+    }
+
+    /// Build the aggregated response
+    ///
+    /// A stream without any choices still yields one empty choice at index 0.
+    pub(crate) fn finish(mut self) -> ChatCompletion {
+        if self.choices.is_empty() {
+            self.choices.insert(0, ChatChoiceState::default());
+        }
+        let choices = self
+            .choices
+            .into_iter()
+            .map(|(index, state)| ChatChoice {
+                index: Some(index),
+                message: Some(ChatMessage {
+                    role: state.role.unwrap_or(chat_message::Role::Assistant),
+                    content: state.content,
+                    name: None,
+                    tool_calls: (!state.tool_calls.is_empty()).then_some(state.tool_calls),
+                    tool_call_id: None,
+                    audio: None,
+                }),
+                finish_reason: state.finish_reason,
+                matched_stop: None,
+            })
+            .collect();
+
+        ChatCompletion {
+            id: self.id,
+            object: Some(chat_completion::Object::ChatPeriodCompletion),
+            created: self.created,
+            model: self.model,
+            system_fingerprint: None,
+            choices: Some(choices),
+            usage: self.usage,
+            time_info: None,
+        }
+    }
+}
+
+/// Folds text completion chunks into a single [`Completion`]
+///
+/// Like [`ChatCompletionAccumulator`], text is grouped by choice `index`.
+#[derive(Debug, Default)]
+pub(crate) struct CompletionAccumulator {
+    id: Option<String>,
+    model: Option<String>,
+    created: Option<i64>,
+    choices: BTreeMap<i32, CompletionChoiceState>,
+    usage: Option<Usage>,
+}
+
+/// The part of one text completion choice received so far
+#[derive(Debug, Default)]
+struct CompletionChoiceState {
+    text: String,
+    finish_reason: Option<completion_choice::FinishReason>,
+}
+
+impl CompletionAccumulator {
+    /// Add a chunk to the aggregate
+    pub(crate) fn push(&mut self, chunk: CompletionChunk) {
+        if self.id.is_none() && chunk.id.is_some() {
+            self.id = chunk.id;
+        }
+        if self.model.is_none() && chunk.model.is_some() {
+            self.model = chunk.model;
+        }
+        if self.created.is_none() && chunk.created.is_some() {
+            self.created = chunk.created;
+        }
+        if chunk.usage.is_some() {
+            self.usage = chunk.usage;
+        }
+
+        for choice in chunk.choices.into_iter().flatten() {
+            let state = self.choices.entry(choice.index.unwrap_or(0)).or_default();
+            if let Some(text) = choice.text {
+                state.text.push_str(&text);
+            }
+            if let Some(finish_reason) = choice.finish_reason {
+                state.finish_reason = Some(match finish_reason {
+                    completion_choice_delta::FinishReason::Stop => {
+                        completion_choice::FinishReason::Stop
+                    }
+                    completion_choice_delta::FinishReason::Length => {
+                        completion_choice::FinishReason::Length
+                    }
+                    completion_choice_delta::FinishReason::ToolCalls => {
+                        completion_choice::FinishReason::ToolCalls
+                    }
+                    completion_choice_delta::FinishReason::ContentFilter => {
+                        completion_choice::FinishReason::ContentFilter
+                    }
+                    completion_choice_delta::FinishReason::Unknown => {
+                        completion_choice::FinishReason::Unknown
+                    }
+                });
+            }
+        }
+    }
+
+    /// Build the aggregated response
+    ///
+    /// A stream without any choices still yields one empty choice at index 0.
+    pub(crate) fn finish(mut self) -> Completion {
+        if self.choices.is_empty() {
+            self.choices.insert(0, CompletionChoiceState::default());
+        }
+        let choices = self
+            .choices
+            .into_iter()
+            .map(|(index, state)| CompletionChoice {
+                index: Some(index),
+                text: Some(state.text),
+                finish_reason: state.finish_reason,
+                logprobs: None,
+                matched_stop: None,
+            })
+            .collect();
+
+        Completion {
+            id: self.id,
+            object: Some(completion::Object::TextCompletion),
+            created: self.created,
+            model: self.model,
+            system_fingerprint: None,
+            choices: Some(choices),
+            usage: self.usage,
+            time_info: None,
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_completion_finish_reason_tolerance() {
+        let chunk: CompletionChunk = serde_json::from_value(serde_json::json!({
+            "choices": [
+                { "index": 0, "text": "a", "finish_reason": "tool_calls" },
+                { "index": 1, "text": "b", "finish_reason": "something_new" }
+            ]
+        }))
+        .unwrap();
+        let choices = chunk.choices.as_ref().unwrap();
+        assert_eq!(
+            choices[0].finish_reason,
+            Some(completion_choice_delta::FinishReason::ToolCalls)
+        );
+        assert_eq!(
+            choices[1].finish_reason,
+            Some(completion_choice_delta::FinishReason::Unknown)
+        );
+
+        let mut accumulator = CompletionAccumulator::default();
+        accumulator.push(chunk);
+        let completion = accumulator.finish();
+        let choices = completion.choices.unwrap();
+        assert_eq!(
+            choices[0].finish_reason,
+            Some(completion_choice::FinishReason::ToolCalls)
+        );
+        assert_eq!(
+            choices[1].finish_reason,
+            Some(completion_choice::FinishReason::Unknown)
+        );
+    }
+
+    #[test]
+    fn test_choices_grouped_by_index() {
+        let chunk = |index: i32, content: &str| -> ChatCompletionChunk {
+            serde_json::from_value(serde_json::json!({
+                "choices": [{ "index": index, "delta": { "content": content } }]
+            }))
+            .unwrap()
+        };
+
+        let mut accumulator = ChatCompletionAccumulator::default();
+        for (index, content) in [(1, "B"), (0, "A"), (1, "b"), (0, "a")] {
+            accumulator.push(chunk(index, content));
+        }
+        let choices = accumulator.finish().choices.unwrap();
+        let contents: Vec<_> = choices
+            .iter()
+            .map(|c| (c.index, c.message.as_ref().unwrap().content.as_str()))
+            .collect();
+        assert_eq!(contents, vec![(Some(0), "Aa"), (Some(1), "Bb")]);
+
+        let mut accumulator = CompletionAccumulator::default();
+        accumulator.push(
+            serde_json::from_value(serde_json::json!({
+                "choices": [{ "index": 2, "text": "x" }, { "index": 0, "text": "y" }]
+            }))
+            .unwrap(),
+        );
+        let choices = accumulator.finish().choices.unwrap();
+        assert_eq!(choices[0].index, Some(0));
+        assert_eq!(choices[1].text.as_deref(), Some("x"));
+
+        // Streams without choices still produce one
+        let completion = ChatCompletionAccumulator::default().finish();
+        assert_eq!(completion.choices.unwrap()[0].index, Some(0));
+    }
+
+    #[test]
+    fn test_chat_role_from_first_delta() {
+        let chunk = |delta: serde_json::Value| -> ChatCompletionChunk {
+            serde_json::from_value(serde_json::json!({
+                "choices": [{ "index": 0, "delta": delta }]
+            }))
+            .unwrap()
+        };
+
+        let mut accumulator = ChatCompletionAccumulator::default();
+        accumulator.push(chunk(serde_json::json!({ "role": "user", "content": "" })));
+        accumulator.push(chunk(
+            serde_json::json!({ "role": "assistant", "content": "Hi" }),
+        ));
+        let completion = accumulator.finish();
+        let message = completion.choices.unwrap()[0].message.clone().unwrap();
+        assert_eq!(message.role, chat_message::Role::User);
+        assert_eq!(message.content, "Hi");
+
+        // Streams that never send a role are assembled as assistant messages
+        let mut accumulator = ChatCompletionAccumulator::default();
+        accumulator.push(chunk(serde_json::json!({ "content": "Hi" })));
+        let completion = accumulator.finish();
+        assert_eq!(
+            completion.choices.unwrap()[0]
+                .message
+                .as_ref()
+                .unwrap()
+                .role,
+            chat_message::Role::Assistant
+        );
+    }
+
+    #[test]
+    fn test_post_2038_timestamps() {
+        // Past i32::MAX, which is reached in January 2038
+        let created = 2_200_000_000_i64;
+
+        let mut accumulator = ChatCompletionAccumulator::default();
+        accumulator.push(
+            serde_json::from_value(serde_json::json!({
+                "created": created,
+                "choices": [{ "index": 0, "delta": { "content": "Hi" } }]
+            }))
+            .unwrap(),
+        );
+        assert_eq!(accumulator.finish().created, Some(created));
+
+        let mut accumulator = CompletionAccumulator::default();
+        accumulator.push(
+            serde_json::from_value(serde_json::json!({
+                "created": created,
+                "choices": [{ "index": 0, "text": "Hi" }]
+            }))
+            .unwrap(),
+        );
+        assert_eq!(accumulator.finish().created, Some(created));
+    }
+
+    #[test]
+    fn test_completion_usage_chunk() {
+        let mut accumulator = CompletionAccumulator::default();
+        accumulator.push(
+            serde_json::from_value(serde_json::json!({
+                "choices": [{ "index": 0, "text": "Hi", "finish_reason": "stop" }]
+            }))
+            .unwrap(),
+        );
+        // The usage arrives on a final chunk without choices
+        accumulator.push(
+            serde_json::from_value(serde_json::json!({
+                "choices": [],
+                "usage": { "prompt_tokens": 4, "completion_tokens": 1, "total_tokens": 5 }
+            }))
+            .unwrap(),
+        );
+        let completion = accumulator.finish();
+        assert_eq!(completion.usage.unwrap().total_tokens, Some(5));
+        assert_eq!(completion.choices.unwrap()[0].text.as_deref(), Some("Hi"));
+    }
+}
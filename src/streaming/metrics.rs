@@ -0,0 +1,259 @@
+//! Latency and throughput metrics for streamed responses
+
+use std::fmt;
+use std::time::{Duration, Instant};
+
+/// Timing metrics collected while a stream is consumed
+///
+/// Each content-bearing chunk is counted as one token, which matches how the
+/// API emits deltas in practice.
+///
+/// # Example
+/// ```rust,no_run
+/// use cerebras_rs::{Client, ChatCompletionRequest, ModelIdentifier};
+/// use futures_util::StreamExt;
+///
+/// # async fn example() -> Result<(), Box<dyn std::error::Error>> {
+/// let client = Client::from_env()?;
+/// let request = ChatCompletionRequest::builder(ModelIdentifier::Llama3Period18b)
+///     .user_message("Tell me a story")
+///     .build();
+///
+/// let (mut stream, metrics) = client.chat_completion_stream(request).await?.with_metrics();
+/// while let Some(chunk) = stream.next().await {
+///     chunk?;
+/// }
+///
+/// let metrics = metrics.lock().unwrap();
+/// println!("TTFT: {:?}", metrics.time_to_first_token());
+/// println!("Throughput: {:?} tokens/s", metrics.tokens_per_second());
+/// # Ok(())
+/// # }
+/// ```
+#[derive(Debug, Clone)]
+pub struct StreamMetrics {
+    started_at: Instant,
+    first_token_at: Option<Instant>,
+    last_token_at: Option<Instant>,
+    token_count: usize,
+}
+
+impl StreamMetrics {
+    /// Create metrics measured from the given start instant
+    pub fn new(started_at: Instant) -> Self {
+        Self {
+            started_at,
+            first_token_at: None,
+            last_token_at: None,
+            token_count: 0,
+        }
+    }
+
+    /// Record that a content chunk arrived now
+    pub fn record_token(&mut self) {
+        self.record_token_at(Instant::now());
+    }
+
+    /// Record that a content chunk arrived at the given instant
+    pub fn record_token_at(&mut self, at: Instant) {
+        if self.first_token_at.is_none() {
+            self.first_token_at = Some(at);
+        }
+        self.last_token_at = Some(at);
+        self.token_count += 1;
+    }
+
+    /// Instant the request was started
+    pub fn started_at(&self) -> Instant {
+        self.started_at
+    }
+
+    /// Number of content chunks received so far
+    pub fn token_count(&self) -> usize {
+        self.token_count
+    }
+
+    /// Time between starting the request and receiving the first content chunk
+    pub fn time_to_first_token(&self) -> Option<Duration> {
+        self.first_token_at
+            .map(|first| first.duration_since(self.started_at))
+    }
+
+    /// Time between the first and the last content chunk
+    pub fn generation_time(&self) -> Option<Duration> {
+        match (self.first_token_at, self.last_token_at) {
+            (Some(first), Some(last)) => Some(last.duration_since(first)),
+            _ => None,
+        }
+    }
+
+    /// Generation throughput, measured between the first and last content chunk
+    ///
+    /// Returns `None` until at least two chunks have been received.
+    pub fn tokens_per_second(&self) -> Option<f64> {
+        let elapsed = self.generation_time()?.as_secs_f64();
+        if self.token_count < 2 || elapsed <= 0.0 {
+            return None;
+        }
+        Some((self.token_count - 1) as f64 / elapsed)
+    }
+}
+
+/// Arrival time of every content chunk in a stream, for latency debugging
+///
+/// Offsets are measured from the moment the request was sent. The
+/// [`Display`](fmt::Display) implementation prints one line per token with its
+/// offset and the gap since the previous token, which makes stalls easy to
+/// spot.
+///
+/// # Example
+/// ```rust,no_run
+/// use cerebras_rs::{Client, ChatCompletionRequest, ModelIdentifier};
+/// use futures_util::StreamExt;
+///
+/// # async fn example() -> Result<(), Box<dyn std::error::Error>> {
+/// let client = Client::from_env()?;
+/// let request = ChatCompletionRequest::builder(ModelIdentifier::Llama3Period18b)
+///     .user_message("Tell me a story")
+///     .build();
+///
+/// let (mut stream, timings) = client.chat_completion_stream(request).await?.with_token_timings();
+/// while let Some(chunk) = stream.next().await {
+///     chunk?;
+/// }
+///
+/// print!("{}", timings.lock().unwrap());
+/// # Ok(())
+/// # }
+/// ```
+#[derive(Debug, Clone)]
+pub struct TokenTimings {
+    started_at: Instant,
+    tokens: Vec<(String, Duration)>,
+}
+
+impl TokenTimings {
+    /// Create an empty record measured from the given start instant
+    pub fn new(started_at: Instant) -> Self {
+        Self {
+            started_at,
+            tokens: Vec::new(),
+        }
+    }
+
+    /// Record that `token` arrived at the given instant
+    pub fn record_at(&mut self, token: impl Into<String>, at: Instant) {
+        self.tokens
+            .push((token.into(), at.saturating_duration_since(self.started_at)));
+    }
+
+    /// Each token with its arrival offset from the start of the request
+    pub fn tokens(&self) -> &[(String, Duration)] {
+        &self.tokens
+    }
+
+    /// Consume the record, returning each token with its arrival offset
+    pub fn into_tokens(self) -> Vec<(String, Duration)> {
+        self.tokens
+    }
+
+    /// Time between each token and the one before it; the first is measured from the start
+    pub fn gaps(&self) -> Vec<Duration> {
+        let mut previous = Duration::ZERO;
+        self.tokens
+            .iter()
+            .map(|(_, offset)| {
+                let gap = offset.saturating_sub(previous);
+                previous = *offset;
+                gap
+            })
+            .collect()
+    }
+
+    /// The longest gap between consecutive tokens, with the token that ended it
+    pub fn longest_stall(&self) -> Option<(&str, Duration)> {
+        self.tokens
+            .iter()
+            .zip(self.gaps())
+            .skip(1)
+            .max_by_key(|(_, gap)| *gap)
+            .map(|((token, _), gap)| (token.as_str(), gap))
+    }
+}
+
+impl fmt::Display for TokenTimings {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        for ((token, offset), gap) in self.tokens.iter().zip(self.gaps()) {
+            writeln!(
+                f,
+                "{:>10.1}ms (+{:>8.1}ms) {:?}",
+                offset.as_secs_f64() * 1000.0,
+                gap.as_secs_f64() * 1000.0,
+                token
+            )?;
+        }
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_metrics_empty() {
+        let metrics = StreamMetrics::new(Instant::now());
+        assert_eq!(metrics.token_count(), 0);
+        assert!(metrics.time_to_first_token().is_none());
+        assert!(metrics.tokens_per_second().is_none());
+    }
+
+    #[test]
+    fn test_metrics_throughput() {
+        let start = Instant::now();
+        let mut metrics = StreamMetrics::new(start);
+
+        metrics.record_token_at(start + Duration::from_millis(100));
+        metrics.record_token_at(start + Duration::from_millis(150));
+        metrics.record_token_at(start + Duration::from_millis(200));
+
+        assert_eq!(metrics.token_count(), 3);
+        assert_eq!(
+            metrics.time_to_first_token(),
+            Some(Duration::from_millis(100))
+        );
+        assert_eq!(metrics.generation_time(), Some(Duration::from_millis(100)));
+        let tps = metrics.tokens_per_second().unwrap();
+        assert!((tps - 20.0).abs() < 1e-9);
+    }
+
+    #[test]
+    fn test_token_timings() {
+        let start = Instant::now();
+        let mut timings = TokenTimings::new(start);
+        timings.record_at("Hello", start + Duration::from_millis(100));
+        timings.record_at(",", start + Duration::from_millis(110));
+        timings.record_at(" world", start + Duration::from_millis(400));
+
+        assert_eq!(
+            timings.tokens()[2],
+            (" world".to_string(), Duration::from_millis(400))
+        );
+        assert_eq!(
+            timings.gaps(),
+            vec![
+                Duration::from_millis(100),
+                Duration::from_millis(10),
+                Duration::from_millis(290),
+            ]
+        );
+        assert_eq!(
+            timings.longest_stall(),
+            Some((" world", Duration::from_millis(290)))
+        );
+
+        let printed = timings.to_string();
+        assert_eq!(printed.lines().count(), 3);
+        assert!(printed.lines().nth(2).unwrap().ends_with("\" world\""));
+    }
+}
@@ -1,5 +1,10 @@
 //! Streaming support for API responses
 
+mod accumulator;
+mod metrics;
+pub mod ops;
 mod stream_handler;
 
+pub use metrics::{StreamMetrics, TokenTimings};
 pub use stream_handler::{ChatCompletionStream, CompletionStream};
+pub(crate) use stream_handler::{send_streaming_request, until_shutdown};
@@ -0,0 +1,429 @@
+//! Composable adapters for streams of content tokens
+//!
+//! These operate on the output of
+//! [`ChatCompletionStream::text_stream`](crate::streaming::ChatCompletionStream::text_stream)
+//! (or any `Stream<Item = Result<String>>`) and return a new token stream, so
+//! they can be chained:
+//!
+//! ```rust,no_run
+//! use cerebras_rs::{Client, ChatCompletionRequest, ModelIdentifier};
+//! use cerebras_rs::streaming::ops;
+//! use futures_util::StreamExt;
+//!
+//! # async fn example() -> Result<(), Box<dyn std::error::Error>> {
+//! let client = Client::from_env()?;
+//! let request = ChatCompletionRequest::builder(ModelIdentifier::Llama3Period18b)
+//!     .user_message("Tell me a story")
+//!     .build();
+//!
+//! let tokens = client.chat_completion_stream(request).await?.text_stream();
+//! let mut sentences = Box::pin(ops::buffer_until_sentence(ops::dedup_whitespace(tokens)));
+//! while let Some(sentence) = sentences.next().await {
+//!     println!("{}", sentence?);
+//! }
+//! # Ok(())
+//! # }
+//! ```
+//!
+//! Errors from the underlying stream are passed through as they arrive; any
+//! buffered text is kept and emitted later.
+
+use futures_util::{Stream, StreamExt, future, stream};
+
+use crate::Result;
+
+/// Collapse runs of whitespace into a single character, including runs split across tokens
+///
+/// The first whitespace character of each run is kept. Tokens that become
+/// empty are dropped.
+pub fn dedup_whitespace<S>(tokens: S) -> impl Stream<Item = Result<String>>
+where
+    S: Stream<Item = Result<String>>,
+{
+    let mut previous_whitespace = false;
+    tokens
+        .map(move |token| {
+            token.map(|token| {
+                let mut out = String::with_capacity(token.len());
+                for c in token.chars() {
+                    let whitespace = c.is_whitespace();
+                    if !(whitespace && previous_whitespace) {
+                        out.push(c);
+                    }
+                    previous_whitespace = whitespace;
+                }
+                out
+            })
+        })
+        .filter(|token| future::ready(!matches!(token, Ok(token) if token.is_empty())))
+}
+
+/// Buffer tokens and emit them a sentence at a time
+///
+/// A sentence ends at a newline, or at `.`, `!` or `?` followed by whitespace;
+/// the trailing whitespace is included. Whatever remains when the stream ends
+/// is emitted as a final item.
+pub fn buffer_until_sentence<S>(tokens: S) -> impl Stream<Item = Result<String>>
+where
+    S: Stream<Item = Result<String>>,
+{
+    rebuffer(tokens, take_sentences)
+}
+
+/// Emit one complete sentence at a time, trimmed, for text-to-speech and similar pipelines
+///
+/// Unlike [`buffer_until_sentence`], which emits every complete sentence in
+/// the buffer at once and keeps whitespace, this yields each sentence as its
+/// own item with surrounding whitespace removed. A sentence ends at a
+/// newline, or at a run of `.`, `!` or `?` (plus any closing quotes or
+/// brackets) once the whitespace after it has arrived, so decimals such as
+/// `3.14` are never split. A `.` after a common abbreviation (`Dr.`, `Mr.`,
+/// `e.g.`, ...) or a single capital letter (an initial, as in `J. Smith`)
+/// doesn't end a sentence. Whatever remains when the stream ends is emitted
+/// as a final item.
+///
+/// The splitter is a heuristic: a sentence that really does end with an
+/// abbreviation or a capital letter (`...I got a B. Then`) is joined to
+/// the next one, and abbreviations outside its short English list, or
+/// numbered items such as `No. 5`, may split early.
+pub fn sentences<S>(tokens: S) -> impl Stream<Item = Result<String>>
+where
+    S: Stream<Item = Result<String>>,
+{
+    rebuffer(tokens, take_sentence).filter_map(|item| {
+        future::ready(match item {
+            Ok(sentence) => {
+                let sentence = sentence.trim();
+                (!sentence.is_empty()).then(|| Ok(sentence.to_string()))
+            }
+            Err(e) => Some(Err(e)),
+        })
+    })
+}
+
+/// Buffer tokens and emit them in groups of `n` words
+///
+/// A word counts once the whitespace following it has arrived, so words split
+/// across tokens are never broken up. Each group includes the whitespace after
+/// its last word. Whatever remains when the stream ends is emitted as a final
+/// item. An `n` of zero is treated as one.
+pub fn chunk_by_words<S>(tokens: S, n: usize) -> impl Stream<Item = Result<String>>
+where
+    S: Stream<Item = Result<String>>,
+{
+    let n = n.max(1);
+    rebuffer(tokens, move |buffer| take_words(buffer, n))
+}
+
+/// Buffer tokens and emit markdown in blocks that render without flicker
+///
+/// Text is held back until a structurally safe point: the end of a fenced
+/// code block, a blank line ending a paragraph, a heading line, or the start
+/// of the next list item. A code fence is never split, so a renderer won't
+/// see an unterminated fence turn the rest of the output into code. Whatever
+/// remains when the stream ends is emitted as a final item.
+pub fn markdown_blocks<S>(tokens: S) -> impl Stream<Item = Result<String>>
+where
+    S: Stream<Item = Result<String>>,
+{
+    rebuffer(tokens, take_markdown_blocks)
+}
+
+/// Accumulate tokens, emitting whatever `take_ready` splits off the buffer
+fn rebuffer<S, F>(tokens: S, take_ready: F) -> impl Stream<Item = Result<String>>
+where
+    S: Stream<Item = Result<String>>,
+    F: FnMut(&mut String) -> Option<String>,
+{
+    let state = (Box::pin(tokens), String::new(), take_ready, false);
+    stream::unfold(
+        state,
+        |(mut tokens, mut buffer, mut take_ready, mut done)| async move {
+            loop {
+                if let Some(ready) = take_ready(&mut buffer) {
+                    return Some((Ok(ready), (tokens, buffer, take_ready, done)));
+                }
+                if done {
+                    return None;
+                }
+                match tokens.next().await {
+                    Some(Ok(token)) => buffer.push_str(&token),
+                    Some(Err(e)) => return Some((Err(e), (tokens, buffer, take_ready, done))),
+                    None => {
+                        done = true;
+                        if !buffer.is_empty() {
+                            let rest = std::mem::take(&mut buffer);
+                            return Some((Ok(rest), (tokens, buffer, take_ready, done)));
+                        }
+                    }
+                }
+            }
+        },
+    )
+}
+
+/// Split off every complete sentence at the start of `buffer`
+fn take_sentences(buffer: &mut String) -> Option<String> {
+    let mut end = None;
+    let mut chars = buffer.char_indices().peekable();
+    while let Some((i, c)) = chars.next() {
+        match c {
+            '\n' => end = Some(i + 1),
+            '.' | '!' | '?' => {
+                if let Some(&(j, next)) = chars.peek()
+                    && next.is_whitespace()
+                {
+                    end = Some(j + next.len_utf8());
+                    chars.next();
+                }
+            }
+            _ => {}
+        }
+    }
+    end.map(|end| buffer.drain(..end).collect())
+}
+
+/// Words that are followed by a `.` without ending a sentence
+const ABBREVIATIONS: &[&str] = &[
+    "mr", "mrs", "ms", "dr", "prof", "sr", "jr", "st", "mt", "vs", "e.g", "i.e", "inc", "ltd",
+    "co", "fig", "approx",
+];
+
+/// Split off the first complete sentence of `buffer`
+fn take_sentence(buffer: &mut String) -> Option<String> {
+    let mut chars = buffer.char_indices().peekable();
+    while let Some((i, c)) = chars.next() {
+        match c {
+            '\n' => return Some(buffer.drain(..i + 1).collect()),
+            '.' | '!' | '?' => {
+                let mut terminators = 1;
+                let mut end = i + 1;
+                while let Some(&(j, next)) = chars.peek() {
+                    if matches!(next, '.' | '!' | '?') {
+                        terminators += 1;
+                    } else if !matches!(next, '"' | '\'' | ')' | ']' | '\u{201D}' | '\u{2019}') {
+                        break;
+                    }
+                    end = j + next.len_utf8();
+                    chars.next();
+                }
+                // Wait for the next character to see whether the sentence ended
+                let &(_, next) = chars.peek()?;
+                if next.is_whitespace()
+                    && !(c == '.' && terminators == 1 && is_abbreviation(&buffer[..i]))
+                {
+                    return Some(buffer.drain(..end).collect());
+                }
+            }
+            _ => {}
+        }
+    }
+    None
+}
+
+/// Whether the word at the end of `text` is an abbreviation or an initial
+fn is_abbreviation(text: &str) -> bool {
+    let word = text
+        .rsplit(char::is_whitespace)
+        .next()
+        .unwrap_or_default()
+        .trim_start_matches(|c: char| !c.is_alphanumeric());
+    let mut letters = word.chars();
+    match (letters.next(), letters.next()) {
+        (Some(initial), None) => initial.is_uppercase(),
+        _ => ABBREVIATIONS
+            .iter()
+            .any(|abbr| abbr.eq_ignore_ascii_case(word)),
+    }
+}
+
+/// Split off every complete markdown block at the start of `buffer`
+///
+/// The buffer always starts outside a code fence, since blocks are only
+/// split off outside one, so no state needs to be kept between calls.
+fn take_markdown_blocks(buffer: &mut String) -> Option<String> {
+    let mut end = 0;
+    // Fence character and length of the code block being read, if any
+    let mut fence: Option<(char, usize)> = None;
+    let mut offset = 0;
+    for line in buffer.split_inclusive('\n') {
+        if !line.ends_with('\n') {
+            break;
+        }
+        let start = offset;
+        offset += line.len();
+        let trimmed = line.trim_start();
+
+        match fence {
+            Some((marker, len)) => {
+                let run = trimmed.chars().take_while(|&c| c == marker).count();
+                if run >= len && trimmed[run * marker.len_utf8()..].trim().is_empty() {
+                    fence = None;
+                    end = offset;
+                }
+            }
+            None => {
+                let marker = trimmed.chars().next().filter(|c| matches!(c, '`' | '~'));
+                let run = marker.map_or(0, |m| trimmed.chars().take_while(|&c| c == m).count());
+                if let Some(marker) = marker.filter(|_| run >= 3) {
+                    fence = Some((marker, run));
+                    end = start;
+                } else if trimmed.trim().is_empty() || trimmed.starts_with('#') {
+                    end = offset;
+                } else if is_list_item(trimmed) {
+                    end = start;
+                }
+            }
+        }
+    }
+    (end > 0).then(|| buffer.drain(..end).collect())
+}
+
+/// Whether a line, with its indentation removed, starts a list item
+fn is_list_item(line: &str) -> bool {
+    if line.starts_with("- ") || line.starts_with("* ") || line.starts_with("+ ") {
+        return true;
+    }
+    let digits = line.chars().take_while(char::is_ascii_digit).count();
+    digits > 0 && (line[digits..].starts_with(". ") || line[digits..].starts_with(") "))
+}
+
+/// Split off the first `n` complete words of `buffer`
+fn take_words(buffer: &mut String, n: usize) -> Option<String> {
+    let mut words = 0;
+    let mut in_word = false;
+    for (i, c) in buffer.char_indices() {
+        if !c.is_whitespace() {
+            in_word = true;
+        } else if in_word {
+            in_word = false;
+            words += 1;
+            if words == n {
+                return Some(buffer.drain(..i + c.len_utf8()).collect());
+            }
+        }
+    }
+    None
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::Error;
+
+    fn tokens(parts: &[&str]) -> impl Stream<Item = Result<String>> + use<> {
+        let parts: Vec<_> = parts.iter().map(|p| Ok(p.to_string())).collect();
+        stream::iter(parts)
+    }
+
+    async fn run(stream: impl Stream<Item = Result<String>>) -> Vec<String> {
+        stream.map(|item| item.unwrap()).collect().await
+    }
+
+    #[tokio::test]
+    async fn test_dedup_whitespace() {
+        let out = run(dedup_whitespace(tokens(&[
+            "Hello  ", " ", "  world", "\n\n", "!",
+        ])))
+        .await;
+        assert_eq!(out, vec!["Hello ", "world", "\n", "!"]);
+    }
+
+    #[tokio::test]
+    async fn test_buffer_until_sentence() {
+        let out = run(buffer_until_sentence(tokens(&[
+            "Hello",
+            " there.",
+            " How",
+            " are you?",
+            " Pi is 3.",
+            "14! Bye",
+        ])))
+        .await;
+        assert_eq!(
+            out,
+            vec!["Hello there. ", "How are you? ", "Pi is 3.14! ", "Bye"]
+        );
+
+        let out = run(buffer_until_sentence(tokens(&["line one\nline", " two\n"]))).await;
+        assert_eq!(out, vec!["line one\n", "line two\n"]);
+    }
+
+    #[tokio::test]
+    async fn test_sentences() {
+        let text = "Dr. Smith paid $3.50 for it. Really?! \"Yes.\" See e.g. the receipt from J. R. Jones\nOK... then bye";
+        // Stream a character at a time, the worst case for splitting
+        let parts: Vec<String> = text.chars().map(String::from).collect();
+        let parts: Vec<&str> = parts.iter().map(String::as_str).collect();
+        let out = run(sentences(tokens(&parts))).await;
+        assert_eq!(
+            out,
+            vec![
+                "Dr. Smith paid $3.50 for it.",
+                "Really?!",
+                "\"Yes.\"",
+                "See e.g. the receipt from J. R. Jones",
+                "OK...",
+                "then bye"
+            ]
+        );
+
+        // Sentences become available as soon as the whitespace after them arrives
+        let out = run(sentences(tokens(&["One. Two.", " ", "  \n\n", "Three."]))).await;
+        assert_eq!(out, vec!["One.", "Two.", "Three."]);
+        assert!(run(sentences(tokens(&["  ", "\n"]))).await.is_empty());
+    }
+
+    #[tokio::test]
+    async fn test_chunk_by_words() {
+        let out = run(chunk_by_words(
+            tokens(&["The qu", "ick brown ", "fox jumps", " over"]),
+            2,
+        ))
+        .await;
+        assert_eq!(out, vec!["The quick ", "brown fox ", "jumps over"]);
+
+        let out = run(chunk_by_words(tokens(&["a b"]), 0)).await;
+        assert_eq!(out, vec!["a ", "b"]);
+    }
+
+    #[tokio::test]
+    async fn test_markdown_blocks() {
+        let text = "# Title\nFirst para\ngraph.\n\nSteps:\n- one\n- two\n  more\n1. three\n\n```rust\nfn main() {\n\n}\n```\nDone";
+        // Stream a character at a time, the worst case for splitting
+        let parts: Vec<String> = text.chars().map(String::from).collect();
+        let parts: Vec<&str> = parts.iter().map(String::as_str).collect();
+        let out = run(markdown_blocks(tokens(&parts))).await;
+        assert_eq!(out.concat(), text);
+        assert_eq!(
+            out,
+            vec![
+                "# Title\n",
+                "First para\ngraph.\n\n",
+                "Steps:\n",
+                "- one\n",
+                "- two\n  more\n",
+                "1. three\n\n",
+                "```rust\nfn main() {\n\n}\n```\n",
+                "Done"
+            ]
+        );
+
+        // A longer closing fence still closes; a shorter one doesn't
+        let out = run(markdown_blocks(tokens(&["````\n```\n", "x\n`````\nafter"]))).await;
+        assert_eq!(out, vec!["````\n```\nx\n`````\n", "after"]);
+    }
+
+    #[tokio::test]
+    async fn test_errors_pass_through() {
+        let items = vec![
+            Ok("One".to_string()),
+            Err(Error::Streaming("boom".into())),
+            Ok(" two. ".to_string()),
+        ];
+        let out: Vec<_> = buffer_until_sentence(stream::iter(items)).collect().await;
+        assert!(matches!(out[0], Err(Error::Streaming(_))));
+        assert_eq!(out[1].as_deref().unwrap(), "One two. ");
+        assert_eq!(out.len(), 2);
+    }
+}
@@ -1,22 +1,37 @@
 //! Streaming support for Cerebras API responses
 
 use eventsource_stream::Eventsource;
-use futures_util::{Stream, StreamExt};
+use futures::channel::mpsc;
+use futures_util::{Stream, StreamExt, stream};
 use pin_project_lite::pin_project;
+use serde::de::DeserializeOwned;
 use std::pin::Pin;
+use std::sync::{Arc, Mutex};
 use std::task::{Context, Poll};
+use std::time::Instant;
+use tokio_util::sync::CancellationToken;
 
 use crate::{
     Error, Result,
-    apis::{configuration::Configuration, default_api},
+    apis::{
+        configuration::{Configuration, EndpointCategory},
+        default_api,
+    },
     models::*,
 };
 
+use crate::responses::{json_complete, parse_choice_json};
+
+use super::accumulator::{ChatCompletionAccumulator, CompletionAccumulator};
+use super::{StreamMetrics, TokenTimings};
+
 pin_project! {
     /// Stream handler for chat completion responses
     pub struct ChatCompletionStream {
         #[pin]
         inner: Pin<Box<dyn Stream<Item = Result<ChatCompletionChunk>> + Send>>,
+        started_at: Instant,
+        trim_output: bool,
     }
 }
 
@@ -29,122 +44,488 @@ impl ChatCompletionStream {
         // Ensure streaming is enabled
         let mut request = request;
         request.stream = Some(true);
+        let started_at = Instant::now();
 
         // Make the request
-        let response = configuration
-            .client
-            .post(&format!("{}/chat/completions", configuration.base_path))
-            .bearer_auth(
-                configuration
-                    .bearer_access_token
-                    .as_ref()
-                    .ok_or_else(|| Error::Configuration("No API key configured".into()))?,
-            )
-            .json(&request)
-            .send()
-            .await
-            .map_err(Error::Http)?;
-
-        if !response.status().is_success() {
-            let status = response.status();
-            let text = response.text().await.unwrap_or_default();
-            return Err(Error::Api(format!("HTTP {}: {}", status, text)));
-        }
-
-        // Create event stream
-        let stream = response
-            .bytes_stream()
-            .eventsource()
-            .filter_map(|result| async move {
-                match result {
-                    Ok(event) => {
-                        if event.data == "[DONE]" {
-                            None
-                        } else {
-                            match serde_json::from_str::<ChatCompletionChunk>(&event.data) {
-                                Ok(chunk) => Some(Ok(chunk)),
-                                Err(e) => Some(Err(Error::Serialization(e))),
-                            }
-                        }
-                    }
-                    Err(e) => Some(Err(Error::Streaming(format!("Event stream error: {}", e)))),
-                }
-            });
+        let response = send_streaming_request(
+            configuration,
+            EndpointCategory::Chat,
+            "chat/completions",
+            &request,
+        )
+        .await?;
+        let resume = Resume::new(
+            configuration,
+            EndpointCategory::Chat,
+            "chat/completions",
+            &request,
+        );
+        let stream = until_shutdown(
+            event_chunks::<ChatCompletionChunk>(
+                response,
+                configuration.require_stream_done,
+                resume,
+            ),
+            configuration.shutdown.clone(),
+        );
 
         Ok(Self {
             inner: Box::pin(stream),
+            started_at,
+            trim_output: configuration.trim_output,
         })
     }
 
-    /// Collect all chunks into a complete response
-    pub async fn collect(mut self) -> Result<ChatCompletion> {
-        let mut messages = Vec::new();
-        let mut model = None;
-        let mut id = None;
-        let mut created = None;
-        let mut finish_reason = None;
+    /// Attach a metrics collector that records timing as the stream is consumed
+    ///
+    /// Time-to-first-token is measured from the moment the request was sent.
+    pub fn with_metrics(self) -> (Self, Arc<Mutex<StreamMetrics>>) {
+        let metrics = Arc::new(Mutex::new(StreamMetrics::new(self.started_at)));
+        let recorder = Arc::clone(&metrics);
+        let inner = self.inner.inspect(move |chunk| {
+            let has_content = chunk.as_ref().is_ok_and(|chunk| {
+                chunk.choices.iter().flatten().any(|choice| {
+                    choice
+                        .delta
+                        .as_ref()
+                        .and_then(|delta| delta.content.as_ref())
+                        .is_some_and(|content| !content.is_empty())
+                })
+            });
+            if has_content && let Ok(mut metrics) = recorder.lock() {
+                metrics.record_token();
+            }
+        });
 
-        while let Some(chunk) = self.next().await {
-            let chunk = chunk?;
+        (
+            Self {
+                inner: Box::pin(inner),
+                started_at: self.started_at,
+                trim_output: self.trim_output,
+            },
+            metrics,
+        )
+    }
 
-            if id.is_none() && chunk.id.is_some() {
-                id = chunk.id;
-            }
-            if model.is_none() && chunk.model.is_some() {
-                model = chunk.model;
+    /// Log a throughput summary when the stream is dropped
+    ///
+    /// Emits one `info` event with the completion tokens (from the reported
+    /// usage if the request asked for it with
+    /// [`include_usage`](crate::builders::ChatCompletionBuilder::include_usage),
+    /// otherwise estimated from the content), the time to first token and
+    /// the tokens per second. The event fires whether the stream was read to
+    /// the end, e.g. by [`collect`](Self::collect), or abandoned early.
+    #[cfg(feature = "tracing")]
+    pub fn with_summary_log(self) -> Self {
+        let mut summary = crate::telemetry::StreamSummary::new(self.started_at);
+        let inner = self.inner.inspect(move |chunk| {
+            let Ok(chunk) = chunk else { return };
+            let content: String = chunk
+                .choices
+                .iter()
+                .flatten()
+                .filter_map(|choice| choice.delta.as_ref()?.content.as_deref())
+                .collect();
+            summary.record(&content, chunk.usage.as_ref());
+        });
+
+        Self {
+            inner: Box::pin(inner),
+            started_at: self.started_at,
+            trim_output: self.trim_output,
+        }
+    }
+
+    /// Record the arrival time of every content token as the stream is consumed
+    ///
+    /// Opt-in per-token counterpart to [`with_metrics`](Self::with_metrics);
+    /// offsets are measured from the moment the request was sent.
+    pub fn with_token_timings(self) -> (Self, Arc<Mutex<TokenTimings>>) {
+        let timings = Arc::new(Mutex::new(TokenTimings::new(self.started_at)));
+        let recorder = Arc::clone(&timings);
+        let inner = self.inner.inspect(move |chunk| {
+            let Ok(chunk) = chunk else { return };
+            let at = Instant::now();
+            let contents = chunk
+                .choices
+                .iter()
+                .flatten()
+                .filter_map(|choice| choice.delta.as_ref()?.content.as_deref())
+                .filter(|content| !content.is_empty());
+            if let Ok(mut timings) = recorder.lock() {
+                for content in contents {
+                    timings.record_at(content, at);
+                }
             }
-            if created.is_none() && chunk.created.is_some() {
-                created = chunk.created;
+        });
+
+        (
+            Self {
+                inner: Box::pin(inner),
+                started_at: self.started_at,
+                trim_output: self.trim_output,
+            },
+            timings,
+        )
+    }
+
+    /// Track the latest token usage reported by the stream as it is consumed
+    ///
+    /// Usage is only sent when the request asked for it with
+    /// [`include_usage`](crate::builders::ChatCompletionBuilder::include_usage).
+    /// The final chunk carries the totals for the whole response; servers that
+    /// also report usage on intermediate chunks update the shared value as
+    /// they go, so `completion_tokens` can drive a progress bar against
+    /// `max_tokens`. The value stays `None` until usage is first reported.
+    ///
+    /// # Example
+    /// ```rust,no_run
+    /// use cerebras_rs::{Client, ChatCompletionRequest, ModelIdentifier};
+    /// use futures_util::StreamExt;
+    ///
+    /// # async fn example() -> Result<(), Box<dyn std::error::Error>> {
+    /// let client = Client::from_env()?;
+    /// let request = ChatCompletionRequest::builder(ModelIdentifier::Llama3Period18b)
+    ///     .user_message("Tell me a story")
+    ///     .max_tokens(500)
+    ///     .include_usage(true)
+    ///     .build();
+    ///
+    /// let (mut stream, usage) = client.chat_completion_stream(request).await?.with_usage();
+    /// while let Some(chunk) = stream.next().await {
+    ///     chunk?;
+    ///     let generated = usage
+    ///         .lock()
+    ///         .unwrap()
+    ///         .as_ref()
+    ///         .and_then(|usage| usage.completion_tokens);
+    ///     if let Some(generated) = generated {
+    ///         println!("{}/500 tokens", generated);
+    ///     }
+    /// }
+    /// # Ok(())
+    /// # }
+    /// ```
+    pub fn with_usage(self) -> (Self, Arc<Mutex<Option<Usage>>>) {
+        let usage = Arc::new(Mutex::new(None));
+        let recorder = Arc::clone(&usage);
+        let inner = self.inner.inspect(move |chunk| {
+            if let Ok(ChatCompletionChunk {
+                usage: Some(latest),
+                ..
+            }) = chunk
+                && let Ok(mut usage) = recorder.lock()
+            {
+                *usage = Some(latest.clone());
             }
+        });
 
-            if let Some(choices) = chunk.choices {
-                for choice in choices {
-                    if let Some(delta) = choice.delta {
-                        if let Some(content) = delta.content {
-                            messages.push(content);
-                        }
-                    }
-                    if choice.finish_reason.is_some() {
-                        finish_reason = choice.finish_reason.map(|fr| match fr {
-                            crate::models::chat_choice_delta::FinishReason::Stop => {
-                                crate::models::chat_choice::FinishReason::Stop
-                            }
-                            crate::models::chat_choice_delta::FinishReason::Length => {
-                                crate::models::chat_choice::FinishReason::Length
-                            }
-                            crate::models::chat_choice_delta::FinishReason::ToolCalls => {
-                                crate::models::chat_choice::FinishReason::ToolCalls
-                            }
-                            crate::models::chat_choice_delta::FinishReason::ContentFilter => {
-                                crate::models::chat_choice::FinishReason::ContentFilter
-                            }
-                        });
-                    }
+        (
+            Self {
+                inner: Box::pin(inner),
+                started_at: self.started_at,
+                trim_output: self.trim_output,
+            },
+            usage,
+        )
+    }
+
+    /// End the stream as soon as `done` returns true for the content received so far
+    ///
+    /// `done` is called with the accumulated content after every chunk that
+    /// adds to it. The chunk that satisfies it is still yielded, then the
+    /// connection is dropped so the server stops generating. Useful when only
+    /// a prefix of the output is needed, e.g. up to the closing brace of a
+    /// JSON object.
+    ///
+    /// # Example
+    /// ```rust,no_run
+    /// use cerebras_rs::{Client, ChatCompletionRequest, ModelIdentifier};
+    ///
+    /// # async fn example() -> Result<(), Box<dyn std::error::Error>> {
+    /// let client = Client::from_env()?;
+    /// let request = ChatCompletionRequest::builder(ModelIdentifier::Llama3Period18b)
+    ///     .user_message("List three colours, one per line")
+    ///     .build();
+    ///
+    /// let first_line = client
+    ///     .chat_completion_stream(request)
+    ///     .await?
+    ///     .take_until(|content| content.contains('\n'))
+    ///     .collect()
+    ///     .await?;
+    /// # Ok(())
+    /// # }
+    /// ```
+    pub fn take_until<F>(self, done: F) -> Self
+    where
+        F: FnMut(&str) -> bool + Send + 'static,
+    {
+        let state = (Some(self.inner), String::new(), done);
+        let inner = stream::unfold(state, |(inner, mut content, mut done)| async move {
+            let mut inner = inner?;
+            let chunk = inner.next().await?;
+            if let Ok(received) = &chunk {
+                let previous_len = content.len();
+                content.extend(
+                    received
+                        .choices
+                        .iter()
+                        .flatten()
+                        .filter_map(|choice| choice.delta.as_ref()?.content.as_deref()),
+                );
+                if content.len() > previous_len && done(&content) {
+                    // Dropping the inner stream closes the connection
+                    return Some((chunk, (None, content, done)));
                 }
             }
+            Some((chunk, (Some(inner), content, done)))
+        });
+
+        Self {
+            inner: Box::pin(inner),
+            started_at: self.started_at,
+            trim_output: self.trim_output,
         }
+    }
+
+    /// Split the stream into a live chunk stream and a future resolving to the aggregate
+    ///
+    /// Every chunk yielded by the returned stream is also fed to the returned
+    /// future, so a live renderer and a final collector can share one
+    /// connection. The future completes once the stream has been read to the
+    /// end, and fails if the stream yields an error or is dropped before its
+    /// end. Since errors can't be cloned, the live stream yields the original
+    /// error while the future fails with [`Error::Streaming`] carrying its
+    /// message.
+    ///
+    /// # Example
+    /// ```rust,no_run
+    /// use cerebras_rs::{Client, ChatCompletionRequest, ModelIdentifier};
+    /// use futures_util::StreamExt;
+    ///
+    /// # async fn example() -> Result<(), Box<dyn std::error::Error>> {
+    /// let client = Client::from_env()?;
+    /// let request = ChatCompletionRequest::builder(ModelIdentifier::Llama3Period18b)
+    ///     .user_message("Tell me a story")
+    ///     .build();
+    ///
+    /// let (mut live, aggregate) = client.chat_completion_stream(request).await?.split();
+    /// while let Some(chunk) = live.next().await {
+    ///     if let Some(content) = chunk?.delta_text() {
+    ///         print!("{}", content);
+    ///     }
+    /// }
+    /// let completion = aggregate.await?;
+    /// # Ok(())
+    /// # }
+    /// ```
+    pub fn split(self) -> (Self, impl Future<Output = Result<ChatCompletion>> + Send) {
+        let (inner, rx) = tee(self.inner);
+
+        let aggregate = async move {
+            let mut accumulator = ChatCompletionAccumulator::default();
+            read_tee(rx, |chunk| accumulator.push(chunk)).await?;
+            Ok(accumulator.finish())
+        };
+
+        (
+            Self {
+                inner: Box::pin(inner),
+                started_at: self.started_at,
+                trim_output: self.trim_output,
+            },
+            aggregate,
+        )
+    }
 
-        Ok(ChatCompletion {
-            id,
-            object: Some(crate::models::chat_completion::Object::ChatPeriodCompletion),
-            created,
-            model,
-            system_fingerprint: None,
-            choices: Some(vec![ChatChoice {
-                index: Some(0),
-                message: Some(ChatMessage {
-                    role: crate::models::chat_message::Role::Assistant,
-                    content: messages.join(""),
-                    name: None,
-                    tool_calls: None,
-                    tool_call_id: None,
-                }),
-                finish_reason,
-            }]),
-            usage: None,
-            time_info: None,
+    /// Convert into a stream of content tokens
+    ///
+    /// Yields the delta content of each chunk, skipping chunks without any.
+    /// Combine with the adapters in [`ops`](crate::streaming::ops).
+    pub fn text_stream(self) -> impl Stream<Item = Result<String>> + Send {
+        self.inner.filter_map(|chunk| async move {
+            let chunk = match chunk {
+                Ok(chunk) => chunk,
+                Err(e) => return Some(Err(e)),
+            };
+            let text: String = chunk
+                .choices
+                .into_iter()
+                .flatten()
+                .filter_map(|choice| choice.delta.and_then(|delta| delta.content))
+                .collect();
+            (!text.is_empty()).then_some(Ok(text))
         })
     }
+
+    /// Convert into a stream of markdown blocks that can be rendered as they arrive
+    ///
+    /// See [`ops::markdown_blocks`](crate::streaming::ops::markdown_blocks).
+    ///
+    /// # Example
+    /// ```rust,no_run
+    /// use cerebras_rs::{Client, ChatCompletionRequest, ModelIdentifier};
+    /// use futures_util::StreamExt;
+    ///
+    /// # async fn example() -> Result<(), Box<dyn std::error::Error>> {
+    /// let client = Client::from_env()?;
+    /// let request = ChatCompletionRequest::builder(ModelIdentifier::Llama3Period18b)
+    ///     .user_message("Explain Rust iterators with an example")
+    ///     .build();
+    ///
+    /// let mut blocks = Box::pin(client.chat_completion_stream(request).await?.markdown_blocks());
+    /// while let Some(block) = blocks.next().await {
+    ///     print!("{}", block?);
+    /// }
+    /// # Ok(())
+    /// # }
+    /// ```
+    pub fn markdown_blocks(self) -> impl Stream<Item = Result<String>> + Send {
+        super::ops::markdown_blocks(self.text_stream())
+    }
+
+    /// Convert into a stream of complete sentences, e.g. to feed text-to-speech
+    ///
+    /// See [`ops::sentences`](crate::streaming::ops::sentences) for how
+    /// sentences are detected and its limitations.
+    ///
+    /// # Example
+    /// ```rust,no_run
+    /// use cerebras_rs::{Client, ChatCompletionRequest, ModelIdentifier};
+    /// use futures_util::StreamExt;
+    ///
+    /// # async fn example() -> Result<(), Box<dyn std::error::Error>> {
+    /// let client = Client::from_env()?;
+    /// let request = ChatCompletionRequest::builder(ModelIdentifier::Llama3Period18b)
+    ///     .user_message("Read me a short bedtime story")
+    ///     .build();
+    ///
+    /// let mut sentences = Box::pin(client.chat_completion_stream(request).await?.sentences());
+    /// while let Some(sentence) = sentences.next().await {
+    ///     println!("speak: {}", sentence?);
+    /// }
+    /// # Ok(())
+    /// # }
+    /// ```
+    pub fn sentences(self) -> impl Stream<Item = Result<String>> + Send {
+        super::ops::sentences(self.text_stream())
+    }
+
+    /// Convert into an [`AsyncRead`](tokio::io::AsyncRead) over the UTF-8 content bytes
+    ///
+    /// Reads return content as it arrives and reach end-of-file when the
+    /// stream finishes. A stream error fails the read with an
+    /// [`io::Error`](std::io::Error) of kind `Other` wrapping the original
+    /// [`Error`](crate::Error), which can be recovered with `into_inner`.
+    ///
+    /// # Example
+    /// ```rust,no_run
+    /// use cerebras_rs::{Client, ChatCompletionRequest, ModelIdentifier};
+    /// use tokio::io::AsyncReadExt;
+    ///
+    /// # async fn example() -> Result<(), Box<dyn std::error::Error>> {
+    /// let client = Client::from_env()?;
+    /// let request = ChatCompletionRequest::builder(ModelIdentifier::Llama3Period18b)
+    ///     .user_message("Write a haiku")
+    ///     .build();
+    ///
+    /// let mut reader = client.chat_completion_stream(request).await?.into_async_read();
+    /// let mut haiku = String::new();
+    /// reader.read_to_string(&mut haiku).await?;
+    /// # Ok(())
+    /// # }
+    /// ```
+    pub fn into_async_read(self) -> impl tokio::io::AsyncRead + Send + Unpin {
+        let bytes = self
+            .text_stream()
+            .map(|text| text.map(bytes::Bytes::from).map_err(std::io::Error::other));
+        tokio_util::io::StreamReader::new(Box::pin(bytes))
+    }
+
+    /// Collect all chunks into a complete response
+    pub async fn collect(mut self) -> Result<ChatCompletion> {
+        let mut accumulator = ChatCompletionAccumulator::default();
+        while let Some(chunk) = self.next().await {
+            accumulator.push(chunk?);
+        }
+        let mut completion = accumulator.finish();
+        if self.trim_output {
+            completion.trim_whitespace();
+        }
+        Ok(completion)
+    }
+
+    /// Read until the content holds a complete JSON value, then deserialize it
+    ///
+    /// The stream is ended with [`take_until`](Self::take_until) as soon as
+    /// the first JSON object or array has been closed, so trailing prose is
+    /// never generated. Leading prose and markdown code fences are skipped,
+    /// as in [`CreateChatCompletionResponse::extract_json`]. Errors are those
+    /// of [`CreateChatCompletionResponse::parse_json`]; in particular a stream
+    /// cut off by `max_tokens` mid-value fails with [`Error::InvalidRequest`].
+    ///
+    /// # Example
+    /// ```rust,no_run
+    /// use cerebras_rs::{Client, ChatCompletionRequest, ModelIdentifier};
+    /// use serde::Deserialize;
+    ///
+    /// #[derive(Deserialize)]
+    /// struct City {
+    ///     name: String,
+    ///     population: u64,
+    /// }
+    ///
+    /// # async fn example() -> Result<(), Box<dyn std::error::Error>> {
+    /// let client = Client::from_env()?;
+    /// let request = ChatCompletionRequest::builder(ModelIdentifier::Llama3Period18b)
+    ///     .user_message("Describe Paris as JSON with name and population fields")
+    ///     .build();
+    ///
+    /// let city: City = client.chat_completion_stream(request).await?.collect_json().await?;
+    /// # Ok(())
+    /// # }
+    /// ```
+    pub async fn collect_json<T: DeserializeOwned>(self) -> Result<T> {
+        let completion = self.take_until(json_complete).collect().await?;
+        let choice = completion
+            .choices
+            .iter()
+            .flatten()
+            .next()
+            .ok_or_else(|| Error::Api("Response contained no choices".into()))?;
+        parse_choice_json(choice)
+    }
+
+    /// Read until the first content arrives, then drop the stream so the server stops generating
+    ///
+    /// Returns the content of the first chunk that has any, usually a single
+    /// token, or `None` if the stream ends without content. Suited to cheap
+    /// routing and classification prompts whose answer is decided by the
+    /// first token; setting `max_tokens` to 1 as well keeps the server from
+    /// generating past it before the connection closes.
+    ///
+    /// # Example
+    /// ```rust,no_run
+    /// use cerebras_rs::{Client, ChatCompletionRequest, ModelIdentifier};
+    ///
+    /// # async fn example() -> Result<(), Box<dyn std::error::Error>> {
+    /// let client = Client::from_env()?;
+    /// let request = ChatCompletionRequest::builder(ModelIdentifier::Llama3Period18b)
+    ///     .system_message("Answer with one letter: A for billing, B for support, C for sales")
+    ///     .user_message("My invoice is wrong")
+    ///     .max_tokens(1)
+    ///     .build();
+    ///
+    /// let route = client.chat_completion_stream(request).await?.first_token().await?;
+    /// println!("{:?}", route.as_deref().map(str::trim));
+    /// # Ok(())
+    /// # }
+    /// ```
+    pub async fn first_token(self) -> Result<Option<String>> {
+        let mut tokens = Box::pin(self.text_stream());
+        tokens.next().await.transpose()
+    }
 }
 
 impl Stream for ChatCompletionStream {
@@ -161,6 +542,8 @@ pin_project! {
     pub struct CompletionStream {
         #[pin]
         inner: Pin<Box<dyn Stream<Item = Result<CompletionChunk>> + Send>>,
+        started_at: Instant,
+        trim_output: bool,
     }
 }
 
@@ -170,125 +553,1234 @@ impl CompletionStream {
         // Ensure streaming is enabled
         let mut request = request;
         request.stream = Some(true);
+        let started_at = Instant::now();
 
         // Make the request
-        let response = configuration
-            .client
-            .post(&format!("{}/completions", configuration.base_path))
-            .bearer_auth(
-                configuration
-                    .bearer_access_token
-                    .as_ref()
-                    .ok_or_else(|| Error::Configuration("No API key configured".into()))?,
-            )
-            .json(&request)
-            .send()
-            .await
-            .map_err(Error::Http)?;
+        let response = send_streaming_request(
+            configuration,
+            EndpointCategory::Completions,
+            "completions",
+            &request,
+        )
+        .await?;
+        let resume = Resume::new(
+            configuration,
+            EndpointCategory::Completions,
+            "completions",
+            &request,
+        );
+        let stream = until_shutdown(
+            event_chunks::<CompletionChunk>(response, configuration.require_stream_done, resume),
+            configuration.shutdown.clone(),
+        );
 
-        if !response.status().is_success() {
-            let status = response.status();
-            let text = response.text().await.unwrap_or_default();
-            return Err(Error::Api(format!("HTTP {}: {}", status, text)));
+        Ok(Self {
+            inner: Box::pin(stream),
+            started_at,
+            trim_output: configuration.trim_output,
+        })
+    }
+
+    /// Attach a metrics collector that records timing as the stream is consumed
+    ///
+    /// Time-to-first-token is measured from the moment the request was sent.
+    pub fn with_metrics(self) -> (Self, Arc<Mutex<StreamMetrics>>) {
+        let metrics = Arc::new(Mutex::new(StreamMetrics::new(self.started_at)));
+        let recorder = Arc::clone(&metrics);
+        let inner = self.inner.inspect(move |chunk| {
+            let has_content = chunk.as_ref().is_ok_and(|chunk| {
+                chunk
+                    .choices
+                    .iter()
+                    .flatten()
+                    .any(|choice| choice.text.as_ref().is_some_and(|text| !text.is_empty()))
+            });
+            if has_content && let Ok(mut metrics) = recorder.lock() {
+                metrics.record_token();
+            }
+        });
+
+        (
+            Self {
+                inner: Box::pin(inner),
+                started_at: self.started_at,
+                trim_output: self.trim_output,
+            },
+            metrics,
+        )
+    }
+
+    /// Log a throughput summary when the stream is dropped
+    ///
+    /// See [`ChatCompletionStream::with_summary_log`].
+    #[cfg(feature = "tracing")]
+    pub fn with_summary_log(self) -> Self {
+        let mut summary = crate::telemetry::StreamSummary::new(self.started_at);
+        let inner = self.inner.inspect(move |chunk| {
+            let Ok(chunk) = chunk else { return };
+            let text: String = chunk
+                .choices
+                .iter()
+                .flatten()
+                .filter_map(|choice| choice.text.as_deref())
+                .collect();
+            summary.record(&text, chunk.usage.as_ref());
+        });
+
+        Self {
+            inner: Box::pin(inner),
+            started_at: self.started_at,
+            trim_output: self.trim_output,
         }
+    }
 
-        // Create event stream
-        let stream = response
-            .bytes_stream()
-            .eventsource()
-            .filter_map(|result| async move {
-                match result {
-                    Ok(event) => {
-                        if event.data == "[DONE]" {
-                            None
-                        } else {
-                            match serde_json::from_str::<CompletionChunk>(&event.data) {
-                                Ok(chunk) => Some(Ok(chunk)),
-                                Err(e) => Some(Err(Error::Serialization(e))),
-                            }
-                        }
-                    }
-                    Err(e) => Some(Err(Error::Streaming(format!("Event stream error: {}", e)))),
+    /// Record the arrival time of every text token as the stream is consumed
+    ///
+    /// See [`ChatCompletionStream::with_token_timings`].
+    pub fn with_token_timings(self) -> (Self, Arc<Mutex<TokenTimings>>) {
+        let timings = Arc::new(Mutex::new(TokenTimings::new(self.started_at)));
+        let recorder = Arc::clone(&timings);
+        let inner = self.inner.inspect(move |chunk| {
+            let Ok(chunk) = chunk else { return };
+            let at = Instant::now();
+            let texts = chunk
+                .choices
+                .iter()
+                .flatten()
+                .filter_map(|choice| choice.text.as_deref())
+                .filter(|text| !text.is_empty());
+            if let Ok(mut timings) = recorder.lock() {
+                for text in texts {
+                    timings.record_at(text, at);
                 }
-            });
+            }
+        });
 
-        Ok(Self {
-            inner: Box::pin(stream),
+        (
+            Self {
+                inner: Box::pin(inner),
+                started_at: self.started_at,
+                trim_output: self.trim_output,
+            },
+            timings,
+        )
+    }
+
+    /// Split the stream into a live chunk stream and a future resolving to the aggregate
+    ///
+    /// See [`ChatCompletionStream::split`].
+    pub fn split(self) -> (Self, impl Future<Output = Result<Completion>> + Send) {
+        let (inner, rx) = tee(self.inner);
+
+        let aggregate = async move {
+            let mut accumulator = CompletionAccumulator::default();
+            read_tee(rx, |chunk| accumulator.push(chunk)).await?;
+            Ok(accumulator.finish())
+        };
+
+        (
+            Self {
+                inner: Box::pin(inner),
+                started_at: self.started_at,
+                trim_output: self.trim_output,
+            },
+            aggregate,
+        )
+    }
+
+    /// Convert into a stream of text tokens
+    ///
+    /// See [`ChatCompletionStream::text_stream`].
+    pub fn text_stream(self) -> impl Stream<Item = Result<String>> + Send {
+        self.inner.filter_map(|chunk| async move {
+            let chunk = match chunk {
+                Ok(chunk) => chunk,
+                Err(e) => return Some(Err(e)),
+            };
+            let text: String = chunk
+                .choices
+                .into_iter()
+                .flatten()
+                .filter_map(|choice| choice.text)
+                .collect();
+            (!text.is_empty()).then_some(Ok(text))
         })
     }
 
     /// Collect all chunks into a complete response
     pub async fn collect(mut self) -> Result<Completion> {
-        let mut texts = Vec::new();
-        let mut model = None;
-        let mut id = None;
-        let mut created = None;
-        let mut finish_reason = None;
-
+        let mut accumulator = CompletionAccumulator::default();
         while let Some(chunk) = self.next().await {
-            let chunk = chunk?;
+            accumulator.push(chunk?);
+        }
+        let mut completion = accumulator.finish();
+        if self.trim_output {
+            completion.trim_whitespace();
+        }
+        Ok(completion)
+    }
+}
 
-            if id.is_none() && chunk.id.is_some() {
-                id = chunk.id;
-            }
-            if model.is_none() && chunk.model.is_some() {
-                model = chunk.model;
-            }
-            if created.is_none() && chunk.created.is_some() {
-                created = chunk.created;
+impl Stream for CompletionStream {
+    type Item = Result<CompletionChunk>;
+
+    fn poll_next(self: Pin<&mut Self>, cx: &mut Context<'_>) -> Poll<Option<Self::Item>> {
+        let this = self.project();
+        this.inner.poll_next(cx)
+    }
+}
+
+/// An item copied to the channel by [`tee`]
+enum Teed<T> {
+    /// An item of the stream, with errors forwarded as their messages
+    Item(std::result::Result<T, String>),
+    /// The stream was read to the end
+    End,
+}
+
+/// Copy every item of `stream` to a channel, then [`Teed::End`] when the stream ends
+///
+/// If the returned stream is dropped early, the channel closes without
+/// [`Teed::End`].
+fn tee<S, T>(
+    stream: S,
+) -> (
+    impl Stream<Item = Result<T>> + Send,
+    mpsc::UnboundedReceiver<Teed<T>>,
+)
+where
+    S: Stream<Item = Result<T>> + Send,
+    T: Clone + Send,
+{
+    let (tx, rx) = mpsc::unbounded();
+    let sender = tx.clone();
+    let stream = stream
+        .inspect(move |item| {
+            let forwarded = match item {
+                Ok(item) => Ok(item.clone()),
+                Err(e) => Err(e.to_string()),
+            };
+            let _ = sender.unbounded_send(Teed::Item(forwarded));
+        })
+        .chain(stream::poll_fn(move |_| {
+            let _ = tx.unbounded_send(Teed::End);
+            tx.close_channel();
+            Poll::Ready(None)
+        }));
+    (stream, rx)
+}
+
+/// Pass the items copied by [`tee`] to `push` until the stream ends
+///
+/// Fails with [`Error::Streaming`] on the first error, or if the stream was
+/// dropped before its end, so a partial aggregate is never mistaken for a
+/// complete one.
+async fn read_tee<T>(
+    mut rx: mpsc::UnboundedReceiver<Teed<T>>,
+    mut push: impl FnMut(T),
+) -> Result<()> {
+    loop {
+        match rx.next().await {
+            Some(Teed::Item(item)) => push(item.map_err(Error::Streaming)?),
+            Some(Teed::End) => return Ok(()),
+            None => {
+                return Err(Error::Streaming(
+                    "stream dropped before completion".to_string(),
+                ));
             }
+        }
+    }
+}
 
-            if let Some(choices) = chunk.choices {
-                for choice in choices {
-                    if let Some(text) = choice.text {
-                        texts.push(text);
+/// End `stream` with [`Error::Cancelled`] as soon as the client is shut down
+///
+/// The pending read is dropped on shutdown, closing the connection.
+pub(crate) fn until_shutdown<S, T>(
+    stream: S,
+    shutdown: CancellationToken,
+) -> impl Stream<Item = Result<T>>
+where
+    S: Stream<Item = Result<T>>,
+{
+    let stream = Box::pin(stream);
+    stream::unfold(Some((stream, shutdown)), |state| async move {
+        let (mut stream, shutdown) = state?;
+        tokio::select! {
+            biased;
+            _ = shutdown.cancelled() => Some((Err(Error::Cancelled), None)),
+            item = stream.next() => item.map(|item| (item, Some((stream, shutdown)))),
+        }
+    })
+}
+
+/// POST a streaming request to `path` under the base path for `category`
+///
+/// Fails with the response body if the server returns an error status.
+pub(crate) async fn send_streaming_request<B: serde::Serialize>(
+    configuration: &Configuration,
+    category: EndpointCategory,
+    path: &str,
+    body: &B,
+) -> Result<reqwest::Response> {
+    post_stream(configuration, category, path, body, None).await
+}
+
+/// POST a streaming request, sending `Last-Event-ID` when resuming a dropped stream
+async fn post_stream<B: serde::Serialize>(
+    configuration: &Configuration,
+    category: EndpointCategory,
+    path: &str,
+    body: &B,
+    last_event_id: Option<&str>,
+) -> Result<reqwest::Response> {
+    if configuration.bearer_access_token.is_none() {
+        return Err(Error::Configuration("No API key configured".into()));
+    }
+    let mut request = configuration.apply_request_id(configuration.client.post(format!(
+        "{}/{}",
+        configuration.base_path_for(category),
+        path
+    )));
+    if let Some(id) = last_event_id {
+        request = request.header(LAST_EVENT_ID_HEADER, id);
+    }
+    let response = configuration
+        .apply_auth(request)
+        .json(body)
+        .send()
+        .await
+        .map_err(Error::from)?;
+
+    if !response.status().is_success() {
+        let status = response.status();
+        let headers = response.headers().clone();
+        let text = response.text().await.unwrap_or_default();
+        return Err(Error::from_status(status, &text).with_retry_after(&headers));
+    }
+    Ok(response)
+}
+
+/// Header telling the server the id of the last event received before a drop
+const LAST_EVENT_ID_HEADER: &str = "Last-Event-ID";
+
+type EventStream = Pin<
+    Box<
+        dyn Stream<
+                Item = std::result::Result<
+                    eventsource_stream::Event,
+                    eventsource_stream::EventStreamError<reqwest::Error>,
+                >,
+            > + Send,
+    >,
+>;
+
+fn events(response: reqwest::Response) -> EventStream {
+    Box::pin(response.bytes_stream().eventsource())
+}
+
+/// What's needed to re-send a stream's request after the connection drops
+struct Resume {
+    configuration: Configuration,
+    category: EndpointCategory,
+    path: &'static str,
+    body: serde_json::Value,
+    attempts_left: u32,
+}
+
+impl Resume {
+    /// Resume state for `body`, or `None` if `stream_resume_attempts` is zero
+    fn new<B: serde::Serialize>(
+        configuration: &Configuration,
+        category: EndpointCategory,
+        path: &'static str,
+        body: &B,
+    ) -> Option<Self> {
+        if configuration.stream_resume_attempts == 0 {
+            return None;
+        }
+        Some(Self {
+            configuration: configuration.clone(),
+            category,
+            path,
+            body: serde_json::to_value(body).ok()?,
+            attempts_left: configuration.stream_resume_attempts,
+        })
+    }
+
+    /// Reconnect from after `last_event_id`, or `None` if no attempts are left
+    async fn reconnect(&mut self, last_event_id: &str) -> Option<Result<EventStream>> {
+        self.attempts_left = self.attempts_left.checked_sub(1)?;
+        let response = post_stream(
+            &self.configuration,
+            self.category,
+            self.path,
+            &self.body,
+            Some(last_event_id),
+        )
+        .await;
+        Some(response.map(events))
+    }
+}
+
+/// Progress through an event stream that may be resumed
+struct EventState {
+    events: EventStream,
+    last_event_id: Option<String>,
+    resume: Option<Resume>,
+}
+
+/// Parse server-sent events into chunks, ending at the `[DONE]` sentinel
+///
+/// Events after `[DONE]` are never read, so a server that keeps the connection
+/// open can't stall the stream. If the connection closes without the sentinel,
+/// the stream ends normally unless `require_done` is set.
+///
+/// With `resume`, a connection that fails or closes before `[DONE]` is
+/// reopened with `Last-Event-ID` set to the last event id received, so the
+/// server continues where it left off. Servers that don't assign event ids
+/// can't be resumed, and are handled as without `resume`.
+///
+/// Some gateways report failures as an in-band `{"error": {...}}` event on a
+/// 200 response; such an event is yielded as the matching typed error and
+/// ends the stream.
+///
+/// Network frames can end in the middle of a multibyte character. The event
+/// parser buffers incomplete UTF-8 sequences until the rest arrives, so event
+/// data is only decoded once whole.
+fn event_chunks<T>(
+    response: reqwest::Response,
+    require_done: bool,
+    resume: Option<Resume>,
+) -> impl Stream<Item = Result<T>>
+where
+    T: DeserializeOwned,
+{
+    let state = EventState {
+        events: events(response),
+        last_event_id: None,
+        resume,
+    };
+    stream::unfold(Some(state), move |state| async move {
+        let mut state = state?;
+        loop {
+            let dropped = match state.events.next().await {
+                Some(Ok(event)) if event.data == "[DONE]" => return None,
+                Some(Ok(event)) => {
+                    if !event.id.is_empty() {
+                        state.last_event_id = Some(event.id);
+                    }
+                    if is_padding(&event.event, &event.data) {
+                        continue;
                     }
-                    if choice.finish_reason.is_some() {
-                        finish_reason = choice.finish_reason.map(|fr| match fr {
-                            crate::models::completion_choice_delta::FinishReason::Stop => {
-                                crate::models::completion_choice::FinishReason::Stop
-                            }
-                            crate::models::completion_choice_delta::FinishReason::Length => {
-                                crate::models::completion_choice::FinishReason::Length
-                            }
-                        });
+                    if let Some(error) = error_event(&event.event, &event.data) {
+                        return Some((Err(error), None));
                     }
+                    let item = serde_json::from_str::<T>(&event.data)
+                        .map_err(|e| Error::decode(e, &event.data));
+                    return Some((item, Some(state)));
                 }
+                Some(Err(e)) => Some(Error::Streaming(format!("Event stream error: {}", e))),
+                None if require_done => {
+                    Some(Error::Streaming("Stream closed before [DONE]".into()))
+                }
+                None => None,
+            };
+
+            let reconnected = match (&mut state.resume, &state.last_event_id) {
+                (Some(resume), Some(id)) => resume.reconnect(id).await,
+                _ => None,
+            };
+            match reconnected {
+                Some(Ok(events)) => state.events = events,
+                Some(Err(e)) => return Some((Err(e), None)),
+                None => return dropped.map(|e| (Err(e), None)),
             }
         }
+    })
+}
 
-        Ok(Completion {
-            id,
-            object: Some(crate::models::completion::Object::TextCompletion),
-            created,
-            model,
-            system_fingerprint: None,
-            choices: Some(vec![CompletionChoice {
-                index: Some(0),
-                text: Some(texts.join("")),
-                finish_reason,
-            }]),
-            usage: None,
-            time_info: None,
-        })
-    }
+/// Whether an event is keep-alive or padding filler rather than a chunk
+///
+/// Comment lines never reach here, since the event parser drops them. Some
+/// servers and proxies also send `ping` events or blank `data:` lines to keep
+/// the connection open or to pad frames against length side channels; these
+/// are skipped without attempting to deserialize them.
+fn is_padding(event_type: &str, data: &str) -> bool {
+    matches!(event_type, "ping" | "keepalive") || data.trim().is_empty()
 }
 
-impl Stream for CompletionStream {
-    type Item = Result<CompletionChunk>;
+/// Body of an in-band error event
+#[derive(serde::Deserialize)]
+struct ErrorEvent {
+    error: ErrorDetail,
+}
 
-    fn poll_next(self: Pin<&mut Self>, cx: &mut Context<'_>) -> Poll<Option<Self::Item>> {
-        let this = self.project();
-        this.inner.poll_next(cx)
+/// Parse an SSE event reporting an error, if it is one
+///
+/// Chunk fields are all optional, so an error body would otherwise
+/// deserialize as an empty chunk; check for it before parsing the chunk.
+fn error_event(event_type: &str, data: &str) -> Option<Error> {
+    if event_type != "error" && !data.contains("\"error\"") {
+        return None;
+    }
+    match serde_json::from_str::<ErrorEvent>(data) {
+        Ok(ErrorEvent { error }) => Some(Error::from_detail(error)),
+        Err(_) if event_type == "error" => Some(Error::Streaming(data.to_string())),
+        Err(_) => None,
     }
 }
 
 #[cfg(test)]
 mod tests {
     use super::*;
+    use wiremock::matchers::{method, path};
+    use wiremock::{Mock, MockServer, ResponseTemplate};
+
+    /// Serve the given SSE `data:` payloads from a mock server
+    async fn mock_sse(route: &str, events: &[&str]) -> (MockServer, Configuration) {
+        let body: String = events
+            .iter()
+            .map(|data| format!("data: {}\n\n", data))
+            .collect();
+        let server = MockServer::start().await;
+        Mock::given(method("POST"))
+            .and(path(route))
+            .respond_with(
+                ResponseTemplate::new(200)
+                    .insert_header("content-type", "text/event-stream")
+                    .set_body_string(body),
+            )
+            .mount(&server)
+            .await;
+
+        let mut configuration = Configuration::new();
+        configuration.base_path = server.uri();
+        configuration.bearer_access_token = Some("test-key".to_string());
+        (server, configuration)
+    }
+
+    fn chat_chunk(content: &str) -> String {
+        serde_json::json!({
+            "id": "chatcmpl-1",
+            "object": "chat.completion.chunk",
+            "model": "llama3.1-8b",
+            "choices": [{ "index": 0, "delta": { "content": content } }]
+        })
+        .to_string()
+    }
+
+    #[tokio::test]
+    async fn test_chat_stream_usage_updates() {
+        let usage_chunk = |content: Option<&str>, completion_tokens: i32| {
+            let choices = match content {
+                Some(content) => {
+                    serde_json::json!([{ "index": 0, "delta": { "content": content } }])
+                }
+                None => serde_json::json!([]),
+            };
+            serde_json::json!({
+                "choices": choices,
+                "usage": {
+                    "prompt_tokens": 10,
+                    "completion_tokens": completion_tokens,
+                    "total_tokens": 10 + completion_tokens
+                }
+            })
+            .to_string()
+        };
+        let events = [
+            usage_chunk(Some("Hello"), 1),
+            chat_chunk(","),
+            usage_chunk(Some(" world"), 3),
+            usage_chunk(None, 3),
+            "[DONE]".to_string(),
+        ];
+        let events: Vec<&str> = events.iter().map(String::as_str).collect();
+
+        let (_server, configuration) = mock_sse("/chat/completions", &events).await;
+        let stream = ChatCompletionStream::new(&configuration, ChatCompletionRequest::default())
+            .await
+            .unwrap();
+        let (mut stream, usage) = stream.with_usage();
+        let mut seen = Vec::new();
+        while let Some(chunk) = stream.next().await {
+            chunk.unwrap();
+            seen.push(
+                usage
+                    .lock()
+                    .unwrap()
+                    .as_ref()
+                    .and_then(|u| u.completion_tokens),
+            );
+        }
+        assert_eq!(seen, vec![Some(1), Some(1), Some(3), Some(3)]);
+
+        let (_server, configuration) = mock_sse("/chat/completions", &events).await;
+        let completion =
+            ChatCompletionStream::new(&configuration, ChatCompletionRequest::default())
+                .await
+                .unwrap()
+                .collect()
+                .await
+                .unwrap();
+        assert_eq!(completion.usage.unwrap().total_tokens, Some(13));
+    }
+
+    #[tokio::test]
+    async fn test_chat_stream_split() {
+        let (_server, configuration) = mock_sse(
+            "/chat/completions",
+            &[&chat_chunk("Hello"), &chat_chunk(", world"), "[DONE]"],
+        )
+        .await;
+
+        let stream = ChatCompletionStream::new(&configuration, ChatCompletionRequest::default())
+            .await
+            .unwrap();
+        let (live, aggregate) = stream.split();
+
+        let chunks: Vec<_> = StreamExt::collect::<Vec<_>>(live).await;
+        assert_eq!(chunks.len(), 2);
+
+        let completion = aggregate.await.unwrap();
+        assert_eq!(completion.id.as_deref(), Some("chatcmpl-1"));
+        let message = completion.choices.unwrap()[0].message.clone().unwrap();
+        assert_eq!(message.content, "Hello, world");
+    }
+
+    #[tokio::test]
+    async fn test_split_aggregate_resolves_at_end_of_stream() {
+        let (_server, configuration) = mock_sse(
+            "/chat/completions",
+            &[&chat_chunk("Hello"), &chat_chunk(", world"), "[DONE]"],
+        )
+        .await;
+
+        let stream = ChatCompletionStream::new(&configuration, ChatCompletionRequest::default())
+            .await
+            .unwrap();
+        let (mut live, aggregate) = stream.split();
+        while let Some(chunk) = live.next().await {
+            chunk.unwrap();
+        }
+
+        // `live` is still alive; the aggregate must not wait for it to be dropped
+        let completion = tokio::time::timeout(std::time::Duration::from_secs(2), aggregate)
+            .await
+            .expect("aggregate did not resolve at end of stream")
+            .unwrap();
+        let message = completion.choices.unwrap()[0].message.clone().unwrap();
+        assert_eq!(message.content, "Hello, world");
+        drop(live);
+    }
+
+    #[tokio::test]
+    async fn test_split_aggregate_fails_if_dropped_early() {
+        let (_server, configuration) = mock_sse(
+            "/chat/completions",
+            &[&chat_chunk("Hello"), &chat_chunk(", world"), "[DONE]"],
+        )
+        .await;
+
+        let stream = ChatCompletionStream::new(&configuration, ChatCompletionRequest::default())
+            .await
+            .unwrap();
+        let (mut live, aggregate) = stream.split();
+        live.next().await.unwrap().unwrap();
+        drop(live);
+
+        let err = aggregate.await.unwrap_err();
+        assert!(matches!(err, Error::Streaming(ref m) if m.contains("dropped")));
+    }
+
+    #[tokio::test]
+    async fn test_chat_stream_collects_tool_calls() {
+        let tool_chunk = |calls: serde_json::Value, finish_reason: Option<&str>| {
+            serde_json::json!({
+                "id": "chatcmpl-1",
+                "choices": [{
+                    "index": 0,
+                    "delta": { "tool_calls": calls },
+                    "finish_reason": finish_reason
+                }]
+            })
+            .to_string()
+        };
+        let (_server, configuration) = mock_sse(
+            "/chat/completions",
+            &[
+                &tool_chunk(
+                    serde_json::json!([{ "id": "call_1", "name": "get_weather", "arguments": "{\"ci" }]),
+                    None,
+                ),
+                &tool_chunk(serde_json::json!([{ "arguments": "ty\": \"Paris\"}" }]), None),
+                &tool_chunk(
+                    serde_json::json!([{ "id": "call_2", "name": "get_time", "arguments": "{}" }]),
+                    None,
+                ),
+                &tool_chunk(serde_json::json!([]), Some("tool_calls")),
+                "[DONE]",
+            ],
+        )
+        .await;
+
+        let stream = ChatCompletionStream::new(&configuration, ChatCompletionRequest::default())
+            .await
+            .unwrap();
+        let completion = stream.collect().await.unwrap();
+        assert!(completion.has_tool_calls());
+        let calls: Vec<_> = completion
+            .tool_calls()
+            .into_iter()
+            .map(|call| {
+                (
+                    call.id.as_deref().unwrap(),
+                    call.name.as_deref().unwrap(),
+                    call.arguments.as_deref().unwrap(),
+                )
+            })
+            .collect();
+        assert_eq!(
+            calls,
+            vec![
+                ("call_1", "get_weather", "{\"city\": \"Paris\"}"),
+                ("call_2", "get_time", "{}"),
+            ]
+        );
+    }
+
+    #[tokio::test]
+    async fn test_chat_text_stream() {
+        let (_server, configuration) = mock_sse(
+            "/chat/completions",
+            &[
+                &chat_chunk("Hello"),
+                &chat_chunk(""),
+                &chat_chunk(" world"),
+                "[DONE]",
+            ],
+        )
+        .await;
+
+        let stream = ChatCompletionStream::new(&configuration, ChatCompletionRequest::default())
+            .await
+            .unwrap();
+        let tokens: Vec<_> = StreamExt::collect::<Vec<_>>(stream.text_stream()).await;
+        let tokens: Vec<_> = tokens.into_iter().map(|t| t.unwrap()).collect();
+        assert_eq!(tokens, vec!["Hello", " world"]);
+    }
+
+    #[tokio::test]
+    async fn test_chat_collect_json() {
+        #[derive(serde::Deserialize)]
+        struct City {
+            name: String,
+            tags: Vec<String>,
+        }
+
+        // The error after the closing brace is never read
+        let error = r#"{"error": {"type": "server_error", "message": "Too late"}}"#;
+        let (_server, configuration) = mock_sse(
+            "/chat/completions",
+            &[
+                &chat_chunk("Here you go:\n```json\n{\"name\": \"Pa"),
+                &chat_chunk("ris\", \"tags\": [\"}\"]}"),
+                error,
+            ],
+        )
+        .await;
+        let stream = ChatCompletionStream::new(&configuration, ChatCompletionRequest::default())
+            .await
+            .unwrap();
+        let city: City = stream.collect_json().await.unwrap();
+        assert_eq!(city.name, "Paris");
+        assert_eq!(city.tags, vec!["}"]);
+
+        // A nested array closing first doesn't end the stream
+        let (_server, configuration) = mock_sse(
+            "/chat/completions",
+            &[
+                &chat_chunk("{\"tags\": [\"a\"]"),
+                &chat_chunk(", \"name\": \"Lyon\"}"),
+                "[DONE]",
+            ],
+        )
+        .await;
+        let stream = ChatCompletionStream::new(&configuration, ChatCompletionRequest::default())
+            .await
+            .unwrap();
+        let city: City = stream.collect_json().await.unwrap();
+        assert_eq!(city.name, "Lyon");
+        assert_eq!(city.tags, vec!["a"]);
+
+        let (_server, configuration) = mock_sse(
+            "/chat/completions",
+            &[&chat_chunk("No JSON {here"), "[DONE]"],
+        )
+        .await;
+        let stream = ChatCompletionStream::new(&configuration, ChatCompletionRequest::default())
+            .await
+            .unwrap();
+        assert!(matches!(
+            stream.collect_json::<serde_json::Value>().await,
+            Err(Error::Api(_))
+        ));
+
+        let truncated = serde_json::json!({
+            "choices": [{ "index": 0, "delta": { "content": ", \"tags\": [" }, "finish_reason": "length" }]
+        })
+        .to_string();
+        let (_server, configuration) = mock_sse(
+            "/chat/completions",
+            &[&chat_chunk("{\"name\": \"Paris\""), &truncated, "[DONE]"],
+        )
+        .await;
+        let stream = ChatCompletionStream::new(&configuration, ChatCompletionRequest::default())
+            .await
+            .unwrap();
+        assert!(matches!(
+            stream.collect_json::<serde_json::Value>().await,
+            Err(Error::InvalidRequest(_))
+        ));
+    }
+
+    #[tokio::test]
+    async fn test_chat_into_async_read() {
+        use tokio::io::AsyncReadExt;
+
+        let (_server, configuration) = mock_sse(
+            "/chat/completions",
+            &[&chat_chunk("Hello"), &chat_chunk(", world"), "[DONE]"],
+        )
+        .await;
+        let stream = ChatCompletionStream::new(&configuration, ChatCompletionRequest::default())
+            .await
+            .unwrap();
+        let mut text = String::new();
+        stream
+            .into_async_read()
+            .read_to_string(&mut text)
+            .await
+            .unwrap();
+        assert_eq!(text, "Hello, world");
+
+        let error = r#"{"error": {"type": "rate_limit_error", "message": "Slow down"}}"#;
+        let (_server, configuration) =
+            mock_sse("/chat/completions", &[&chat_chunk("Hello"), error]).await;
+        let stream = ChatCompletionStream::new(&configuration, ChatCompletionRequest::default())
+            .await
+            .unwrap();
+        let err = stream
+            .into_async_read()
+            .read_to_end(&mut Vec::new())
+            .await
+            .unwrap_err();
+        let inner = err.into_inner().unwrap().downcast::<Error>().unwrap();
+        assert!(matches!(*inner, Error::RateLimit(_)));
+    }
+
+    #[tokio::test]
+    async fn test_chat_take_until() {
+        let (_server, configuration) = mock_sse(
+            "/chat/completions",
+            &[
+                &chat_chunk("{\"a\": "),
+                &chat_chunk("1}"),
+                &chat_chunk(" and some"),
+                &chat_chunk(" trailing prose"),
+                "[DONE]",
+            ],
+        )
+        .await;
+
+        let stream = ChatCompletionStream::new(&configuration, ChatCompletionRequest::default())
+            .await
+            .unwrap();
+        let completion = stream
+            .take_until(|content| content.contains('}'))
+            .collect()
+            .await
+            .unwrap();
+        assert_eq!(
+            completion.choices.unwrap()[0]
+                .message
+                .as_ref()
+                .unwrap()
+                .content,
+            "{\"a\": 1}"
+        );
+
+        // A predicate that never matches passes everything through
+        let stream = ChatCompletionStream::new(&configuration, ChatCompletionRequest::default())
+            .await
+            .unwrap();
+        let items = StreamExt::collect::<Vec<_>>(stream.take_until(|_| false)).await;
+        assert_eq!(items.len(), 4);
+    }
+
+    #[tokio::test]
+    async fn test_stream_without_done_sentinel() {
+        let events = [chat_chunk("Hello"), chat_chunk(", world")];
+        let events: Vec<&str> = events.iter().map(String::as_str).collect();
+
+        let (_server, configuration) = mock_sse("/chat/completions", &events).await;
+        let completion =
+            ChatCompletionStream::new(&configuration, ChatCompletionRequest::default())
+                .await
+                .unwrap()
+                .collect()
+                .await
+                .unwrap();
+        let message = completion.choices.unwrap()[0].message.clone().unwrap();
+        assert_eq!(message.content, "Hello, world");
+
+        let (_server, mut configuration) = mock_sse("/chat/completions", &events).await;
+        configuration.require_stream_done = true;
+        let result = ChatCompletionStream::new(&configuration, ChatCompletionRequest::default())
+            .await
+            .unwrap()
+            .collect()
+            .await;
+        assert!(matches!(result, Err(Error::Streaming(_))));
+    }
+
+    #[tokio::test]
+    async fn test_stream_stops_at_done_sentinel() {
+        let (_server, configuration) = mock_sse(
+            "/chat/completions",
+            &[&chat_chunk("kept"), "[DONE]", &chat_chunk("ignored")],
+        )
+        .await;
+
+        let stream = ChatCompletionStream::new(&configuration, ChatCompletionRequest::default())
+            .await
+            .unwrap();
+        let chunks = StreamExt::collect::<Vec<_>>(stream).await;
+        assert_eq!(chunks.len(), 1);
+    }
+
+    #[tokio::test]
+    async fn test_chat_stream_token_timings() {
+        let (_server, configuration) = mock_sse(
+            "/chat/completions",
+            &[
+                &chat_chunk("Hello"),
+                &chat_chunk(""),
+                &chat_chunk(" world"),
+                "[DONE]",
+            ],
+        )
+        .await;
+
+        let stream = ChatCompletionStream::new(&configuration, ChatCompletionRequest::default())
+            .await
+            .unwrap();
+        let (stream, timings) = stream.with_token_timings();
+        StreamExt::collect::<Vec<_>>(stream).await;
+
+        let timings = timings.lock().unwrap();
+        let tokens: Vec<_> = timings.tokens().iter().map(|(t, _)| t.as_str()).collect();
+        assert_eq!(tokens, vec!["Hello", " world"]);
+        assert!(timings.tokens()[0].1 <= timings.tokens()[1].1);
+    }
 
-    // Note: Actual streaming tests would require a mock server
-    // These are just compilation tests
+    #[tokio::test]
+    async fn test_stream_resumes_with_last_event_id() {
+        use wiremock::matchers::header;
+
+        let sse = |events: &[(&str, &str)], done: bool| {
+            let mut body: String = events
+                .iter()
+                .map(|(id, content)| format!("id: {}\ndata: {}\n\n", id, chat_chunk(content)))
+                .collect();
+            if done {
+                body.push_str("data: [DONE]\n\n");
+            }
+            ResponseTemplate::new(200)
+                .insert_header("content-type", "text/event-stream")
+                .set_body_string(body)
+        };
+        let server = MockServer::start().await;
+        Mock::given(method("POST"))
+            .and(path("/chat/completions"))
+            .and(header("last-event-id", "2"))
+            .respond_with(sse(&[("3", "!")], true))
+            .with_priority(1)
+            .mount(&server)
+            .await;
+        // The first connection drops after two events
+        Mock::given(method("POST"))
+            .and(path("/chat/completions"))
+            .respond_with(sse(&[("1", "Hello"), ("2", " world")], false))
+            .with_priority(2)
+            .mount(&server)
+            .await;
+
+        let mut configuration = Configuration::new();
+        configuration.base_path = server.uri();
+        configuration.bearer_access_token = Some("test-key".to_string());
+        let request = ChatCompletionRequest::builder(ModelIdentifier::Llama3Period18b)
+            .user_message("Hi")
+            .build();
+
+        // Without resumption the stream ends at the drop
+        let completion = ChatCompletionStream::new(&configuration, request.clone())
+            .await
+            .unwrap()
+            .collect()
+            .await
+            .unwrap();
+        let content = |c: &ChatCompletion| {
+            c.choices.as_ref().unwrap()[0]
+                .message
+                .clone()
+                .unwrap()
+                .content
+        };
+        assert_eq!(content(&completion), "Hello world");
+
+        configuration.stream_resume_attempts = 1;
+        let completion = ChatCompletionStream::new(&configuration, request)
+            .await
+            .unwrap()
+            .collect()
+            .await
+            .unwrap();
+        assert_eq!(content(&completion), "Hello world!");
+        let received = server.received_requests().await.unwrap();
+        assert_eq!(received.len(), 3);
+        let body =
+            |i: usize| serde_json::from_slice::<serde_json::Value>(&received[i].body).unwrap();
+        assert_eq!(body(1), body(2));
+    }
+
+    /// Serve one streaming response whose body is written in the given frames
+    async fn serve_frames(frames: Vec<Vec<u8>>) -> Configuration {
+        use tokio::io::{AsyncReadExt, AsyncWriteExt};
+
+        let listener = tokio::net::TcpListener::bind("127.0.0.1:0").await.unwrap();
+        let address = listener.local_addr().unwrap();
+        tokio::spawn(async move {
+            let (mut socket, _) = listener.accept().await.unwrap();
+            // Read the whole request so closing the socket doesn't reset it
+            let mut request = Vec::new();
+            let mut buf = [0; 4096];
+            loop {
+                let n = socket.read(&mut buf).await.unwrap();
+                request.extend_from_slice(&buf[..n]);
+                let text = String::from_utf8_lossy(&request).to_lowercase();
+                if let Some(end) = text.find("\r\n\r\n") {
+                    let length: usize = text
+                        .lines()
+                        .find_map(|line| line.strip_prefix("content-length:"))
+                        .map_or(0, |value| value.trim().parse().unwrap());
+                    if request.len() >= end + 4 + length {
+                        break;
+                    }
+                }
+            }
+
+            socket
+                .write_all(b"HTTP/1.1 200 OK\r\ncontent-type: text/event-stream\r\nconnection: close\r\n\r\n")
+                .await
+                .unwrap();
+            for frame in frames {
+                socket.write_all(&frame).await.unwrap();
+                socket.flush().await.unwrap();
+                tokio::time::sleep(std::time::Duration::from_millis(20)).await;
+            }
+        });
+
+        let mut configuration = Configuration::new();
+        configuration.base_path = format!("http://{}", address);
+        configuration.bearer_access_token = Some("test-key".to_string());
+        configuration
+    }
+
+    #[tokio::test]
+    async fn test_multibyte_character_split_across_frames() {
+        let body = format!("data: {}\n\ndata: [DONE]\n\n", chat_chunk("Hi 👋 there"));
+        let bytes = body.into_bytes();
+        // Split inside the four-byte emoji
+        let split = bytes.windows(4).position(|w| w == "👋".as_bytes()).unwrap() + 2;
+        let configuration =
+            serve_frames(vec![bytes[..split].to_vec(), bytes[split..].to_vec()]).await;
+
+        let request = ChatCompletionRequest::builder(ModelIdentifier::Llama3Period18b)
+            .user_message("Wave")
+            .build();
+        let completion = ChatCompletionStream::new(&configuration, request)
+            .await
+            .unwrap()
+            .collect()
+            .await
+            .unwrap();
+        assert_eq!(
+            completion.choices.unwrap()[0]
+                .message
+                .as_ref()
+                .unwrap()
+                .content,
+            "Hi 👋 there"
+        );
+    }
+
+    #[tokio::test]
+    async fn test_stream_error_event() {
+        let error = serde_json::json!({
+            "error": {
+                "type": "invalid_request_error",
+                "code": "context_length_exceeded",
+                "message": "Please reduce the length of the messages"
+            }
+        })
+        .to_string();
+        let (_server, configuration) = mock_sse(
+            "/chat/completions",
+            &[&chat_chunk("Hello"), &error, &chat_chunk("never seen")],
+        )
+        .await;
+
+        let stream = ChatCompletionStream::new(&configuration, ChatCompletionRequest::default())
+            .await
+            .unwrap();
+        let items = StreamExt::collect::<Vec<_>>(stream).await;
+        assert_eq!(items.len(), 2);
+        assert!(items[0].is_ok());
+        assert!(matches!(items[1], Err(Error::ContextLengthExceeded(_))));
+
+        let rate_limited = error_event(
+            "",
+            r#"{"error": {"type": "rate_limit_error", "message": "Slow down"}}"#,
+        );
+        assert!(matches!(rate_limited, Some(Error::RateLimit(_))));
+        assert!(matches!(
+            error_event("error", "upstream unavailable"),
+            Some(Error::Streaming(_))
+        ));
+        assert!(error_event("", &chat_chunk("an \"error\" in content")).is_none());
+    }
+
+    #[tokio::test]
+    async fn test_chat_first_token() {
+        let (_server, configuration) = mock_sse(
+            "/chat/completions",
+            &[
+                &chat_chunk(""),
+                &chat_chunk("B"),
+                &chat_chunk("illing"),
+                "[DONE]",
+            ],
+        )
+        .await;
+        let stream = ChatCompletionStream::new(&configuration, ChatCompletionRequest::default())
+            .await
+            .unwrap();
+        assert_eq!(stream.first_token().await.unwrap().as_deref(), Some("B"));
+
+        let (_server, configuration) = mock_sse("/chat/completions", &["[DONE]"]).await;
+        let stream = ChatCompletionStream::new(&configuration, ChatCompletionRequest::default())
+            .await
+            .unwrap();
+        assert_eq!(stream.first_token().await.unwrap(), None);
+    }
+
+    #[tokio::test]
+    async fn test_stream_parse_error_keeps_payload() {
+        let (_server, configuration) = mock_sse(
+            "/chat/completions",
+            &[&chat_chunk("Hello"), r#"{"choices": "not a list"}"#],
+        )
+        .await;
+
+        let stream = ChatCompletionStream::new(&configuration, ChatCompletionRequest::default())
+            .await
+            .unwrap();
+        let items = StreamExt::collect::<Vec<_>>(stream).await;
+        assert!(items[0].is_ok());
+        assert!(matches!(
+            &items[1],
+            Err(Error::Decode { payload, .. }) if payload == r#"{"choices": "not a list"}"#
+        ));
+    }
+
+    #[tokio::test]
+    async fn test_stream_skips_padding_events() {
+        let body = format!(
+            ": {}\n\ndata: {}\n\ndata:\n\ndata:    \n\nevent: ping\ndata: {{}}\n\n\
+             event: keepalive\ndata: ok\n\n: pad\ndata: {}\n\ndata: [DONE]\n\n",
+            "x".repeat(512),
+            chat_chunk("Hello"),
+            chat_chunk(" world"),
+        );
+        let (server, configuration) = mock_sse("/unused", &[]).await;
+        Mock::given(method("POST"))
+            .and(path("/chat/completions"))
+            .respond_with(
+                ResponseTemplate::new(200)
+                    .insert_header("content-type", "text/event-stream")
+                    .set_body_string(body),
+            )
+            .mount(&server)
+            .await;
+
+        let stream = ChatCompletionStream::new(&configuration, ChatCompletionRequest::default())
+            .await
+            .unwrap();
+        let items = StreamExt::collect::<Vec<_>>(stream).await;
+        let texts: Vec<_> = items
+            .iter()
+            .map(|item| item.as_ref().unwrap().delta_text().unwrap_or_default())
+            .collect();
+        assert_eq!(texts, vec!["Hello", " world"]);
+    }
+
+    #[tokio::test]
+    async fn test_stream_startup_error_is_structured() {
+        let (server, configuration) = mock_sse("/unused", &[]).await;
+        Mock::given(method("POST"))
+            .and(path("/chat/completions"))
+            .respond_with(ResponseTemplate::new(422).set_body_json(serde_json::json!({
+                "error": {
+                    "type": "invalid_request_error",
+                    "param": "temperature",
+                    "message": "temperature must be between 0 and 1.5"
+                }
+            })))
+            .mount(&server)
+            .await;
+
+        let result =
+            ChatCompletionStream::new(&configuration, ChatCompletionRequest::default()).await;
+        assert!(matches!(
+            result,
+            Err(Error::InvalidRequest(ref m)) if m == "temperature must be between 0 and 1.5"
+        ));
+
+        Mock::given(method("POST"))
+            .and(path("/completions"))
+            .respond_with(ResponseTemplate::new(409).set_body_string(
+                r#"{"type": "conflict_error", "code": "model_busy", "message": "Busy"}"#,
+            ))
+            .mount(&server)
+            .await;
+        let result = CompletionStream::new(&configuration, CompletionRequest::default()).await;
+        assert!(matches!(
+            result,
+            Err(Error::ApiError { ref code, ref message, .. }) if code == "model_busy" && message == "Busy"
+        ));
+
+        // Rate limits carry the wait from `Retry-After`
+        let (server, configuration) = mock_sse("/unused", &[]).await;
+        Mock::given(method("POST"))
+            .and(path("/chat/completions"))
+            .respond_with(ResponseTemplate::new(429).insert_header("retry-after", "7"))
+            .mount(&server)
+            .await;
+        let result =
+            ChatCompletionStream::new(&configuration, ChatCompletionRequest::default()).await;
+        assert!(matches!(result, Err(Error::RateLimit(7))));
+    }
 
     #[test]
     fn test_stream_types() {
@@ -0,0 +1,149 @@
+//! Request tracing, enabled with the `tracing` feature
+
+use std::future::Future;
+
+use crate::{Result, apis::configuration::Configuration};
+
+/// Run `future` inside a span tagged with the endpoint and client request id
+///
+/// Failures are recorded as a `warn` event within the span so the request id
+/// is attached to the error in logs. Without the `tracing` feature this is a
+/// no-op.
+#[cfg(feature = "tracing")]
+pub(crate) async fn instrument<T, F>(
+    endpoint: &'static str,
+    configuration: &Configuration,
+    future: F,
+) -> Result<T>
+where
+    F: Future<Output = Result<T>>,
+{
+    use tracing::Instrument;
+
+    let span = tracing::debug_span!(
+        "cerebras_request",
+        endpoint,
+        request_id = configuration.request_id.as_deref()
+    );
+    async move {
+        let result = future.await;
+        if let Err(ref error) = result {
+            tracing::warn!(%error, "request failed");
+        }
+        result
+    }
+    .instrument(span)
+    .await
+}
+
+/// Run `future` inside a span tagged with the endpoint and client request id
+///
+/// Without the `tracing` feature this is a no-op.
+#[cfg(not(feature = "tracing"))]
+pub(crate) async fn instrument<T, F>(
+    _endpoint: &'static str,
+    _configuration: &Configuration,
+    future: F,
+) -> Result<T>
+where
+    F: Future<Output = Result<T>>,
+{
+    future.await
+}
+
+/// Running totals for the summary a stream logs when it is dropped
+///
+/// Completion tokens are taken from the usage the stream reports, if any,
+/// and otherwise estimated from the content received.
+#[cfg(feature = "tracing")]
+#[derive(Debug)]
+pub(crate) struct StreamSummary {
+    metrics: crate::streaming::StreamMetrics,
+    content_chars: usize,
+    completion_tokens: Option<i32>,
+}
+
+#[cfg(feature = "tracing")]
+impl StreamSummary {
+    pub(crate) fn new(started_at: std::time::Instant) -> Self {
+        Self {
+            metrics: crate::streaming::StreamMetrics::new(started_at),
+            content_chars: 0,
+            completion_tokens: None,
+        }
+    }
+
+    /// Record a chunk's content and, if it reports usage, its token totals
+    pub(crate) fn record(&mut self, content: &str, usage: Option<&crate::models::Usage>) {
+        if !content.is_empty() {
+            self.metrics.record_token();
+            self.content_chars += content.chars().count();
+        }
+        if let Some(tokens) = usage.and_then(|usage| usage.completion_tokens) {
+            self.completion_tokens = Some(tokens);
+        }
+    }
+
+    /// Completion tokens, and whether the count is an estimate
+    fn tokens(&self) -> (u64, bool) {
+        match self.completion_tokens {
+            Some(tokens) => (u64::try_from(tokens).unwrap_or(0), false),
+            None => (
+                self.content_chars.div_ceil(crate::tokens::CHARS_PER_TOKEN) as u64,
+                true,
+            ),
+        }
+    }
+
+    /// Completion tokens per second of generation, from the first to the last content chunk
+    fn tokens_per_second(&self) -> Option<f64> {
+        let elapsed = self.metrics.generation_time()?.as_secs_f64();
+        (elapsed > 0.0).then(|| self.tokens().0 as f64 / elapsed)
+    }
+}
+
+#[cfg(feature = "tracing")]
+impl Drop for StreamSummary {
+    fn drop(&mut self) {
+        let (completion_tokens, estimated) = self.tokens();
+        tracing::info!(
+            completion_tokens,
+            estimated,
+            ttft_ms = self
+                .metrics
+                .time_to_first_token()
+                .map(|ttft| ttft.as_secs_f64() * 1000.0),
+            tokens_per_second = self.tokens_per_second(),
+            "stream finished"
+        );
+    }
+}
+
+#[cfg(all(test, feature = "tracing"))]
+mod tests {
+    use super::*;
+    use std::time::{Duration, Instant};
+
+    #[test]
+    fn test_stream_summary_tokens() {
+        let started_at = Instant::now();
+        let mut summary = StreamSummary::new(started_at);
+        summary.record("Hello, ", None);
+        summary.record("world!", None);
+        assert_eq!(summary.tokens(), (4, true));
+
+        let usage = crate::models::Usage {
+            completion_tokens: Some(3),
+            ..Default::default()
+        };
+        summary.record("", Some(&usage));
+        assert_eq!(summary.tokens(), (3, false));
+
+        summary.metrics = crate::streaming::StreamMetrics::new(started_at);
+        summary.metrics.record_token_at(started_at);
+        summary
+            .metrics
+            .record_token_at(started_at + Duration::from_secs(2));
+        assert_eq!(summary.tokens_per_second(), Some(1.5));
+    }
+}
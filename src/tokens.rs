@@ -0,0 +1,169 @@
+//! Offline token count estimates
+//!
+//! These are heuristics for budgeting, not exact counts: they assume roughly
+//! four characters per token, which holds for typical English text on the
+//! Llama and Qwen tokenizers. Use [`Client::count_tokens`](crate::Client::count_tokens)
+//! when an exact count matters.
+
+use crate::models::ChatMessage;
+
+/// Average number of characters per token assumed by the estimates
+pub const CHARS_PER_TOKEN: usize = 4;
+
+/// Tokens added per message for the role and formatting markers
+pub const MESSAGE_OVERHEAD_TOKENS: u32 = 4;
+
+/// Estimate the number of tokens in `text`
+///
+/// # Example
+/// ```rust
+/// use cerebras_rs::tokens::estimate_tokens;
+///
+/// assert_eq!(estimate_tokens(""), 0);
+/// assert_eq!(estimate_tokens("Hello, world!"), 4);
+/// ```
+pub fn estimate_tokens(text: &str) -> u32 {
+    let chars = text.chars().count();
+    u32::try_from(chars.div_ceil(CHARS_PER_TOKEN)).unwrap_or(u32::MAX)
+}
+
+/// Estimate the prompt tokens taken up by a list of chat messages
+///
+/// Counts each message's content plus [`MESSAGE_OVERHEAD_TOKENS`]. Tool call
+/// arguments are included, since they are sent back as part of the history.
+pub fn estimate_message_tokens(messages: &[ChatMessage]) -> u32 {
+    messages
+        .iter()
+        .map(|message| {
+            let tool_calls: u32 = message
+                .tool_calls
+                .iter()
+                .flatten()
+                .map(|call| {
+                    estimate_tokens(call.name.as_deref().unwrap_or_default()).saturating_add(
+                        estimate_tokens(call.arguments.as_deref().unwrap_or_default()),
+                    )
+                })
+                .fold(0, u32::saturating_add);
+            estimate_tokens(&message.content)
+                .saturating_add(tool_calls)
+                .saturating_add(MESSAGE_OVERHEAD_TOKENS)
+        })
+        .fold(0, u32::saturating_add)
+}
+
+/// Split `text` into chunks of at most `max_tokens` estimated tokens each
+///
+/// Consecutive chunks share about `overlap` tokens of text so that context
+/// isn't lost at the seams, e.g. when summarizing each chunk separately.
+/// Chunks end after whitespace where possible, so words are only split when
+/// a single word is longer than half a chunk. The result is deterministic,
+/// and with no overlap the chunks concatenate back to `text`.
+///
+/// A `max_tokens` of zero is treated as one, and `overlap` is capped below
+/// `max_tokens` so that every chunk makes progress. Empty text yields no
+/// chunks.
+///
+/// # Example
+/// ```rust
+/// use cerebras_rs::tokens::{estimate_tokens, split_for_context};
+///
+/// let document = "The quick brown fox jumps over the lazy dog. ".repeat(100);
+/// let chunks = split_for_context(&document, 256, 32);
+/// assert!(chunks.len() > 1);
+/// assert!(chunks.iter().all(|chunk| estimate_tokens(chunk) <= 256));
+/// ```
+pub fn split_for_context(text: &str, max_tokens: usize, overlap: usize) -> Vec<String> {
+    let max_chars = max_tokens.max(1).saturating_mul(CHARS_PER_TOKEN);
+    let overlap_chars = overlap
+        .min(max_tokens.saturating_sub(1))
+        .saturating_mul(CHARS_PER_TOKEN);
+    let chars: Vec<(usize, char)> = text.char_indices().collect();
+    let byte_offset = |i: usize| chars.get(i).map_or(text.len(), |&(offset, _)| offset);
+    // Whether a chunk may start or end at char `i` without splitting a word
+    let at_break = |i: usize| chars[i - 1].1.is_whitespace();
+
+    let mut chunks = Vec::new();
+    let mut start = 0;
+    while start < chars.len() {
+        let mut end = start.saturating_add(max_chars).min(chars.len());
+        if end < chars.len()
+            && let Some(boundary) = (start + max_chars / 2 + 1..=end)
+                .rev()
+                .find(|&i| at_break(i))
+        {
+            end = boundary;
+        }
+        chunks.push(text[byte_offset(start)..byte_offset(end)].to_string());
+        if end == chars.len() {
+            break;
+        }
+
+        let mut next = end.saturating_sub(overlap_chars).max(start + 1);
+        while next < end && !at_break(next) {
+            next += 1;
+        }
+        start = next;
+    }
+    chunks
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_estimate_tokens() {
+        assert_eq!(estimate_tokens("abcd"), 1);
+        assert_eq!(estimate_tokens("abcde"), 2);
+        // Characters, not bytes, are counted
+        assert_eq!(estimate_tokens("日本語の"), 1);
+    }
+
+    #[test]
+    fn test_estimate_message_tokens() {
+        let messages = [ChatMessage::system("Be brief"), ChatMessage::user("Hi")];
+        assert_eq!(estimate_message_tokens(&messages), 2 + 1 + 2 * 4);
+        assert_eq!(estimate_message_tokens(&[]), 0);
+    }
+
+    #[test]
+    fn test_split_for_context() {
+        assert!(split_for_context("", 10, 2).is_empty());
+        assert_eq!(split_for_context("Hi there", 10, 2), vec!["Hi there"]);
+
+        // Without overlap the chunks tile the text and break between words
+        let text = "alpha beta gamma delta epsilon zeta eta theta";
+        let chunks = split_for_context(text, 3, 0);
+        assert_eq!(chunks.concat(), text);
+        assert!(chunks.iter().all(|c| estimate_tokens(c) <= 3));
+        assert_eq!(chunks[0], "alpha beta ");
+
+        // With overlap each chunk repeats the end of the previous one
+        let chunks = split_for_context(text, 4, 2);
+        assert!(chunks.iter().all(|c| estimate_tokens(c) <= 4));
+        for pair in chunks.windows(2) {
+            let first_word = pair[1].split_whitespace().next().unwrap();
+            assert!(pair[0].contains(first_word), "{:?}", pair);
+        }
+        assert!(chunks.last().unwrap().ends_with("theta"));
+        assert_eq!(split_for_context(text, 4, 2), chunks);
+    }
+
+    #[test]
+    fn test_split_for_context_edge_cases() {
+        // Overlap larger than the chunk still terminates
+        let text = "one two three four five six seven eight nine ten";
+        let chunks = split_for_context(text, 2, 10);
+        assert!(chunks.iter().all(|c| estimate_tokens(c) <= 2));
+        assert!(chunks.last().unwrap().ends_with("ten"));
+
+        // Words longer than a chunk are split, on char boundaries
+        let chunks = split_for_context(&"日".repeat(10), 1, 0);
+        assert_eq!(chunks, vec!["日日日日", "日日日日", "日日"]);
+        assert_eq!(split_for_context("abcdef", 0, 0), vec!["abcd", "ef"]);
+
+        // Huge limits don't overflow
+        assert_eq!(split_for_context(text, usize::MAX, usize::MAX), vec![text]);
+    }
+}
@@ -0,0 +1,319 @@
+//! Dispatching assistant tool calls to local handlers and running tool loops
+
+use std::collections::HashMap;
+use std::fmt;
+
+use serde_json::{Value, json};
+
+use crate::{
+    Client, Error, Result,
+    models::{ChatCompletionRequest, ChatMessage, FunctionCall},
+};
+
+type Handler = Box<dyn Fn(Value) -> String + Send + Sync>;
+
+/// Routes tool calls to handlers registered by function name
+///
+/// Handlers receive the call's arguments parsed as JSON (an empty object when
+/// the model sent none) and return the content of the tool message. Calls to
+/// unregistered functions, or with arguments that aren't valid JSON, are
+/// answered with a JSON error message so the model can recover.
+///
+/// # Example
+/// ```rust
+/// use cerebras_rs::{ChatMessage, FunctionCall, ToolDispatcher};
+/// use serde_json::json;
+///
+/// let dispatcher = ToolDispatcher::new().register("get_weather", |args| {
+///     json!({ "location": args["location"], "temperature": 72 }).to_string()
+/// });
+///
+/// let mut message = ChatMessage::assistant("");
+/// message.tool_calls = Some(vec![FunctionCall {
+///     id: Some("call_1".into()),
+///     name: Some("get_weather".into()),
+///     arguments: Some(r#"{"location": "Paris"}"#.into()),
+/// }]);
+///
+/// let results = dispatcher.dispatch(&message);
+/// assert_eq!(results[0].tool_call_id.as_deref(), Some("call_1"));
+/// ```
+#[derive(Default)]
+pub struct ToolDispatcher {
+    handlers: HashMap<String, Handler>,
+}
+
+impl ToolDispatcher {
+    /// Create a dispatcher with no handlers
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Register the handler for a function, replacing any previous one
+    pub fn register<N, F, R>(mut self, name: N, handler: F) -> Self
+    where
+        N: Into<String>,
+        F: Fn(Value) -> R + Send + Sync + 'static,
+        R: Into<String>,
+    {
+        self.handlers
+            .insert(name.into(), Box::new(move |args| handler(args).into()));
+        self
+    }
+
+    /// Whether a handler is registered for `name`
+    pub fn handles(&self, name: &str) -> bool {
+        self.handlers.contains_key(name)
+    }
+
+    /// Run every tool call in `message`, returning one tool message per call
+    ///
+    /// The results are in call order, each carrying the `tool_call_id` of the
+    /// call it answers. Returns an empty vector if the message has no tool calls.
+    pub fn dispatch(&self, message: &ChatMessage) -> Vec<ChatMessage> {
+        message
+            .tool_calls
+            .iter()
+            .flatten()
+            .map(|call| ChatMessage::tool(self.call(call), call.id.clone().unwrap_or_default()))
+            .collect()
+    }
+
+    /// Run a single tool call, returning the tool message content
+    pub fn call(&self, call: &FunctionCall) -> String {
+        let name = call.name.as_deref().unwrap_or_default();
+        let Some(handler) = self.handlers.get(name) else {
+            return json!({ "error": format!("Unknown function: {}", name) }).to_string();
+        };
+
+        let args = match call.arguments.as_deref().map(str::trim) {
+            None | Some("") => Value::Object(Default::default()),
+            Some(arguments) => match serde_json::from_str(arguments) {
+                Ok(args) => args,
+                Err(e) => {
+                    return json!({
+                        "error": format!("Invalid arguments for {}: {}", name, e)
+                    })
+                    .to_string();
+                }
+            },
+        };
+        handler(args)
+    }
+}
+
+/// Outcome of [`Client::run_tools`]
+#[derive(Debug, Clone)]
+pub struct ToolRun {
+    /// The last assistant message received
+    pub message: ChatMessage,
+    /// The full conversation, including every assistant message and tool result
+    pub history: Vec<ChatMessage>,
+    /// Number of requests sent
+    pub iterations: usize,
+    /// Whether the model finished without requesting more tool calls
+    ///
+    /// `false` means the iteration cap was hit; the tool calls in `message`
+    /// have not been executed.
+    pub completed: bool,
+}
+
+impl Client {
+    /// Send `request`, executing tool calls with `dispatcher` until the model stops calling tools
+    ///
+    /// Each assistant message and its tool results are appended to the
+    /// conversation before it is sent again. At most `max_iterations` requests
+    /// are made; check [`ToolRun::completed`] to see whether the cap was hit.
+    ///
+    /// # Example
+    /// ```rust,no_run
+    /// use cerebras_rs::{ChatCompletionRequest, Client, ModelIdentifier, ToolDispatcher};
+    /// use cerebras_rs::models::{FunctionDefinition, Tool, tool};
+    ///
+    /// # async fn example() -> Result<(), Box<dyn std::error::Error>> {
+    /// let client = Client::from_env()?;
+    /// let request = ChatCompletionRequest::builder(ModelIdentifier::Llama3Period18b)
+    ///     .user_message("What's the weather like in Paris?")
+    ///     .tool(Tool {
+    ///         r#type: Some(tool::Type::Function),
+    ///         function: Some(FunctionDefinition::new("get_weather".to_string())),
+    ///     })
+    ///     .build();
+    /// let dispatcher = ToolDispatcher::new().register("get_weather", |_| r#"{"temperature": 72}"#);
+    ///
+    /// let run = client.run_tools(request, &dispatcher, 5).await?;
+    /// println!("{}", run.message.content);
+    /// # Ok(())
+    /// # }
+    /// ```
+    pub async fn run_tools(
+        &self,
+        mut request: ChatCompletionRequest,
+        dispatcher: &ToolDispatcher,
+        max_iterations: usize,
+    ) -> Result<ToolRun> {
+        let mut iterations = 0;
+        loop {
+            let response = self.chat_completion(request.clone()).await?;
+            iterations += 1;
+
+            let message = response
+                .require_choice()?
+                .message
+                .clone()
+                .ok_or_else(|| Error::Api("Response contained no message".into()))?;
+            request.messages.push(message.clone());
+
+            let has_tool_calls = message.tool_calls.as_ref().is_some_and(|c| !c.is_empty());
+            if !has_tool_calls || iterations >= max_iterations {
+                return Ok(ToolRun {
+                    message,
+                    history: request.messages,
+                    iterations,
+                    completed: !has_tool_calls,
+                });
+            }
+            request.messages.extend(dispatcher.dispatch(&message));
+        }
+    }
+}
+
+impl fmt::Debug for ToolDispatcher {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let mut names: Vec<_> = self.handlers.keys().collect();
+        names.sort();
+        f.debug_struct("ToolDispatcher")
+            .field("handlers", &names)
+            .finish()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn call(id: &str, name: &str, arguments: Option<&str>) -> FunctionCall {
+        FunctionCall {
+            id: Some(id.to_string()),
+            name: Some(name.to_string()),
+            arguments: arguments.map(str::to_string),
+        }
+    }
+
+    fn dispatcher() -> ToolDispatcher {
+        ToolDispatcher::new()
+            .register("add", |args| {
+                let sum = args["a"].as_i64().unwrap_or(0) + args["b"].as_i64().unwrap_or(0);
+                sum.to_string()
+            })
+            .register("ping", |_| "pong")
+    }
+
+    #[test]
+    fn test_dispatch_multiple_calls() {
+        let mut message = ChatMessage::assistant("");
+        message.tool_calls = Some(vec![
+            call("call_1", "add", Some(r#"{"a": 2, "b": 3}"#)),
+            call("call_2", "ping", None),
+        ]);
+
+        let results = dispatcher().dispatch(&message);
+        assert_eq!(results.len(), 2);
+        assert_eq!(results[0].content, "5");
+        assert_eq!(results[0].tool_call_id.as_deref(), Some("call_1"));
+        assert_eq!(results[1].content, "pong");
+        assert_eq!(results[1].tool_call_id.as_deref(), Some("call_2"));
+        assert_eq!(results[1].role, crate::chat_message::Role::Tool);
+    }
+
+    #[tokio::test]
+    async fn test_run_tools_loop() {
+        use crate::models::ModelIdentifier;
+        use wiremock::matchers::{method, path};
+        use wiremock::{Mock, MockServer, ResponseTemplate};
+
+        let tool_call_response = serde_json::json!({
+            "choices": [{
+                "index": 0,
+                "message": {
+                    "role": "assistant",
+                    "content": "",
+                    "tool_calls": [{ "id": "call_1", "name": "add", "arguments": "{\"a\": 2, \"b\": 3}" }]
+                },
+                "finish_reason": "tool_calls"
+            }]
+        });
+        let server = MockServer::start().await;
+        Mock::given(method("POST"))
+            .and(path("/chat/completions"))
+            .respond_with(ResponseTemplate::new(200).set_body_json(&tool_call_response))
+            .up_to_n_times(1)
+            .with_priority(1)
+            .mount(&server)
+            .await;
+        Mock::given(method("POST"))
+            .and(path("/chat/completions"))
+            .respond_with(ResponseTemplate::new(200).set_body_json(serde_json::json!({
+                "choices": [{
+                    "index": 0,
+                    "message": { "role": "assistant", "content": "The sum is 5" },
+                    "finish_reason": "stop"
+                }]
+            })))
+            .mount(&server)
+            .await;
+
+        let client = Client::new("test-key").with_base_url(server.uri());
+        let request = ChatCompletionRequest::builder(ModelIdentifier::Llama3Period18b)
+            .user_message("What is 2 + 3?")
+            .build();
+        let run = client
+            .run_tools(request.clone(), &dispatcher(), 5)
+            .await
+            .unwrap();
+
+        assert!(run.completed);
+        assert_eq!(run.iterations, 2);
+        assert_eq!(run.message.content, "The sum is 5");
+        assert_eq!(run.history.len(), 4);
+        assert_eq!(run.history[2].content, "5");
+        assert_eq!(run.history[2].tool_call_id.as_deref(), Some("call_1"));
+
+        // A cap of one stops before executing the requested tools
+        server.reset().await;
+        Mock::given(method("POST"))
+            .and(path("/chat/completions"))
+            .respond_with(ResponseTemplate::new(200).set_body_json(&tool_call_response))
+            .mount(&server)
+            .await;
+        let run = client.run_tools(request, &dispatcher(), 1).await.unwrap();
+        assert!(!run.completed);
+        assert_eq!(run.iterations, 1);
+        assert_eq!(run.history.len(), 2);
+    }
+
+    #[test]
+    fn test_dispatch_errors() {
+        let dispatcher = dispatcher();
+        let unknown: Value =
+            serde_json::from_str(&dispatcher.call(&call("c", "missing", None))).unwrap();
+        assert_eq!(unknown["error"], "Unknown function: missing");
+
+        let invalid: Value =
+            serde_json::from_str(&dispatcher.call(&call("c", "add", Some("{oops")))).unwrap();
+        assert!(
+            invalid["error"]
+                .as_str()
+                .unwrap()
+                .starts_with("Invalid arguments for add")
+        );
+
+        assert!(
+            dispatcher
+                .dispatch(&ChatMessage::assistant("hi"))
+                .is_empty()
+        );
+        assert!(dispatcher.handles("ping"));
+        assert!(!dispatcher.handles("missing"));
+    }
+}
@@ -0,0 +1,332 @@
+//! Client-side validation of requests before they are sent
+
+use std::collections::HashSet;
+
+use serde_json::Value;
+
+use crate::{Error, Result, chat_message::Role, models::ChatCompletionRequest};
+
+/// How strictly [`ChatCompletionRequest::validate_with`] checks the order of message roles
+///
+/// Not every model cares about ordering, so the check is opt-in.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum RoleOrdering {
+    /// Accept messages in any order
+    #[default]
+    Any,
+    /// System messages must come before all other messages
+    SystemFirst,
+    /// System messages first, and no two user messages without an assistant turn between them
+    Alternating,
+}
+
+impl ChatCompletionRequest {
+    /// Check the request for mistakes the API would otherwise reject opaquely
+    ///
+    /// Verifies that every tool message carries a `tool_call_id` matching a
+    /// tool call issued by a preceding assistant message, and that a
+    /// `json_schema` response format has a usable schema: a JSON object with
+    /// a `type`, where strict mode also requires `additionalProperties: false`
+    /// on every object in it.
+    ///
+    /// # Example
+    /// ```rust
+    /// use cerebras_rs::{ChatCompletionRequest, ChatMessage, Error, ModelIdentifier};
+    ///
+    /// let request = ChatCompletionRequest::builder(ModelIdentifier::Llama3Period18b)
+    ///     .user_message("What's the weather?")
+    ///     .message(ChatMessage::tool("Sunny", "call_unknown"))
+    ///     .build();
+    ///
+    /// assert!(matches!(request.validate(), Err(Error::InvalidRequest(_))));
+    /// ```
+    pub fn validate(&self) -> Result<()> {
+        let mut tool_call_ids = HashSet::new();
+
+        for (index, message) in self.messages.iter().enumerate() {
+            match message.role {
+                Role::Assistant => {
+                    tool_call_ids.extend(
+                        message
+                            .tool_calls
+                            .iter()
+                            .flatten()
+                            .filter_map(|call| call.id.as_deref()),
+                    );
+                }
+                Role::Tool => {
+                    let id = message.tool_call_id.as_deref().ok_or_else(|| {
+                        Error::InvalidRequest(format!(
+                            "Tool message at index {} has no tool_call_id",
+                            index
+                        ))
+                    })?;
+                    if !tool_call_ids.contains(id) {
+                        return Err(Error::InvalidRequest(format!(
+                            "Tool message at index {} references unknown tool_call_id '{}'",
+                            index, id
+                        )));
+                    }
+                }
+                _ => {}
+            }
+        }
+
+        if let Some(json_schema) = self
+            .response_format
+            .as_ref()
+            .and_then(|format| format.json_schema.as_ref())
+        {
+            let schema = json_schema.schema.as_ref().ok_or_else(|| {
+                Error::InvalidRequest("json_schema response format has no schema".into())
+            })?;
+            validate_schema(schema, json_schema.strict == Some(true))?;
+        }
+
+        Ok(())
+    }
+
+    /// Run [`validate`](Self::validate) and also check the order of message roles
+    ///
+    /// # Example
+    /// ```rust
+    /// use cerebras_rs::{ChatCompletionRequest, Error, ModelIdentifier, RoleOrdering};
+    ///
+    /// let request = ChatCompletionRequest::builder(ModelIdentifier::Llama3Period18b)
+    ///     .user_message("Hello")
+    ///     .user_message("Are you there?")
+    ///     .build();
+    ///
+    /// assert!(request.validate_with(RoleOrdering::SystemFirst).is_ok());
+    /// assert!(matches!(
+    ///     request.validate_with(RoleOrdering::Alternating),
+    ///     Err(Error::InvalidRequest(_))
+    /// ));
+    /// ```
+    pub fn validate_with(&self, ordering: RoleOrdering) -> Result<()> {
+        self.validate()?;
+        if ordering == RoleOrdering::Any {
+            return Ok(());
+        }
+
+        let mut seen_non_system = false;
+        let mut previous_user = None;
+        for (index, message) in self.messages.iter().enumerate() {
+            match message.role {
+                Role::System if seen_non_system => {
+                    return Err(Error::InvalidRequest(format!(
+                        "System message at index {} follows user or assistant messages",
+                        index
+                    )));
+                }
+                Role::System => {}
+                Role::User => {
+                    if ordering == RoleOrdering::Alternating
+                        && let Some(previous) = previous_user
+                    {
+                        return Err(Error::InvalidRequest(format!(
+                            "User messages at index {} and {} have no assistant turn between them",
+                            previous, index
+                        )));
+                    }
+                    previous_user = Some(index);
+                }
+                Role::Assistant => previous_user = None,
+                Role::Tool => {}
+            }
+            seen_non_system |= message.role != Role::System;
+        }
+
+        Ok(())
+    }
+}
+
+/// Check a structured output schema for the mistakes servers reject most often
+fn validate_schema(schema: &Value, strict: bool) -> Result<()> {
+    let Some(root) = schema.as_object() else {
+        return Err(Error::InvalidRequest(
+            "json_schema schema must be a JSON object, e.g. {\"type\": \"object\", ...}".into(),
+        ));
+    };
+    if !root.contains_key("type") {
+        return Err(Error::InvalidRequest(
+            "json_schema schema has no \"type\"; set it on the root, e.g. \"type\": \"object\""
+                .into(),
+        ));
+    }
+    if strict {
+        check_closed_objects(schema, "#")?;
+    }
+    Ok(())
+}
+
+/// Require `additionalProperties: false` on every object schema within `schema`, as strict mode does
+fn check_closed_objects(schema: &Value, pointer: &str) -> Result<()> {
+    let Some(object) = schema.as_object() else {
+        return Ok(());
+    };
+    let is_object = object.get("type").is_some_and(|ty| match ty {
+        Value::String(ty) => ty == "object",
+        Value::Array(types) => types.iter().any(|ty| ty == "object"),
+        _ => false,
+    }) || object.contains_key("properties");
+    if is_object && object.get("additionalProperties") != Some(&Value::Bool(false)) {
+        return Err(Error::InvalidRequest(format!(
+            "strict json_schema requires \"additionalProperties\": false on every object; missing at {}",
+            pointer
+        )));
+    }
+
+    for keyword in ["properties", "$defs", "definitions"] {
+        if let Some(Value::Object(children)) = object.get(keyword) {
+            for (name, child) in children {
+                check_closed_objects(child, &format!("{}/{}/{}", pointer, keyword, name))?;
+            }
+        }
+    }
+    for keyword in ["anyOf", "oneOf", "allOf", "prefixItems"] {
+        if let Some(Value::Array(children)) = object.get(keyword) {
+            for (index, child) in children.iter().enumerate() {
+                check_closed_objects(child, &format!("{}/{}/{}", pointer, keyword, index))?;
+            }
+        }
+    }
+    if let Some(items) = object.get("items") {
+        check_closed_objects(items, &format!("{}/items", pointer))?;
+    }
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::models::{ChatMessage, FunctionCall, ModelIdentifier};
+
+    use super::*;
+
+    fn assistant_calling(id: &str) -> ChatMessage {
+        let mut message = ChatMessage::assistant("");
+        message.tool_calls = Some(vec![FunctionCall {
+            id: Some(id.to_string()),
+            name: Some("get_weather".to_string()),
+            arguments: Some("{}".to_string()),
+        }]);
+        message
+    }
+
+    #[test]
+    fn test_validate_matching_tool_call() {
+        let request = ChatCompletionRequest::builder(ModelIdentifier::Llama3Period18b)
+            .user_message("What's the weather?")
+            .message(assistant_calling("call_1"))
+            .message(ChatMessage::tool("Sunny", "call_1"))
+            .build();
+
+        assert!(request.validate().is_ok());
+    }
+
+    #[test]
+    fn test_validate_unknown_tool_call_id() {
+        let request = ChatCompletionRequest::builder(ModelIdentifier::Llama3Period18b)
+            .user_message("What's the weather?")
+            .message(assistant_calling("call_1"))
+            .message(ChatMessage::tool("Sunny", "call_2"))
+            .build();
+
+        match request.validate() {
+            Err(Error::InvalidRequest(message)) => assert!(message.contains("call_2")),
+            other => panic!("Expected InvalidRequest, got {:?}", other),
+        }
+    }
+
+    #[test]
+    fn test_validate_tool_result_before_call() {
+        let request = ChatCompletionRequest::builder(ModelIdentifier::Llama3Period18b)
+            .message(ChatMessage::tool("Sunny", "call_1"))
+            .message(assistant_calling("call_1"))
+            .build();
+
+        assert!(request.validate().is_err());
+    }
+
+    #[test]
+    fn test_validate_role_ordering() {
+        let late_system = ChatCompletionRequest::builder(ModelIdentifier::Llama3Period18b)
+            .user_message("Hi")
+            .system_message("Be brief")
+            .build();
+        assert!(late_system.validate_with(RoleOrdering::Any).is_ok());
+        match late_system.validate_with(RoleOrdering::SystemFirst) {
+            Err(Error::InvalidRequest(message)) => assert!(message.contains("index 1")),
+            other => panic!("Expected InvalidRequest, got {:?}", other),
+        }
+
+        let alternating = ChatCompletionRequest::builder(ModelIdentifier::Llama3Period18b)
+            .system_message("Be brief")
+            .user_message("What's the weather?")
+            .message(assistant_calling("call_1"))
+            .message(ChatMessage::tool("Sunny", "call_1"))
+            .user_message("And tomorrow?")
+            .build();
+        assert!(alternating.validate_with(RoleOrdering::Alternating).is_ok());
+
+        let consecutive = ChatCompletionRequest::builder(ModelIdentifier::Llama3Period18b)
+            .user_message("Hello")
+            .user_message("Are you there?")
+            .build();
+        assert!(consecutive.validate_with(RoleOrdering::SystemFirst).is_ok());
+        match consecutive.validate_with(RoleOrdering::Alternating) {
+            Err(Error::InvalidRequest(message)) => assert!(message.contains("0 and 1")),
+            other => panic!("Expected InvalidRequest, got {:?}", other),
+        }
+    }
+
+    #[test]
+    fn test_validate_json_schema() {
+        let request = |schema: Value, strict: bool| {
+            ChatCompletionRequest::builder(ModelIdentifier::Llama3Period18b)
+                .user_message("Describe a person")
+                .json_schema("person", schema, strict)
+                .build()
+        };
+        let person = serde_json::json!({
+            "type": "object",
+            "properties": {
+                "name": { "type": "string" },
+                "pets": {
+                    "type": "array",
+                    "items": {
+                        "type": "object",
+                        "properties": { "kind": { "type": "string" } },
+                        "additionalProperties": false
+                    }
+                }
+            },
+            "additionalProperties": false
+        });
+        assert!(request(person.clone(), true).validate().is_ok());
+
+        let mut open = person.clone();
+        open["properties"]["pets"]["items"]
+            .as_object_mut()
+            .unwrap()
+            .remove("additionalProperties");
+        assert!(request(open.clone(), false).validate().is_ok());
+        match request(open, true).validate() {
+            Err(Error::InvalidRequest(message)) => {
+                assert!(message.contains("#/properties/pets/items"), "{}", message)
+            }
+            other => panic!("Expected InvalidRequest, got {:?}", other),
+        }
+
+        assert!(
+            request(serde_json::json!("object"), false)
+                .validate()
+                .is_err()
+        );
+        assert!(
+            request(serde_json::json!({ "properties": {} }), false)
+                .validate()
+                .is_err()
+        );
+    }
+}
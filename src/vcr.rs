@@ -0,0 +1,598 @@
+//! Recording and replaying HTTP interactions, so tests can run without a live API key
+//!
+//! A [`Vcr`] runs a small HTTP server on localhost and hands out clients
+//! pointed at it. In [`VcrMode::Record`] it forwards each request to the real
+//! API and appends the exchange to a JSON cassette file; in
+//! [`VcrMode::Playback`] it answers from the cassette without touching the
+//! network. Streaming responses are recorded whole and replayed in one piece.
+//!
+//! # Example
+//! ```rust,no_run
+//! use cerebras_rs::vcr::Vcr;
+//! use cerebras_rs::{ChatCompletionRequest, ModelIdentifier};
+//!
+//! # async fn example() -> Result<(), Box<dyn std::error::Error>> {
+//! // CEREBRAS_VCR=record records against the API; anything else plays back
+//! let vcr = Vcr::from_env("tests/cassettes/chat.json").await?;
+//! let client = vcr.client()?;
+//!
+//! let request = ChatCompletionRequest::builder(ModelIdentifier::Llama3Period18b)
+//!     .user_message("Hello")
+//!     .temperature(0.0)
+//!     .build();
+//! println!("{}", client.chat_completion(request).await?.first_text()?);
+//! # Ok(())
+//! # }
+//! ```
+
+use std::net::SocketAddr;
+use std::path::{Path, PathBuf};
+use std::sync::{Arc, Mutex};
+
+use serde::{Deserialize, Serialize};
+use tokio::io::{AsyncBufReadExt, AsyncReadExt, AsyncWriteExt, BufReader};
+use tokio::net::{TcpListener, TcpStream};
+
+use crate::{Client, Configuration, Error, Result};
+
+/// Environment variable selecting the mode for [`Vcr::from_env`]
+pub const VCR_MODE_ENV: &str = "CEREBRAS_VCR";
+
+/// Whether a [`Vcr`] records new interactions or replays recorded ones
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum VcrMode {
+    /// Forward requests to the API and save each exchange to the cassette
+    Record,
+    /// Answer requests from the cassette, without network access
+    #[default]
+    Playback,
+}
+
+impl VcrMode {
+    /// The mode named by `CEREBRAS_VCR`: `record`, or playback for anything else
+    pub fn from_env() -> Self {
+        match std::env::var(VCR_MODE_ENV) {
+            Ok(mode) if mode.eq_ignore_ascii_case("record") => VcrMode::Record,
+            _ => VcrMode::Playback,
+        }
+    }
+}
+
+/// One recorded request and the response it got
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct Interaction {
+    /// HTTP method, e.g. `POST`
+    pub method: String,
+    /// Path and query below the base URL, e.g. `/chat/completions`
+    pub path: String,
+    /// The JSON request body, if there was one
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub request: Option<serde_json::Value>,
+    /// Response status code
+    pub status: u16,
+    /// Response `Content-Type`
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub content_type: Option<String>,
+    /// The full response body
+    pub body: String,
+}
+
+/// The interactions stored in a cassette file
+#[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize)]
+pub struct Cassette {
+    /// Interactions in the order they were recorded
+    pub interactions: Vec<Interaction>,
+}
+
+impl Cassette {
+    /// Read a cassette, or start an empty one if the file doesn't exist
+    pub fn load(path: impl AsRef<Path>) -> Result<Self> {
+        match std::fs::read_to_string(path.as_ref()) {
+            Ok(json) => Ok(serde_json::from_str(&json)?),
+            Err(e) if e.kind() == std::io::ErrorKind::NotFound => Ok(Self::default()),
+            Err(e) => Err(Error::Configuration(format!(
+                "Failed to read cassette {}: {}",
+                path.as_ref().display(),
+                e
+            ))),
+        }
+    }
+
+    /// Write the cassette as pretty-printed JSON, creating parent directories
+    pub fn save(&self, path: impl AsRef<Path>) -> Result<()> {
+        let path = path.as_ref();
+        let write = || -> std::io::Result<()> {
+            if let Some(parent) = path.parent() {
+                std::fs::create_dir_all(parent)?;
+            }
+            std::fs::write(path, serde_json::to_string_pretty(self)?)
+        };
+        write().map_err(|e| {
+            Error::Configuration(format!(
+                "Failed to write cassette {}: {}",
+                path.display(),
+                e
+            ))
+        })
+    }
+}
+
+/// State shared by the server's connection tasks
+struct Tape {
+    mode: VcrMode,
+    path: PathBuf,
+    upstream: String,
+    http: reqwest::Client,
+    cassette: Mutex<Cassette>,
+    /// Indices of the interactions already played back
+    played: Mutex<Vec<bool>>,
+}
+
+/// A local server that records or replays API interactions
+///
+/// Playback matches requests by method, path and JSON body, serving each
+/// recorded interaction once in order and then repeating the last match.
+/// Requests with no recorded match get a `404` describing the request.
+/// Recording appends to an existing cassette, saving after every exchange.
+/// The server stops when the `Vcr` is dropped.
+#[derive(Debug)]
+pub struct Vcr {
+    mode: VcrMode,
+    addr: SocketAddr,
+    tape: Arc<Tape>,
+    server: tokio::task::JoinHandle<()>,
+}
+
+impl Vcr {
+    /// Start a server for the cassette at `path`, recording from `upstream` in record mode
+    ///
+    /// `upstream` is the API base URL requests are forwarded to, e.g.
+    /// `https://api.cerebras.ai/v1`; it's unused in playback mode.
+    pub async fn start(
+        path: impl Into<PathBuf>,
+        mode: VcrMode,
+        upstream: impl Into<String>,
+    ) -> Result<Self> {
+        let path = path.into();
+        let cassette = Cassette::load(&path)?;
+        let played = vec![false; cassette.interactions.len()];
+        let tape = Arc::new(Tape {
+            mode,
+            path,
+            upstream: upstream.into().trim_end_matches('/').to_string(),
+            http: reqwest::Client::new(),
+            cassette: Mutex::new(cassette),
+            played: Mutex::new(played),
+        });
+
+        let listener = TcpListener::bind(("127.0.0.1", 0))
+            .await
+            .map_err(|e| Error::Configuration(format!("Failed to start VCR server: {}", e)))?;
+        let addr = listener
+            .local_addr()
+            .map_err(|e| Error::Configuration(format!("Failed to start VCR server: {}", e)))?;
+        let server = tokio::spawn({
+            let tape = Arc::clone(&tape);
+            async move {
+                while let Ok((socket, _)) = listener.accept().await {
+                    tokio::spawn(serve(socket, Arc::clone(&tape)));
+                }
+            }
+        });
+        Ok(Self {
+            mode,
+            addr,
+            tape,
+            server,
+        })
+    }
+
+    /// Start a server for the cassette at `path` in the mode named by `CEREBRAS_VCR`
+    ///
+    /// Records from the default API base URL.
+    pub async fn from_env(path: impl Into<PathBuf>) -> Result<Self> {
+        Self::start(
+            path,
+            VcrMode::from_env(),
+            Configuration::default().base_path,
+        )
+        .await
+    }
+
+    /// The mode the server runs in
+    pub fn mode(&self) -> VcrMode {
+        self.mode
+    }
+
+    /// Base URL of the local server
+    pub fn base_url(&self) -> String {
+        format!("http://{}", self.addr)
+    }
+
+    /// A client that sends its requests through the server
+    ///
+    /// In record mode the API key is read from `CEREBRAS_API_KEY`, as in
+    /// [`Client::from_env`]; playback needs no key.
+    pub fn client(&self) -> Result<Client> {
+        let client = match self.mode {
+            VcrMode::Record => Client::from_env()?,
+            VcrMode::Playback => Client::new("vcr-playback"),
+        };
+        Ok(client.with_base_url(self.base_url()))
+    }
+
+    /// A copy of the interactions recorded or loaded so far
+    pub fn cassette(&self) -> Cassette {
+        self.tape
+            .cassette
+            .lock()
+            .map(|cassette| cassette.clone())
+            .unwrap_or_default()
+    }
+}
+
+impl Drop for Vcr {
+    fn drop(&mut self) {
+        self.server.abort();
+    }
+}
+
+impl std::fmt::Debug for Tape {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.debug_struct("Tape")
+            .field("mode", &self.mode)
+            .field("path", &self.path)
+            .finish()
+    }
+}
+
+/// A request as read off the socket
+struct RawRequest {
+    method: String,
+    path: String,
+    /// End-to-end headers to forward upstream when recording
+    headers: Vec<(String, String)>,
+    body: Vec<u8>,
+}
+
+/// Headers that describe the client's connection to this server, not the request itself
+///
+/// `accept-encoding` is left to the recording client, so response bodies are
+/// stored decoded.
+const UNFORWARDED_HEADERS: &[&str] = &[
+    "accept-encoding",
+    "connection",
+    "content-length",
+    "host",
+    "keep-alive",
+    "proxy-authenticate",
+    "proxy-authorization",
+    "proxy-connection",
+    "te",
+    "trailer",
+    "transfer-encoding",
+    "upgrade",
+];
+
+/// Answer one connection, which carries a single request
+async fn serve(socket: TcpStream, tape: Arc<Tape>) {
+    let mut socket = BufReader::new(socket);
+    let Ok(request) = read_request(&mut socket).await else {
+        return;
+    };
+    let interaction = match tape.mode {
+        VcrMode::Record => record(&tape, request).await,
+        VcrMode::Playback => play(&tape, &request),
+    };
+
+    let reason = reqwest::StatusCode::from_u16(interaction.status)
+        .ok()
+        .and_then(|status| status.canonical_reason())
+        .unwrap_or("Unknown");
+    let mut head = format!(
+        "HTTP/1.1 {} {}\r\ncontent-length: {}\r\nconnection: close\r\n",
+        interaction.status,
+        reason,
+        interaction.body.len()
+    );
+    if let Some(content_type) = &interaction.content_type {
+        head.push_str(&format!("content-type: {}\r\n", content_type));
+    }
+    head.push_str("\r\n");
+    let socket = socket.get_mut();
+    let _ = socket.write_all(head.as_bytes()).await;
+    let _ = socket.write_all(interaction.body.as_bytes()).await;
+    let _ = socket.shutdown().await;
+}
+
+/// Read the request line, the headers, and the body
+async fn read_request(socket: &mut BufReader<TcpStream>) -> std::io::Result<RawRequest> {
+    let mut line = String::new();
+    socket.read_line(&mut line).await?;
+    let mut parts = line.split_whitespace();
+    let method = parts.next().unwrap_or_default().to_string();
+    let path = parts.next().unwrap_or("/").to_string();
+
+    let mut content_length = 0;
+    let mut headers = Vec::new();
+    loop {
+        line.clear();
+        if socket.read_line(&mut line).await? == 0 || line.trim().is_empty() {
+            break;
+        }
+        if let Some((name, value)) = line.split_once(':') {
+            let value = value.trim();
+            let name = name.trim();
+            if name.eq_ignore_ascii_case("content-length") {
+                content_length = value.parse().unwrap_or(0);
+            }
+            if !UNFORWARDED_HEADERS
+                .iter()
+                .any(|unforwarded| name.eq_ignore_ascii_case(unforwarded))
+            {
+                headers.push((name.to_string(), value.to_string()));
+            }
+        }
+    }
+
+    let mut body = vec![0; content_length];
+    socket.read_exact(&mut body).await?;
+    Ok(RawRequest {
+        method,
+        path,
+        headers,
+        body,
+    })
+}
+
+/// Forward a request upstream and append the exchange to the cassette
+async fn record(tape: &Tape, request: RawRequest) -> Interaction {
+    let method = reqwest::Method::from_bytes(request.method.as_bytes()).unwrap_or_default();
+    let mut upstream = tape
+        .http
+        .request(method, format!("{}{}", tape.upstream, request.path));
+    // Forwarded as sent, so any auth header the client is configured with works
+    for (name, value) in &request.headers {
+        upstream = upstream.header(name.as_str(), value.as_str());
+    }
+    if !request.body.is_empty() {
+        upstream = upstream.body(request.body.clone());
+    }
+
+    let (status, content_type, body) = match upstream.send().await {
+        Ok(response) => {
+            let status = response.status().as_u16();
+            let content_type = response
+                .headers()
+                .get(reqwest::header::CONTENT_TYPE)
+                .and_then(|value| value.to_str().ok())
+                .map(str::to_string);
+            (
+                status,
+                content_type,
+                response.text().await.unwrap_or_default(),
+            )
+        }
+        Err(e) => {
+            // Not recorded, so a retry can still succeed
+            return error_interaction(502, &format!("VCR upstream failed: {}", e));
+        }
+    };
+    let interaction = Interaction {
+        method: request.method,
+        path: request.path,
+        request: serde_json::from_slice(&request.body).ok(),
+        status,
+        content_type,
+        body,
+    };
+
+    if let Ok(mut cassette) = tape.cassette.lock() {
+        cassette.interactions.push(interaction.clone());
+        if let Err(e) = cassette.save(&tape.path) {
+            return error_interaction(500, &e.to_string());
+        }
+    }
+    interaction
+}
+
+/// Find the recorded response for a request
+fn play(tape: &Tape, request: &RawRequest) -> Interaction {
+    let body: Option<serde_json::Value> = serde_json::from_slice(&request.body).ok();
+    let (Ok(cassette), Ok(mut played)) = (tape.cassette.lock(), tape.played.lock()) else {
+        return error_interaction(500, "VCR state is poisoned");
+    };
+    let matches: Vec<usize> = cassette
+        .interactions
+        .iter()
+        .enumerate()
+        .filter(|(_, recorded)| {
+            recorded.method == request.method
+                && recorded.path == request.path
+                && recorded.request == body
+        })
+        .map(|(index, _)| index)
+        .collect();
+    let Some(&index) = matches
+        .iter()
+        .find(|&&index| !played[index])
+        .or(matches.last())
+    else {
+        let message = format!(
+            "No recorded interaction for {} {} in {}",
+            request.method,
+            request.path,
+            tape.path.display()
+        );
+        return error_interaction(404, &message);
+    };
+    played[index] = true;
+    cassette.interactions[index].clone()
+}
+
+/// A JSON error response, never recorded, that the client maps to an [`Error`]
+fn error_interaction(status: u16, message: &str) -> Interaction {
+    Interaction {
+        method: String::new(),
+        path: String::new(),
+        request: None,
+        status,
+        content_type: Some("application/json".to_string()),
+        body: serde_json::json!({ "message": message }).to_string(),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{ChatCompletionRequest, ModelIdentifier};
+    use futures_util::StreamExt;
+    use wiremock::matchers::{body_partial_json, header, method, path};
+    use wiremock::{Mock, MockServer, ResponseTemplate};
+
+    fn cassette_path(name: &str) -> PathBuf {
+        std::env::temp_dir().join(format!(
+            "cerebras-vcr-{}-{}.json",
+            name,
+            uuid::Uuid::new_v4()
+        ))
+    }
+
+    fn request(content: &str) -> ChatCompletionRequest {
+        ChatCompletionRequest::builder(ModelIdentifier::Llama3Period18b)
+            .user_message(content)
+            .build()
+    }
+
+    #[tokio::test]
+    async fn test_record_then_play_back() {
+        let upstream = MockServer::start().await;
+        Mock::given(method("POST"))
+            .and(path("/chat/completions"))
+            .and(header("authorization", "Bearer test-key"))
+            .and(body_partial_json(serde_json::json!({ "stream": true })))
+            .respond_with(
+                ResponseTemplate::new(200)
+                    .insert_header("content-type", "text/event-stream")
+                    .set_body_string(
+                        "data: {\"choices\": [{\"index\": 0, \"delta\": {\"content\": \"Hi\"}}]}\n\ndata: [DONE]\n\n",
+                    ),
+            )
+            .with_priority(1)
+            .mount(&upstream)
+            .await;
+        Mock::given(method("POST"))
+            .and(path("/chat/completions"))
+            .respond_with(ResponseTemplate::new(200).set_body_json(serde_json::json!({
+                "choices": [{ "index": 0, "message": { "role": "assistant", "content": "Hello" } }]
+            })))
+            .mount(&upstream)
+            .await;
+
+        let path = cassette_path("round-trip");
+        let vcr = Vcr::start(&path, VcrMode::Record, upstream.uri())
+            .await
+            .unwrap();
+        let client = Client::new("test-key").with_base_url(vcr.base_url());
+        let reply = client.chat_completion(request("Hello")).await.unwrap();
+        assert_eq!(reply.first_text().unwrap(), "Hello");
+        let streamed = client
+            .chat_completion_stream(request("Stream"))
+            .await
+            .unwrap()
+            .collect()
+            .await
+            .unwrap();
+        assert_eq!(vcr.cassette().interactions.len(), 2);
+        drop(vcr);
+
+        // Playback serves the same answers with the upstream gone
+        drop(upstream);
+        let vcr = Vcr::start(&path, VcrMode::Playback, "http://unused")
+            .await
+            .unwrap();
+        let client = vcr.client().unwrap();
+        let replayed = client.chat_completion(request("Hello")).await.unwrap();
+        assert_eq!(replayed, reply);
+        let replayed = client
+            .chat_completion_stream(request("Stream"))
+            .await
+            .unwrap()
+            .collect()
+            .await
+            .unwrap();
+        assert_eq!(replayed.choices, streamed.choices);
+
+        let err = client
+            .chat_completion(request("Unrecorded"))
+            .await
+            .unwrap_err();
+        assert!(
+            matches!(&err, Error::NotFound(message) if message.contains("POST /chat/completions")),
+            "{:?}",
+            err
+        );
+        let _ = std::fs::remove_file(&path);
+    }
+
+    #[tokio::test]
+    async fn test_record_forwards_custom_auth_header() {
+        let upstream = MockServer::start().await;
+        Mock::given(method("POST"))
+            .and(path("/chat/completions"))
+            .and(header("api-key", "test-key"))
+            .respond_with(ResponseTemplate::new(200).set_body_json(serde_json::json!({
+                "choices": [{ "index": 0, "message": { "role": "assistant", "content": "Hello" } }]
+            })))
+            .mount(&upstream)
+            .await;
+
+        let path = cassette_path("auth-header");
+        let vcr = Vcr::start(&path, VcrMode::Record, upstream.uri())
+            .await
+            .unwrap();
+        let client = Client::new("test-key")
+            .with_auth_header("api-key")
+            .with_base_url(vcr.base_url());
+        let reply = client.chat_completion(request("Hello")).await.unwrap();
+        assert_eq!(reply.first_text().unwrap(), "Hello");
+        assert_eq!(vcr.cassette().interactions[0].status, 200);
+        let _ = std::fs::remove_file(&path);
+    }
+
+    #[tokio::test]
+    async fn test_playback_order_and_missing_cassette() {
+        let path = cassette_path("order");
+        let answer = |content: &str| Interaction {
+            method: "POST".into(),
+            path: "/chat/completions".into(),
+            request: Some(serde_json::to_value(request("Again")).unwrap()),
+            status: 200,
+            content_type: Some("application/json".into()),
+            body: serde_json::json!({
+                "choices": [{ "index": 0, "message": { "role": "assistant", "content": content } }]
+            })
+            .to_string(),
+        };
+        Cassette {
+            interactions: vec![answer("first"), answer("second")],
+        }
+        .save(&path)
+        .unwrap();
+
+        let vcr = Vcr::start(&path, VcrMode::Playback, "http://unused")
+            .await
+            .unwrap();
+        let client = vcr.client().unwrap();
+        let mut replies = Vec::new();
+        for _ in 0..3 {
+            let response = client.chat_completion(request("Again")).await.unwrap();
+            replies.push(response.first_text().unwrap().to_string());
+        }
+        assert_eq!(replies, vec!["first", "second", "second"]);
+        let _ = std::fs::remove_file(&path);
+
+        assert_eq!(Cassette::load(&path).unwrap(), Cassette::default());
+        assert_eq!(VcrMode::default(), VcrMode::Playback);
+    }
+}
@@ -1,6 +1,7 @@
 // Auto-generated integration tests for the Cerebras API client
 // Run 'cargo test --test integration' to execute these tests
-// To run tests with API calls, set CEREBRAS_API_KEY environment variable
+// To run tests with API calls, set CEREBRAS_API_KEY environment variable,
+// or enable the `vcr` feature to play them back from tests/cassettes
 
 #![cfg(test)]
 #![allow(unused_imports)]
@@ -38,6 +39,32 @@ mod integration_tests {
             .expect("Failed to create client from CEREBRAS_API_KEY environment variable")
     }
 
+    // Helper to create a client for tests that call the API
+    //
+    // With the `vcr` feature, requests are played back from
+    // tests/cassettes/<name>.json, so no API key is needed. Run with
+    // CEREBRAS_VCR=record and CEREBRAS_API_KEY set to re-record a cassette
+    // against the live API. Without the feature, the live API is called and
+    // the test is skipped when no key is set.
+    #[cfg(feature = "vcr")]
+    async fn api_client(name: &str) -> Option<(cerebras_rs::vcr::Vcr, Client)> {
+        let path = format!(
+            "{}/tests/cassettes/{}.json",
+            env!("CARGO_MANIFEST_DIR"),
+            name
+        );
+        let vcr = cerebras_rs::vcr::Vcr::from_env(path)
+            .await
+            .expect("Failed to start the VCR server");
+        let client = vcr.client().expect("Failed to create a VCR client");
+        Some((vcr, client))
+    }
+
+    #[cfg(not(feature = "vcr"))]
+    async fn api_client(_name: &str) -> Option<((), Client)> {
+        requires_api_key().then(|| ((), create_test_client()))
+    }
+
     // Helper function to create a test chat completion request
     fn create_test_chat_request() -> ChatCompletionRequest {
         ChatCompletionRequest {
@@ -62,11 +89,9 @@ mod integration_tests {
     // Test listing available models
     #[tokio::test]
     async fn test_list_models() {
-        if !requires_api_key() {
+        let Some((_vcr, client)) = api_client("list_models").await else {
             return;
-        }
-
-        let client = create_test_client();
+        };
         let result = client.list_models().await;
 
         assert!(result.is_ok(), "Failed to list models: {:?}", result.err());
@@ -94,11 +119,9 @@ mod integration_tests {
     // Test retrieving a specific model
     #[tokio::test]
     async fn test_get_model() {
-        if !requires_api_key() {
+        let Some((_vcr, client)) = api_client("get_model").await else {
             return;
-        }
-
-        let client = create_test_client();
+        };
         let result = client.get_model(ModelIdentifier::Llama3Period18b).await;
 
         assert!(result.is_ok(), "Failed to get model: {:?}", result.err());
@@ -119,11 +142,9 @@ mod integration_tests {
     // Test basic chat completion
     #[tokio::test]
     async fn test_chat_completion_basic() {
-        if !requires_api_key() {
+        let Some((_vcr, client)) = api_client("chat_completion_basic").await else {
             return;
-        }
-
-        let client = create_test_client();
+        };
         let request = ChatCompletionRequest {
             model: ModelIdentifier::Llama3Period18b,
             messages: vec![
@@ -166,11 +187,9 @@ mod integration_tests {
     // Test chat completion with multiple messages
     #[tokio::test]
     async fn test_chat_completion_conversation() {
-        if !requires_api_key() {
+        let Some((_vcr, client)) = api_client("chat_completion_conversation").await else {
             return;
-        }
-
-        let client = create_test_client();
+        };
         let request = ChatCompletionRequest {
             model: ModelIdentifier::Llama3Period18b,
             messages: vec![
@@ -207,11 +226,9 @@ mod integration_tests {
     // Test chat completion with streaming
     #[tokio::test]
     async fn test_chat_completion_streaming() {
-        if !requires_api_key() {
+        let Some((_vcr, client)) = api_client("chat_completion_streaming").await else {
             return;
-        }
-
-        let client = create_test_client();
+        };
         let request = ChatCompletionRequest {
             model: ModelIdentifier::Llama3Period18b,
             messages: vec![
@@ -261,11 +278,9 @@ mod integration_tests {
     // Test text completion
     #[tokio::test]
     async fn test_completion_basic() {
-        if !requires_api_key() {
+        let Some((_vcr, client)) = api_client("completion_basic").await else {
             return;
-        }
-
-        let client = create_test_client();
+        };
         let request = CompletionRequest {
             model: ModelIdentifier::Llama3Period18b,
             prompt: cerebras_rs::models::Prompt::String("The capital of France is".to_string()),
@@ -300,11 +315,9 @@ mod integration_tests {
     // Test completion with streaming
     #[tokio::test]
     async fn test_completion_streaming() {
-        if !requires_api_key() {
+        let Some((_vcr, client)) = api_client("completion_streaming").await else {
             return;
-        }
-
-        let client = create_test_client();
+        };
         let request = CompletionRequest {
             model: ModelIdentifier::Llama3Period18b,
             prompt: cerebras_rs::models::Prompt::String("Once upon a time".to_string()),
@@ -349,11 +362,9 @@ mod integration_tests {
     // Test error handling - invalid model
     #[tokio::test]
     async fn test_error_invalid_model() {
-        if !requires_api_key() {
+        let Some((_vcr, client)) = api_client("error_invalid_model").await else {
             return;
-        }
-
-        let client = create_test_client();
+        };
         // Use a non-existent model by using a different valid variant
         // This will still test error handling as the API might reject certain models
         let request = ChatCompletionRequest {
@@ -373,11 +384,9 @@ mod integration_tests {
     // Test with different temperature settings
     #[tokio::test]
     async fn test_temperature_variation() {
-        if !requires_api_key() {
+        let Some((_vcr, client)) = api_client("temperature_variation").await else {
             return;
-        }
-
-        let client = create_test_client();
+        };
 
         // Test with temperature 0 (deterministic)
         let request_deterministic = ChatCompletionRequest {
@@ -422,11 +431,9 @@ mod integration_tests {
     // Test max_tokens limit
     #[tokio::test]
     async fn test_max_tokens_limit() {
-        if !requires_api_key() {
+        let Some((_vcr, client)) = api_client("max_tokens_limit").await else {
             return;
-        }
-
-        let client = create_test_client();
+        };
         let request = ChatCompletionRequest {
             model: ModelIdentifier::Llama3Period18b,
             messages: vec![ChatMessage::user(
@@ -454,6 +461,54 @@ mod integration_tests {
         );
     }
 
+    // Test completion logprobs
+    #[tokio::test]
+    async fn test_completion_logprobs() {
+        let Some((_vcr, client)) = api_client("completion_logprobs").await else {
+            return;
+        };
+        let request = CompletionRequest::builder(ModelIdentifier::Llama3Period18b)
+            .prompt("The capital of France is")
+            .max_tokens(3)
+            .logprobs(2)
+            .build();
+
+        let result = client.completion(request).await;
+        assert!(result.is_ok(), "Failed to get logprobs: {:?}", result.err());
+        let response = result.unwrap();
+        let choice = &response.choices.unwrap()[0];
+        let logprobs = choice.logprobs.as_ref().expect("No logprobs returned");
+        assert!(logprobs.tokens.as_ref().is_some_and(|t| !t.is_empty()));
+
+        println!("Token logprobs: {:?}", logprobs.token_logprobs);
+    }
+
+    // Test server-side token counting
+    #[tokio::test]
+    async fn test_count_tokens() {
+        let Some((_vcr, client)) = api_client("count_tokens").await else {
+            return;
+        };
+        let short = client
+            .count_tokens(ModelIdentifier::Llama3Period18b, "Hello")
+            .await;
+        assert!(short.is_ok(), "Failed to count tokens: {:?}", short.err());
+
+        let long = client
+            .count_tokens(
+                ModelIdentifier::Llama3Period18b,
+                "Hello, this is a considerably longer prompt with many more tokens in it.",
+            )
+            .await
+            .unwrap();
+        assert!(
+            long > short.unwrap(),
+            "Expected a longer prompt to use more tokens"
+        );
+
+        println!("Prompt tokens: {}", long);
+    }
+
     #[tokio::test]
     async fn test_assistant() {
         // Test ChatMessage::assistant helper
@@ -518,7 +573,7 @@ mod integration_tests {
 
     #[tokio::test]
     async fn test_with_base_url() {
-        let client = create_test_client().with_base_url("http://example.com".to_string());
+        let client = Client::new("test-api-key").with_base_url("http://example.com".to_string());
         assert_eq!(client.configuration().base_path, "http://example.com");
     }
 